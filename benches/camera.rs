@@ -0,0 +1,76 @@
+//! Per-frame cost of the camera math and of a whole app frame with a flying
+//! camera. Run with `cargo bench --features test_utils`.
+
+use bevy::prelude::*;
+use bevy_flying_cam::{
+   limit_pitch, rotate_cam_quat, test_utils::CameraTestApp, FlyingCamAction, FlyingCamFrameInput,
+   FlyingCamState, MovableCameraParams,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn rotation(c: &mut Criterion) {
+   let window_size = Vec2::new(1280.0, 720.0);
+   let tq = Quat::from_rotation_y(0.3) * Quat::from_rotation_x(-0.2);
+   c.bench_function("rotate_cam_quat", |b| {
+      b.iter(|| {
+         rotate_cam_quat(
+            black_box(window_size),
+            black_box(Vec2::new(12.0, -7.0)),
+            black_box(1.0),
+            black_box(tq),
+         )
+      })
+   });
+   c.bench_function("limit_pitch", |b| {
+      b.iter(|| limit_pitch(black_box(Quat::from_rotation_x(2.0))))
+   });
+}
+
+fn translation(c: &mut Criterion) {
+   let mut group = c.benchmark_group("translation");
+   for precise in [false, true] {
+      let params = MovableCameraParams {
+         precise_translation: precise,
+         ..Default::default()
+      };
+      let mut state = FlyingCamState::new(params, Transform::default());
+      let input = FlyingCamFrameInput {
+         movement: Vec3::new(0.3, 0.1, 1.0),
+         ..Default::default()
+      };
+      let name = if precise { "precise" } else { "f32" };
+      group.bench_function(name, |b| {
+         b.iter(|| state.tick(black_box(input), black_box(1.0 / 60.0)))
+      });
+   }
+   group.finish();
+}
+
+fn tick(c: &mut Criterion) {
+   let mut state = FlyingCamState::new(MovableCameraParams::default(), Transform::default());
+   let input = FlyingCamFrameInput {
+      movement: Vec3::new(0.3, 0.1, 1.0),
+      look: Vec2::new(4.0, -2.0),
+      scroll: 0.5,
+      ..Default::default()
+   };
+   c.bench_function("state_tick", |b| {
+      b.iter(|| state.tick(black_box(input), black_box(1.0 / 60.0)))
+   });
+
+   // A whole frame of the plugin with one camera flying and looking around
+   let mut test = CameraTestApp::new();
+   let camera = test.spawn_camera(Transform::default());
+   test.press(camera, FlyingCamAction::Forward);
+   test.press(camera, FlyingCamAction::Secondary);
+   test.advance(10);
+   c.bench_function("app_frame", |b| {
+      b.iter(|| {
+         test.move_mouse(Vec2::new(4.0, -2.0));
+         test.advance(1);
+      })
+   });
+}
+
+criterion_group!(benches, rotation, translation, tick);
+criterion_main!(benches);
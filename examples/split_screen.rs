@@ -0,0 +1,165 @@
+//! Split-screen for two players, each flying their own camera with their own
+//! gamepad. This version of Bevy has no viewports, so each player gets a
+//! window of their own instead of half of one.
+//!
+//! The D-pad moves, the shoulder buttons rise and sink, the right stick looks
+//! around, the analog triggers zoom, South focuses and East cycles focus.
+
+use bevy::{
+   core_pipeline::{draw_3d_graph, node, AlphaMask3d, Opaque3d, Transparent3d},
+   prelude::*,
+   render::{
+      camera::{ActiveCameras, ExtractedCameraNames},
+      render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, SlotValue},
+      render_phase::RenderPhase,
+      renderer::RenderContext,
+      RenderApp, RenderStage,
+   },
+   window::{CreateWindow, WindowId},
+};
+use bevy_flying_cam::*;
+use leafwing_input_manager::prelude::*;
+
+const SECONDARY_CAMERA_NAME: &str = "Secondary";
+const SECONDARY_PASS_DRIVER: &str = "secondary_pass_driver";
+
+fn main() {
+   let mut app = App::new();
+   app.add_plugins(DefaultPlugins)
+      .add_plugin(InputManagerPlugin::<FlyingCamAction>::default())
+      .add_plugin(FlyingCamPlugin)
+      .add_startup_system(setup_scene)
+      .add_startup_system(spawn_cameras);
+
+   // Render the second window, as in Bevy's `multiple_windows` example
+   let render_app = app.sub_app_mut(RenderApp);
+   render_app.add_system_to_stage(RenderStage::Extract, extract_secondary_camera_phases);
+   let mut graph = render_app.world.get_resource_mut::<RenderGraph>().unwrap();
+   graph.add_node(SECONDARY_PASS_DRIVER, SecondaryCameraDriver);
+   graph
+      .add_node_edge(node::MAIN_PASS_DEPENDENCIES, SECONDARY_PASS_DRIVER)
+      .unwrap();
+   app.run();
+}
+
+fn setup_scene(
+   mut commands: Commands,
+   mut meshes: ResMut<Assets<Mesh>>,
+   mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+   commands.spawn_bundle(PbrBundle {
+      mesh: meshes.add(Mesh::from(shape::Plane { size: 10.0 })),
+      material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+      ..Default::default()
+   });
+   for (x, color) in [(-2.0, Color::RED), (2.0, Color::BLUE)] {
+      commands
+         .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+            material: materials.add(color.into()),
+            transform: Transform::from_xyz(x, 0.5, 0.0),
+            ..Default::default()
+         })
+         .insert(Focusable);
+   }
+   commands.spawn_bundle(PointLightBundle {
+      transform: Transform::from_xyz(4.0, 8.0, 4.0),
+      ..Default::default()
+   });
+}
+
+fn input_map(gamepad: Gamepad) -> InputMap<FlyingCamAction> {
+   let mut input_map = InputMap::default();
+   input_map
+      .insert(FlyingCamAction::Forward, GamepadButtonType::DPadUp)
+      .insert(FlyingCamAction::Left, GamepadButtonType::DPadLeft)
+      .insert(FlyingCamAction::Back, GamepadButtonType::DPadDown)
+      .insert(FlyingCamAction::Right, GamepadButtonType::DPadRight)
+      .insert(FlyingCamAction::Up, GamepadButtonType::RightTrigger)
+      .insert(FlyingCamAction::Down, GamepadButtonType::LeftTrigger)
+      .insert(FlyingCamAction::Focus, GamepadButtonType::South)
+      .insert(FlyingCamAction::NextFocus, GamepadButtonType::East)
+      .set_gamepad(gamepad);
+   input_map
+}
+
+/// Spawns a camera rig driven entirely by `gamepad`.
+fn spawn_rig(commands: &mut Commands, camera: PerspectiveCameraBundle, gamepad: Gamepad) {
+   commands
+      .spawn_bundle(camera)
+      .insert(MovableCamera::default())
+      .insert(GamepadLook::new(gamepad))
+      .insert_bundle(InputManagerBundle {
+         action_state: ActionState::default(),
+         input_map: input_map(gamepad),
+      });
+}
+
+fn spawn_cameras(
+   mut commands: Commands,
+   mut create_window_events: EventWriter<CreateWindow>,
+   mut active_cameras: ResMut<ActiveCameras>,
+) {
+   spawn_rig(
+      &mut commands,
+      PerspectiveCameraBundle {
+         transform: Transform::from_xyz(-2.0, 3.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+         ..PerspectiveCameraBundle::new_3d()
+      },
+      Gamepad(0),
+   );
+
+   let window_id = WindowId::new();
+   create_window_events.send(CreateWindow {
+      id: window_id,
+      descriptor: WindowDescriptor {
+         width: 800.0,
+         height: 600.0,
+         title: "Player 2".to_string(),
+         ..Default::default()
+      },
+   });
+   spawn_rig(
+      &mut commands,
+      PerspectiveCameraBundle {
+         camera: Camera {
+            window: window_id,
+            name: Some(SECONDARY_CAMERA_NAME.into()),
+            ..Default::default()
+         },
+         transform: Transform::from_xyz(6.0, 4.0, 0.0).looking_at(Vec3::ZERO, Vec3::Y),
+         ..PerspectiveCameraBundle::new()
+      },
+      Gamepad(1),
+   );
+   active_cameras.add(SECONDARY_CAMERA_NAME);
+}
+
+fn extract_secondary_camera_phases(mut commands: Commands, active_cameras: Res<ActiveCameras>) {
+   if let Some(secondary) = active_cameras.get(SECONDARY_CAMERA_NAME) {
+      if let Some(entity) = secondary.entity {
+         commands.get_or_spawn(entity).insert_bundle((
+            RenderPhase::<Opaque3d>::default(),
+            RenderPhase::<AlphaMask3d>::default(),
+            RenderPhase::<Transparent3d>::default(),
+         ));
+      }
+   }
+}
+
+struct SecondaryCameraDriver;
+
+impl Node for SecondaryCameraDriver {
+   fn run(
+      &self,
+      graph: &mut RenderGraphContext,
+      _render_context: &mut RenderContext,
+      world: &World,
+   ) -> Result<(), NodeRunError> {
+      let extracted_cameras = world.get_resource::<ExtractedCameraNames>().unwrap();
+      if let Some(camera_3d) = extracted_cameras.entities.get(SECONDARY_CAMERA_NAME) {
+         graph.run_sub_graph(draw_3d_graph::NAME, vec![SlotValue::Entity(*camera_3d)])?;
+      }
+      Ok(())
+   }
+}
@@ -0,0 +1,74 @@
+//! [`CameraMode::AttachTo`]: ride along with an entity.
+
+use crate::{
+   free_rig, rig_root, rotate_cam_quat, window_has_mouse, window_size, CameraActions, CameraMode,
+   CameraMouse, FlyingCamAction, MovableCamera,
+};
+use bevy::prelude::*;
+
+/// Carries attached cameras along with their entity. Dragging with
+/// `Secondary` looks around on top of the entity's orientation and `Detach`
+/// drops back to free flight at the current pose.
+pub fn attach_camera(
+   windows: Res<Windows>,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   q_targets: Query<&GlobalTransform>,
+   mut q_child: Query<(Entity, &Camera, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+) {
+   let motion_total = mouse.motion;
+
+   for (entity, camera, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+      let (target, offset) = match cam.mode() {
+         CameraMode::AttachTo { target, offset } => (target, offset),
+         _ => continue,
+      };
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_targets) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      let before = space.to_world(&transform_parent, &transform_child);
+      let (mut root, mut child) = before;
+      let action_state = actions.get(entity);
+
+      if action_state.map_or(false, |action_state| {
+         action_state.just_pressed(FlyingCamAction::Detach)
+      }) {
+         let (free_parent, free_child) = free_rig(&root, &child);
+         let (free_parent, free_child) = space.to_local(&free_parent, &free_child);
+         *transform_parent = free_parent;
+         *transform_child = free_child;
+         cam.enter_laid_out_mode(CameraMode::Free);
+         continue;
+      }
+
+      // The target went away, so stay where we are
+      let target = match q_targets.get(target) {
+         Ok(target) => target,
+         Err(_) => continue,
+      };
+      cam.mode_entered = false;
+      root.translation = target.translation;
+      root.rotation = target.rotation;
+      child.translation = offset;
+
+      if let (Some(action_state), true, Some(window_size)) = (
+         action_state,
+         window_has_mouse(&windows, camera.window),
+         window_size(&windows, camera.window),
+      ) {
+         if action_state.pressed(FlyingCamAction::Secondary) && motion_total.length_squared() > 0.0
+         {
+            child.rotation =
+               rotate_cam_quat(window_size, motion_total, cam.angular_speed, child.rotation);
+         }
+      }
+      // Only what moved is written, so a resting target leaves the rig untouched
+      space.write_back(
+         before,
+         (root, child),
+         (&mut transform_parent, &mut transform_child),
+      );
+   }
+}
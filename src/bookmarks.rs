@@ -0,0 +1,322 @@
+//! Saved camera viewpoints, stored with `SaveBookmark` held plus a bookmark
+//! key and flown back to with the bookmark key alone, or toured in order by a
+//! camera with a [`BookmarkTour`].
+
+use crate::{
+   limit_pitch_to, orbit_around, rig_root, travel_rig, CameraActions, CameraMode, CameraMouse,
+   CameraTime, CameraTween, FlyingCamAction, MovableCamera, MovableCameraParams, RigSpace,
+   TravelStyle,
+};
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Bookmark actions and the slot each one stores to.
+const BOOKMARK_ACTIONS: [(FlyingCamAction, u32); 9] = [
+   (FlyingCamAction::Bookmark1, 1),
+   (FlyingCamAction::Bookmark2, 2),
+   (FlyingCamAction::Bookmark3, 3),
+   (FlyingCamAction::Bookmark4, 4),
+   (FlyingCamAction::Bookmark5, 5),
+   (FlyingCamAction::Bookmark6, 6),
+   (FlyingCamAction::Bookmark7, 7),
+   (FlyingCamAction::Bookmark8, 8),
+   (FlyingCamAction::Bookmark9, 9),
+];
+
+/// A saved viewpoint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraBookmark {
+   /// World-space camera pose.
+   pub eye: Transform,
+   /// Orbit pivot if the camera was orbiting.
+   pub pivot: Option<Vec3>,
+   /// Field of view of a perspective camera or scale of an orthographic one.
+   pub zoom: Option<f32>,
+}
+
+/// Bookmarked viewpoints by slot, in slot order, shared by all cameras.
+#[derive(Clone, Debug, Default)]
+pub struct CameraBookmarks {
+   pub slots: BTreeMap<u32, CameraBookmark>,
+}
+
+impl CameraBookmarks {
+   pub fn save(&mut self, slot: u32, bookmark: CameraBookmark) {
+      self.slots.insert(slot, bookmark);
+   }
+
+   pub fn get(&self, slot: u32) -> Option<&CameraBookmark> {
+      self.slots.get(&slot)
+   }
+
+   pub fn remove(&mut self, slot: u32) -> Option<CameraBookmark> {
+      self.slots.remove(&slot)
+   }
+
+   /// Occupied slots in ascending order.
+   pub fn sorted_slots(&self) -> impl Iterator<Item = u32> + '_ {
+      self.slots.keys().copied()
+   }
+}
+
+/// Attract mode: a camera with this component flies between the bookmarks in
+/// slot order, pausing at each, and loops until any keyboard, mouse or gamepad
+/// input removes the component.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BookmarkTour {
+   /// Seconds spent at each bookmark.
+   pub dwell: f32,
+   /// Seconds spent flying between bookmarks.
+   pub travel: f32,
+   /// Position of the next stop among the occupied slots.
+   next: usize,
+   /// Seconds left before leaving the current stop.
+   wait: f32,
+}
+
+impl BookmarkTour {
+   pub fn new(dwell: f32, travel: f32) -> Self {
+      Self {
+         dwell,
+         travel,
+         next: 0,
+         wait: 0.0,
+      }
+   }
+}
+
+impl Default for BookmarkTour {
+   fn default() -> Self {
+      Self::new(3.0, 2.0)
+   }
+}
+
+/// Event that bookmarks a camera's current viewpoint in `slot`.
+#[derive(Clone, Copy, Debug)]
+pub struct SaveCameraBookmark {
+   pub camera: Entity,
+   pub slot: u32,
+}
+
+/// Event that flies a camera, or every movable camera when `camera` is
+/// `None`, to the viewpoint bookmarked in `slot`.
+#[derive(Clone, Copy, Debug)]
+pub struct RecallCameraBookmark {
+   pub camera: Option<Entity>,
+   pub slot: u32,
+}
+
+/// Saves and recalls [`CameraBookmarks`] from the bookmark actions and the
+/// bookmark events. Only free flying and orbiting cameras take part.
+pub fn camera_bookmarks(
+   mut commands: Commands,
+   cam_params: Res<MovableCameraParams>,
+   mut bookmarks: ResMut<CameraBookmarks>,
+   actions: CameraActions,
+   mut save_events: EventReader<SaveCameraBookmark>,
+   mut recall_events: EventReader<RecallCameraBookmark>,
+   mut saves: Local<Vec<SaveCameraBookmark>>,
+   mut recalls: Local<Vec<RecallCameraBookmark>>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&mut PerspectiveProjection>,
+      Option<&mut OrthographicProjection>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   // Reused across frames, so handling events doesn't allocate
+   saves.clear();
+   saves.extend(save_events.iter().copied());
+   recalls.clear();
+   recalls.extend(recall_events.iter().copied());
+
+   for (entity, parent, mut transform_child, mut cam, perspective, orthographic) in
+      q_child.iter_mut()
+   {
+      if !cam.mode().is_manual() {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+
+      let mut save = saves
+         .iter()
+         .filter(|event| event.camera == entity)
+         .map(|event| event.slot)
+         .last();
+      let mut recall = recalls
+         .iter()
+         .filter(|event| event.camera.map_or(true, |camera| camera == entity))
+         .map(|event| event.slot)
+         .last();
+      if let Some(action_state) = actions.get(entity) {
+         for (action, slot) in BOOKMARK_ACTIONS {
+            if action_state.just_pressed(action) {
+               if action_state.pressed(FlyingCamAction::SaveBookmark) {
+                  save = Some(slot);
+               } else {
+                  recall = Some(slot);
+               }
+            }
+         }
+      }
+
+      if let Some(slot) = save {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         bookmarks.save(
+            slot,
+            CameraBookmark {
+               eye: root.mul_transform(child),
+               pivot: cam.is_orbiting().then(|| root.translation),
+               zoom: perspective
+                  .as_ref()
+                  .map(|projection| projection.fov)
+                  .or_else(|| orthographic.as_ref().map(|projection| projection.scale)),
+            },
+         );
+      }
+
+      let bookmark = match recall.and_then(|slot| bookmarks.get(slot)) {
+         Some(bookmark) => *bookmark,
+         None => continue,
+      };
+      recall_bookmark(
+         &mut commands,
+         entity,
+         &cam_params.bookmark_travel,
+         &bookmark,
+         cam_params.pitch_limits,
+         space,
+         (&mut transform_parent, &mut transform_child),
+         &mut cam,
+         (perspective, orthographic),
+      );
+   }
+}
+
+/// Flies a camera rig in `space` to `bookmark`.
+fn recall_bookmark(
+   commands: &mut Commands,
+   camera: Entity,
+   style: &TravelStyle,
+   bookmark: &CameraBookmark,
+   pitch_limits: (f32, f32),
+   space: RigSpace,
+   (transform_parent, transform_child): (&mut Transform, &mut Transform),
+   cam: &mut MovableCamera,
+   (perspective, orthographic): (
+      Option<Mut<PerspectiveProjection>>,
+      Option<Mut<OrthographicProjection>>,
+   ),
+) {
+   let rig = match bookmark.pivot {
+      Some(pivot) => orbit_around(pivot, bookmark.eye),
+      // Saved under other limits, perhaps
+      None => (
+         Transform::default(),
+         Transform {
+            rotation: limit_pitch_to(bookmark.eye.rotation, pitch_limits),
+            ..bookmark.eye
+         },
+      ),
+   };
+   travel_rig(
+      commands,
+      camera,
+      style,
+      transform_parent,
+      transform_child,
+      space.to_local(&rig.0, &rig.1),
+   );
+   cam.enter_laid_out_mode(if bookmark.pivot.is_some() {
+      CameraMode::Orbit
+   } else {
+      CameraMode::Free
+   });
+   cam.reference_point = bookmark.pivot;
+   cam.orbit_velocity = Vec2::ZERO;
+   if let Some(zoom) = bookmark.zoom {
+      if let Some(mut projection) = perspective {
+         projection.fov = zoom;
+      } else if let Some(mut projection) = orthographic {
+         projection.scale = zoom;
+      }
+   }
+}
+
+/// Drives cameras with a [`BookmarkTour`].
+pub fn bookmark_tour(
+   mut commands: Commands,
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   bookmarks: Res<CameraBookmarks>,
+   keys: Res<Input<KeyCode>>,
+   mouse_buttons: Res<Input<MouseButton>>,
+   gamepad_buttons: Res<Input<GamepadButton>>,
+   mouse: Res<CameraMouse>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &mut BookmarkTour,
+      Option<&CameraTween>,
+      Option<&mut PerspectiveProjection>,
+      Option<&mut OrthographicProjection>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let input = keys.get_just_pressed().next().is_some()
+      || mouse_buttons.get_just_pressed().next().is_some()
+      || gamepad_buttons.get_just_pressed().next().is_some()
+      || mouse.motion != Vec2::ZERO
+      || mouse.scroll != 0.0;
+   let stops = bookmarks.slots.len();
+
+   for (entity, parent, mut transform_child, mut cam, mut tour, tween, perspective, orthographic) in
+      q_child.iter_mut()
+   {
+      if input || !cam.mode().is_manual() {
+         // Stop where the camera is rather than finishing the flight
+         commands
+            .entity(entity)
+            .remove::<BookmarkTour>()
+            .remove::<CameraTween>();
+         continue;
+      }
+      if tween.is_some() || stops == 0 {
+         continue;
+      }
+      tour.wait -= time.camera_delta_seconds(entity);
+      if tour.wait > 0.0 {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      if let Some(bookmark) = bookmarks.slots.values().nth(tour.next % stops) {
+         recall_bookmark(
+            &mut commands,
+            entity,
+            &TravelStyle::duration(tour.travel),
+            bookmark,
+            cam_params.pitch_limits,
+            space,
+            (&mut transform_parent, &mut transform_child),
+            &mut cam,
+            (perspective, orthographic),
+         );
+      }
+      tour.next = (tour.next + 1) % stops;
+      // The dwell starts once the flight's tween is gone
+      tour.wait = tour.dwell;
+   }
+}
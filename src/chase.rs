@@ -0,0 +1,92 @@
+//! [`CameraMode::Chase`]: follow behind a moving entity on a spring.
+
+use crate::{
+   move_free, rig_root, CameraActions, CameraMode, CameraTime, FlyingCamAction, MovableCamera,
+   MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::{Mul, Neg};
+
+/// Settings of [`CameraMode::Chase`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChaseParams {
+   /// Spring stiffness pulling a chase camera to its spot behind the target.
+   pub stiffness: f32,
+   /// Damping of the chase spring. `2 * sqrt(stiffness)` settles fastest
+   /// without overshooting.
+   pub damping: f32,
+}
+
+impl Default for ChaseParams {
+   fn default() -> Self {
+      Self {
+         stiffness: 30.0,
+         damping: 11.0,
+      }
+   }
+}
+
+/// Moves chase cameras towards their spot behind the target with a
+/// spring-damper and turns them to look at the target. The sideways part of
+/// the offset follows the camera's shoulder, so `SwapShoulder` mirrors it.
+pub fn chase_camera(
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   actions: CameraActions,
+   q_targets: Query<&GlobalTransform>,
+   mut q_child: Query<(Entity, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+) {
+   for (entity, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+      let dt = time.camera_delta_seconds(entity);
+      let (target, offset) = match cam.mode() {
+         CameraMode::Chase { target, offset } => (target, offset),
+         _ => continue,
+      };
+      let target = match q_targets.get(target) {
+         Ok(target) => target,
+         Err(_) => continue,
+      };
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_targets) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      cam.mode_entered = false;
+
+      if actions.get(entity).map_or(false, |action_state| {
+         action_state.just_pressed(FlyingCamAction::SwapShoulder)
+      }) {
+         cam.swap_shoulder();
+      }
+      let side = cam.step_shoulder(cam_params.third_person.shoulder_swap_time, dt);
+      let offset = Vec3::new(offset.x.mul(side), offset.y, offset.z);
+
+      // Chase with the camera's world-space pose, whatever its rig root is
+      move_free(
+         space,
+         (&mut transform_parent, &mut transform_child),
+         |mut eye| {
+            let anchor = cam.track(&cam_params, target.translation);
+            let desired = anchor + target.rotation.mul_vec3(offset);
+            let acceleration = (desired - eye.translation).mul(cam_params.chase.stiffness)
+               - cam.chase_velocity.mul(cam_params.chase.damping);
+            cam.chase_velocity += acceleration.mul(dt);
+            eye.translation += cam.chase_velocity.mul(dt);
+
+            let aim = match cam_params.look_offset {
+               Some(look_offset) => anchor + target.rotation.mul_vec3(look_offset),
+               None => anchor,
+            };
+            let to_target = aim - eye.translation;
+            if to_target.cross(Vec3::Y).length_squared() > f32::EPSILON {
+               let look = Transform::from_translation(eye.translation)
+                  .looking_at(aim, Vec3::Y)
+                  .rotation;
+               let t = 1.0 - cam_params.chase.stiffness.sqrt().mul(dt).neg().exp();
+               eye.rotation = eye.rotation.slerp(look, t);
+            }
+            eye
+         },
+      );
+   }
+}
@@ -0,0 +1,131 @@
+//! Near and far planes that follow what the camera is looking at.
+
+use crate::{CameraBounds, MovableCamera, MovableCameraParams, RigSpace};
+use bevy::prelude::*;
+use std::ops::Mul;
+
+/// How [`adjust_clip_planes`] sets the near and far planes of a camera. Set
+/// [`MovableCameraParams::clip_planes`] to use it.
+///
+/// This version of Bevy draws with an infinite far plane, so the far plane
+/// only sets how far out things are culled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClipPlanes {
+   /// Near plane as a fraction of the distance to what the camera looks at:
+   /// the pivot while orbiting, otherwise the ground plane or the last point
+   /// of interest.
+   pub near_fraction: f32,
+   /// Closest and furthest the near plane goes.
+   pub near_limits: (f32, f32),
+   /// Far plane as a multiple of the camera's height above the ground plane.
+   pub far_per_height: f32,
+   /// Seconds of flight at the current speed the far plane reaches past.
+   pub far_lookahead: f32,
+   /// Closest and furthest the far plane goes. With [`CameraBounds`] it also
+   /// goes no further than their furthest corner.
+   pub far_limits: (f32, f32),
+}
+
+impl Default for ClipPlanes {
+   fn default() -> Self {
+      Self {
+         near_fraction: 0.01,
+         near_limits: (0.01, 10.0),
+         far_per_height: 100.0,
+         far_lookahead: 30.0,
+         far_limits: (100.0, 100_000.0),
+      }
+   }
+}
+
+impl ClipPlanes {
+   /// Near and far planes for a camera at `position` that is `focus_distance`
+   /// from what it looks at, `height` above the ground and moving at `speed`.
+   pub fn planes(
+      &self,
+      position: Vec3,
+      focus_distance: Option<f32>,
+      height: Option<f32>,
+      speed: f32,
+      bounds: Option<&CameraBounds>,
+   ) -> (f32, f32) {
+      let (min_near, max_near) = self.near_limits;
+      let near = focus_distance
+         .map_or(min_near, |distance| distance.mul(self.near_fraction))
+         .clamp(min_near, max_near);
+      let (min_far, max_far) = self.far_limits;
+      let mut far = height
+         .unwrap_or(0.0)
+         .mul(self.far_per_height)
+         .max(speed.mul(self.far_lookahead))
+         .clamp(min_far, max_far);
+      if let Some(bounds) = bounds {
+         let furthest = (position - bounds.min)
+            .abs()
+            .max((position - bounds.max).abs());
+         far = far.min(furthest.length());
+      }
+      (near, far.max(near))
+   }
+}
+
+/// Sets the near and far planes of cameras with
+/// [`MovableCameraParams::clip_planes`], tightening the near plane up close
+/// and pushing the far plane out at altitude and speed.
+pub fn adjust_clip_planes(
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      &Parent,
+      &Transform,
+      &MovableCamera,
+      &mut Camera,
+      Option<&mut PerspectiveProjection>,
+      Option<&mut OrthographicProjection>,
+      Option<&MovableCameraParams>,
+      Option<&CameraBounds>,
+   )>,
+   q_parent: Query<(&Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for (parent, transform_child, cam, mut camera, perspective, orthographic, own_params, bounds) in
+      q_child.iter_mut()
+   {
+      let cam_params = own_params.unwrap_or(&cam_params);
+      let clip = match cam_params.clip_planes {
+         Some(clip) => clip,
+         None => continue,
+      };
+      let (root, child) = match q_parent.get(parent.0) {
+         Ok((transform_parent, root_parent)) => {
+            let frame = root_parent.and_then(|root_parent| q_globals.get(root_parent.0).ok());
+            RigSpace::new(frame, transform_parent).to_world(transform_parent, transform_child)
+         }
+         Err(_) => continue,
+      };
+      let position = root.mul_transform(child).translation;
+      let height = cam_params
+         .ground_plane
+         .map(|(normal, offset)| (position.dot(normal) - offset).abs());
+      let focus_distance = if cam.is_orbiting() {
+         Some(child.translation.z.abs())
+      } else {
+         height.or_else(|| cam.reference_point.map(|point| point.distance(position)))
+      };
+      let (near, far) = clip.planes(position, focus_distance, height, cam.speed, bounds);
+      if let Some(mut projection) = perspective {
+         if projection.near != near || projection.far != far {
+            projection.near = near;
+            projection.far = far;
+         }
+      } else if let Some(mut projection) = orthographic {
+         if projection.near != near || projection.far != far {
+            projection.near = near;
+            projection.far = far;
+         }
+      }
+      if camera.near != near || camera.far != far {
+         camera.near = near;
+         camera.far = far;
+      }
+   }
+}
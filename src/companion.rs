@@ -0,0 +1,112 @@
+//! Picture-in-picture: a second camera that follows a flying camera.
+
+use crate::MovableCamera;
+use bevy::prelude::*;
+
+/// How a [`CompanionCamera`] views its main camera's scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompanionView {
+   /// The main camera's pose with this transform applied in its local space,
+   /// sharing its field of view.
+   Offset(Transform),
+   /// Looks straight down on the main camera's pivot, or the camera itself
+   /// when it isn't orbiting, from `height` above.
+   TopDown { height: f32 },
+   /// Looks at the main camera's pivot, or the camera itself when it isn't
+   /// orbiting, from `distance` away along the world-space `direction`.
+   Fixed { direction: Vec3, distance: f32 },
+}
+
+/// A camera kept in sync with the flying camera `main` by
+/// [`sync_companion_cameras`]. It isn't part of a rig and takes no input.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CompanionCamera {
+   pub main: Entity,
+   pub view: CompanionView,
+}
+
+/// Spawns a [`CompanionCamera`] of `main`. `camera` picks where it renders;
+/// this version of Bevy has no viewports, so a corner inset is a second
+/// window or a named camera drawn by the app's render graph.
+pub fn spawn_companion_camera(
+   commands: &mut Commands,
+   main: Entity,
+   view: CompanionView,
+   camera: Camera,
+) -> Entity {
+   commands
+      .spawn_bundle(PerspectiveCameraBundle {
+         camera,
+         ..PerspectiveCameraBundle::new()
+      })
+      .insert(CompanionCamera { main, view })
+      .id()
+}
+
+/// Moves companion cameras to match their main camera. Runs before transform
+/// propagation so the companion is never a frame behind.
+pub fn sync_companion_cameras(
+   q_main: Query<(
+      &Parent,
+      &Transform,
+      &MovableCamera,
+      Option<&PerspectiveProjection>,
+   )>,
+   q_parent: Query<&Transform, (Without<MovableCamera>, Without<CompanionCamera>)>,
+   mut q_companion: Query<
+      (
+         &CompanionCamera,
+         &mut Transform,
+         Option<&mut PerspectiveProjection>,
+      ),
+      Without<MovableCamera>,
+   >,
+) {
+   for (companion, mut transform, projection) in q_companion.iter_mut() {
+      let (parent, transform_child, cam, main_projection) = match q_main.get(companion.main) {
+         Ok(main) => main,
+         Err(_) => continue,
+      };
+      let transform_parent = match q_parent.get(parent.0) {
+         Ok(transform_parent) => transform_parent,
+         Err(_) => continue,
+      };
+      let eye = transform_parent.mul_transform(*transform_child);
+      let pivot = if cam.is_orbiting() {
+         transform_parent.translation
+      } else {
+         eye.translation
+      };
+
+      match companion.view {
+         CompanionView::Offset(offset) => {
+            *transform = eye.mul_transform(offset);
+            if let (Some(mut projection), Some(main_projection)) = (projection, main_projection) {
+               projection.fov = main_projection.fov;
+            }
+         }
+         CompanionView::TopDown { height } => {
+            // Keep the main camera's heading pointing up the screen
+            let forward = eye.forward();
+            let up = Vec3::new(forward.x, 0.0, forward.z)
+               .try_normalize()
+               .unwrap_or(-Vec3::Z);
+            *transform =
+               Transform::from_translation(pivot + Vec3::Y * height).looking_at(pivot, up);
+         }
+         CompanionView::Fixed {
+            direction,
+            distance,
+         } => {
+            let direction = direction.try_normalize().unwrap_or(Vec3::Z);
+            let up = if direction.cross(Vec3::Y).length_squared() > f32::EPSILON {
+               Vec3::Y
+            } else {
+               -Vec3::Z
+            };
+            *transform =
+               Transform::from_translation(pivot + direction * distance).looking_at(pivot, up);
+         }
+      }
+   }
+}
@@ -0,0 +1,371 @@
+//! Switching which camera renders, with hard cuts or eased blends, directly or
+//! through a [`CameraStack`] of temporary cameras.
+
+use crate::Easing;
+use bevy::{
+   prelude::*,
+   render::camera::{ActiveCameras, CameraPlugin},
+};
+
+/// Event that makes a camera the active one at once.
+#[derive(Clone, Copy, Debug)]
+pub struct CutTo(pub Entity);
+
+/// Event that blends from the active camera to `camera` over `duration`
+/// seconds. The blend is shown through a proxy camera that moves between the
+/// two cameras' poses and fields of view, which follow the cameras as they move.
+#[derive(Clone, Copy, Debug)]
+pub struct BlendTo {
+   pub camera: Entity,
+   pub duration: f32,
+   pub easing: Easing,
+}
+
+/// Marks the proxy camera the director shows during a blend.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DirectorProxy;
+
+/// Where a blend starts from.
+#[derive(Clone, Copy, Debug)]
+enum BlendFrom {
+   Camera(Entity),
+   /// Wherever an interrupted blend had got to.
+   Pose(Transform, f32),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DirectorBlend {
+   from: BlendFrom,
+   to: Entity,
+   duration: f32,
+   easing: Easing,
+   elapsed: f32,
+   proxy: Entity,
+}
+
+impl DirectorBlend {
+   /// Eased progress of the blend.
+   fn progress(&self) -> f32 {
+      self.easing.apply(self.elapsed / self.duration)
+   }
+}
+
+/// Picks which camera fills the active camera `slot` in response to
+/// [`CutTo`] and [`BlendTo`].
+#[derive(Clone, Debug)]
+pub struct CameraDirector {
+   /// Name of the [`ActiveCameras`] entry to drive.
+   pub slot: String,
+   blend: Option<DirectorBlend>,
+}
+
+impl Default for CameraDirector {
+   fn default() -> Self {
+      Self {
+         slot: CameraPlugin::CAMERA_3D.to_string(),
+         blend: None,
+      }
+   }
+}
+
+impl CameraDirector {
+   pub fn is_blending(&self) -> bool {
+      self.blend.is_some()
+   }
+}
+
+/// Handles [`CutTo`] and [`BlendTo`] and advances the blend in progress. The
+/// proxy is moved by [`place_director_proxy`].
+pub fn direct_cameras(
+   mut commands: Commands,
+   time: Res<Time>,
+   mut director: ResMut<CameraDirector>,
+   active_cameras: Option<ResMut<ActiveCameras>>,
+   mut cuts: EventReader<CutTo>,
+   mut blends: EventReader<BlendTo>,
+   q_cameras: Query<(&GlobalTransform, Option<&PerspectiveProjection>), Without<DirectorProxy>>,
+   mut q_proxy: Query<&mut PerspectiveProjection, With<DirectorProxy>>,
+) {
+   let mut active_cameras = match active_cameras {
+      Some(active_cameras) => active_cameras,
+      None => return,
+   };
+   let director = &mut *director;
+   if active_cameras.get(&director.slot).is_none() {
+      active_cameras.add(&director.slot);
+   }
+   let pose = |from| blend_pose(&q_cameras, from);
+
+   let mut show = None;
+   for CutTo(camera) in cuts.iter() {
+      if let Some(blend) = director.blend.take() {
+         commands.entity(blend.proxy).despawn();
+      }
+      show = Some(*camera);
+   }
+   for event in blends.iter() {
+      let active = match active_cameras
+         .get(&director.slot)
+         .and_then(|active| active.entity)
+      {
+         Some(active) if event.duration > 0.0 => active,
+         _ => {
+            if let Some(blend) = director.blend.take() {
+               commands.entity(blend.proxy).despawn();
+            }
+            show = Some(event.camera);
+            continue;
+         }
+      };
+      // Carry on from the proxy if a blend was already running
+      let (from, proxy) = match director.blend.take() {
+         Some(blend) => {
+            let from = match (pose(blend.from), pose(BlendFrom::Camera(blend.to))) {
+               (Some(from), Some(to)) => blend_poses(from, to, blend.progress()),
+               (from, to) => from.or(to).unwrap_or_default(),
+            };
+            (BlendFrom::Pose(from.0, from.1), blend.proxy)
+         }
+         None => {
+            let from = BlendFrom::Camera(active);
+            let (transform, fov) = pose(from).unwrap_or_default();
+            let proxy = commands
+               .spawn_bundle(PerspectiveCameraBundle {
+                  transform,
+                  perspective_projection: PerspectiveProjection {
+                     fov,
+                     ..Default::default()
+                  },
+                  ..PerspectiveCameraBundle::new()
+               })
+               .insert(DirectorProxy)
+               .id();
+            (from, proxy)
+         }
+      };
+      director.blend = Some(DirectorBlend {
+         from,
+         to: event.camera,
+         duration: event.duration,
+         easing: event.easing,
+         elapsed: 0.0,
+         proxy,
+      });
+      show = Some(proxy);
+   }
+
+   if let Some(mut blend) = director.blend {
+      blend.elapsed += time.delta_seconds();
+      if blend.elapsed >= blend.duration {
+         commands.entity(blend.proxy).despawn();
+         director.blend = None;
+         show = Some(blend.to);
+      } else {
+         // The field of view is blended here, ahead of the projection update
+         if let (Some(from), Some(to), Ok(mut projection)) = (
+            pose(blend.from),
+            pose(BlendFrom::Camera(blend.to)),
+            q_proxy.get_mut(blend.proxy),
+         ) {
+            let (_, fov) = blend_poses(from, to, blend.progress());
+            projection.fov = fov;
+         }
+         director.blend = Some(blend);
+      }
+   }
+
+   if let (Some(camera), Some(active)) = (show, active_cameras.get_mut(&director.slot)) {
+      active.entity = Some(camera);
+   }
+}
+
+/// Moves the blend proxy between the poses the two cameras are drawn at this
+/// frame. Runs after transform propagation and
+/// [`FlyingCamSystem::Present`](crate::FlyingCamSystem::Present), so the proxy never shows a camera where it
+/// was the frame before.
+pub fn place_director_proxy(
+   director: Res<CameraDirector>,
+   q_cameras: Query<(&GlobalTransform, Option<&PerspectiveProjection>), Without<DirectorProxy>>,
+   mut q_proxy: Query<(&mut Transform, &mut GlobalTransform), With<DirectorProxy>>,
+) {
+   let blend = match director.blend {
+      Some(blend) => blend,
+      None => return,
+   };
+   if let (Some(from), Some(to), Ok((mut transform, mut global))) = (
+      blend_pose(&q_cameras, blend.from),
+      blend_pose(&q_cameras, BlendFrom::Camera(blend.to)),
+      q_proxy.get_mut(blend.proxy),
+   ) {
+      let (pose, _) = blend_poses(from, to, blend.progress());
+      // The proxy has no parent, so its global pose is its own
+      *transform = pose;
+      *global = GlobalTransform::from(pose);
+   }
+}
+
+/// Pose and field of view of one end of a blend.
+fn blend_pose(
+   q_cameras: &Query<(&GlobalTransform, Option<&PerspectiveProjection>), Without<DirectorProxy>>,
+   from: BlendFrom,
+) -> Option<(Transform, f32)> {
+   match from {
+      BlendFrom::Camera(camera) => q_cameras.get(camera).ok().map(|(global, projection)| {
+         (
+            Transform::from(*global),
+            projection.map_or(std::f32::consts::FRAC_PI_4, |projection| projection.fov),
+         )
+      }),
+      BlendFrom::Pose(transform, fov) => Some((transform, fov)),
+   }
+}
+
+fn blend_poses(
+   (from, from_fov): (Transform, f32),
+   (to, to_fov): (Transform, f32),
+   t: f32,
+) -> (Transform, f32) {
+   (
+      Transform {
+         translation: from.translation.lerp(to.translation, t),
+         rotation: from.rotation.slerp(to.rotation, t),
+         scale: from.scale.lerp(to.scale, t),
+      },
+      from_fov + (to_fov - from_fov) * t,
+   )
+}
+
+/// Handle to a camera pushed onto the [`CameraStack`]. Pop it to hand the
+/// view back.
+#[must_use = "the camera stays on the stack until its lease is popped"]
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CameraLease(u64);
+
+#[derive(Clone, Copy, Debug)]
+struct StackEntry {
+   lease: u64,
+   camera: Entity,
+   priority: i32,
+   /// Blend used when this camera takes over or hands back the view.
+   blend: Option<(f32, Easing)>,
+}
+
+/// Temporary cameras that take over from the `base` camera, such as
+/// cutscenes, death cams or photo mode. The highest priority camera is shown,
+/// the latest pushed on a tie, and popping it returns to the one below and
+/// finally to `base`. Changes go through the [`CameraDirector`].
+#[derive(Clone, Debug, Default)]
+pub struct CameraStack {
+   /// Camera shown when the stack is empty, usually the flying camera.
+   pub base: Option<Entity>,
+   entries: Vec<StackEntry>,
+   next_lease: u64,
+   shown: Option<Entity>,
+   /// Blend for the next change of camera, from the last push or pop.
+   transition: Option<(f32, Easing)>,
+}
+
+impl CameraStack {
+   /// Pushes `camera` with `priority`, cutting to it if it ends up on top.
+   pub fn push(&mut self, camera: Entity, priority: i32) -> CameraLease {
+      self.push_entry(camera, priority, None)
+   }
+
+   /// Pushes `camera` with `priority`, blending to it over `duration` seconds
+   /// if it ends up on top, and blending back the same way when popped.
+   pub fn push_blended(
+      &mut self,
+      camera: Entity,
+      priority: i32,
+      duration: f32,
+      easing: Easing,
+   ) -> CameraLease {
+      self.push_entry(camera, priority, Some((duration, easing)))
+   }
+
+   fn push_entry(
+      &mut self,
+      camera: Entity,
+      priority: i32,
+      blend: Option<(f32, Easing)>,
+   ) -> CameraLease {
+      let lease = self.next_lease;
+      self.next_lease += 1;
+      self.entries.push(StackEntry {
+         lease,
+         camera,
+         priority,
+         blend,
+      });
+      self.transition = blend;
+      CameraLease(lease)
+   }
+
+   /// Removes a leased camera. Returns the camera if it was still on the
+   /// stack.
+   pub fn pop(&mut self, lease: CameraLease) -> Option<Entity> {
+      let index = self
+         .entries
+         .iter()
+         .position(|entry| entry.lease == lease.0)?;
+      let entry = self.entries.remove(index);
+      self.transition = entry.blend;
+      Some(entry.camera)
+   }
+
+   /// Camera that should be shown.
+   pub fn top(&self) -> Option<Entity> {
+      self
+         .entries
+         .iter()
+         .max_by_key(|entry| entry.priority)
+         .map(|entry| entry.camera)
+         .or(self.base)
+   }
+
+   pub fn is_empty(&self) -> bool {
+      self.entries.is_empty()
+   }
+}
+
+/// Shows the top of the [`CameraStack`] whenever it changes, dropping leased
+/// cameras that were despawned.
+pub fn apply_camera_stack(
+   mut stack: ResMut<CameraStack>,
+   mut cuts: EventWriter<CutTo>,
+   mut blends: EventWriter<BlendTo>,
+   q_cameras: Query<(), With<Camera>>,
+) {
+   let despawned = stack
+      .entries
+      .iter()
+      .any(|entry| q_cameras.get(entry.camera).is_err());
+   if !stack.is_changed() && !despawned {
+      return;
+   }
+   if despawned {
+      stack
+         .entries
+         .retain(|entry| q_cameras.get(entry.camera).is_ok());
+   }
+   let top = stack.top();
+   if top == stack.shown {
+      if stack.transition.is_some() {
+         stack.transition = None;
+      }
+      return;
+   }
+   stack.shown = top;
+   let camera = match top {
+      Some(camera) => camera,
+      None => return,
+   };
+   match stack.transition.take() {
+      Some((duration, easing)) => blends.send(BlendTo {
+         camera,
+         duration,
+         easing,
+      }),
+      None => cuts.send(CutTo(camera)),
+   }
+}
@@ -0,0 +1,148 @@
+//! [`CameraMode::Drone`]: a hovering quadcopter that leans into its motion.
+
+use crate::{
+   net_movement, rig_root, window_has_mouse, window_size, yaw_angle, CameraActions, CameraMode,
+   CameraMouse, CameraTime, FlyingCamAction, MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::{
+   f32::consts::{FRAC_PI_2, PI, TAU},
+   ops::{Div, Mul, Neg},
+};
+
+/// Settings of [`CameraMode::Drone`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DroneParams {
+   /// How quickly, per second, the drone loses speed. Its thrust is scaled
+   /// so it tops out at its fly speed.
+   pub drag: f32,
+   /// Radians the drone leans at full thrust.
+   pub max_tilt: f32,
+   /// Seconds the drone takes to lean about two thirds of the way to its new
+   /// tilt.
+   pub tilt_response: f32,
+}
+
+impl Default for DroneParams {
+   fn default() -> Self {
+      Self {
+         drag: 2.0,
+         max_tilt: 0.3,
+         tilt_response: 0.25,
+      }
+   }
+}
+
+/// Flies drone cameras. The rig parent is the drone, moving with momentum
+/// and turning with `RotateLeft`, `RotateRight` or a `Secondary` drag, and
+/// the camera is its gimbal, pitched by the drag and tilted toward the
+/// drone's acceleration. The tilt is only for show and levels out once the
+/// input stops.
+pub fn drone_camera(
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(Entity, &Camera, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let motion_total = mouse.motion;
+
+   for (entity, camera, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+      let dt = time.camera_delta_seconds(entity);
+      if cam.mode() != CameraMode::Drone {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      let (mut root, mut child) = space.to_world(&transform_parent, &transform_child);
+      // Split the pose into the level drone and the pitch of its gimbal
+      if cam.mode_entered {
+         let eye = root.mul_transform(child);
+         root = Transform {
+            translation: eye.translation,
+            rotation: Quat::from_rotation_y(yaw_angle(eye.rotation)),
+            ..Default::default()
+         };
+         *transform_parent = space.to_local(&root, &child).0;
+         cam.drone_pitch = eye.forward().y.clamp(-1.0, 1.0).asin();
+         cam.drone_velocity = Vec3::ZERO;
+         cam.drone_tilt = Vec2::ZERO;
+         cam.mode_entered = false;
+      }
+      let before = (root, child);
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+
+      let mut turn = net_movement(
+         action_state,
+         FlyingCamAction::RotateRight,
+         FlyingCamAction::RotateLeft,
+      )
+      .mul(cam_params.top_down.rotate_speed)
+      .mul(dt);
+      if let (true, true, Some(window_size)) = (
+         action_state.pressed(FlyingCamAction::Secondary),
+         window_has_mouse(&windows, camera.window),
+         window_size(&windows, camera.window),
+      ) {
+         turn -= motion_total
+            .x
+            .div(window_size.x)
+            .mul(TAU)
+            .mul(cam.angular_speed);
+         cam.drone_pitch = (cam.drone_pitch
+            - motion_total
+               .y
+               .div(window_size.y)
+               .mul(PI)
+               .mul(cam.angular_speed))
+         .clamp(FRAC_PI_2.neg(), FRAC_PI_2);
+      }
+      root.rotation = Quat::from_rotation_y(turn).mul(root.rotation);
+
+      // Thrust toward the input and let drag set the top speed at `speed`
+      let thrust = Vec3::new(
+         net_movement(action_state, FlyingCamAction::Left, FlyingCamAction::Right),
+         net_movement(action_state, FlyingCamAction::Down, FlyingCamAction::Up),
+         net_movement(
+            action_state,
+            FlyingCamAction::Forward,
+            FlyingCamAction::Back,
+         ),
+      )
+      .normalize_or_zero()
+      .mul(cam.speed.mul(cam_params.drone.drag));
+      let acceleration = root.rotation.mul_vec3(thrust);
+      cam.drone_velocity =
+         (cam.drone_velocity + acceleration.mul(dt)).mul(cam_params.drone.drag.neg().mul(dt).exp());
+      root.translation += cam.drone_velocity.mul(dt);
+
+      // Lean into the horizontal thrust: nose down to speed up, bank to strafe
+      let target_tilt = Vec2::new(thrust.z, thrust.x.neg())
+         .div(cam.speed.mul(cam_params.drone.drag).max(f32::EPSILON))
+         .mul(cam_params.drone.max_tilt);
+      let blend = if cam_params.drone.tilt_response > 0.0 {
+         1.0 - dt.div(cam_params.drone.tilt_response).neg().exp()
+      } else {
+         1.0
+      };
+      cam.drone_tilt = cam.drone_tilt.lerp(target_tilt, blend);
+
+      child.translation = Vec3::ZERO;
+      child.rotation = Quat::from_rotation_z(cam.drone_tilt.y)
+         .mul(Quat::from_rotation_x(cam.drone_tilt.x))
+         .mul(Quat::from_rotation_x(cam.drone_pitch));
+      space.write_back(
+         before,
+         (root, child),
+         (&mut transform_parent, &mut transform_child),
+      );
+   }
+}
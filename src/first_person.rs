@@ -0,0 +1,58 @@
+//! [`CameraMode::FirstPerson`]: free flight with the cursor captured, so the
+//! mouse always looks around, as first-person games expect.
+
+use crate::{CameraActions, CameraMode, FlyingCamAction, MovableCamera};
+use bevy::prelude::*;
+
+/// Decides when first-person cameras capture the cursor, which
+/// [`lock_cursor`](crate::lock_cursor) then applies to the window.
+/// `ReleaseCursor`, typically bound to Escape, hands it back until the window
+/// is clicked with `Primary`. Losing focus hands it back until the window has
+/// focus again, and leaving the mode releases it for good.
+pub fn capture_cursor(
+   windows: Res<Windows>,
+   actions: CameraActions,
+   #[cfg(target_arch = "wasm32")] web_lock: Res<crate::WebPointerLock>,
+   mut q_cam: Query<(Entity, &Camera, &mut MovableCamera)>,
+) {
+   for (entity, camera, mut cam) in q_cam.iter_mut() {
+      let window = match windows.get(camera.window) {
+         Some(window) => window,
+         None => {
+            cam.cursor_captured = false;
+            continue;
+         }
+      };
+      if cam.mode() != CameraMode::FirstPerson {
+         if cam.cursor_captured {
+            cam.cursor_captured = false;
+         }
+         cam.cursor_released = false;
+         continue;
+      }
+      let action_state = actions.get(entity);
+      let pressed = |action| action_state.map_or(false, |state| state.just_pressed(action));
+
+      if cam.cursor_captured {
+         // Alt-tabbing away must not leave the cursor trapped and hidden
+         if !window.is_focused() {
+            cam.cursor_captured = false;
+         } else if pressed(FlyingCamAction::ReleaseCursor) {
+            cam.cursor_captured = false;
+            cam.cursor_released = true;
+         }
+      } else if window.is_focused() {
+         if cam.cursor_released && pressed(FlyingCamAction::Primary) {
+            cam.cursor_released = false;
+         }
+         if !cam.cursor_released {
+            cam.cursor_captured = true;
+         }
+      }
+   }
+   // The browser only captures on a click, and may let go at any time
+   #[cfg(target_arch = "wasm32")]
+   for (_, _, mut cam) in q_cam.iter_mut() {
+      web_lock.sync_capture(&mut cam);
+   }
+}
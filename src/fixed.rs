@@ -0,0 +1,260 @@
+//! Fixed-rate camera simulation with interpolated presentation.
+
+use crate::{CameraMouse, FlyingCamSystem, MovableCamera};
+use bevy::{ecs::schedule::ShouldRun, prelude::*, transform::TransformSystem};
+
+/// Runs the camera systems on a fixed timestep of `timestep` seconds instead
+/// of once a frame, and smooths what is drawn between ticks with
+/// [`CameraKinematics`].
+///
+/// Camera systems see the timestep through [`CameraTime`](crate::CameraTime).
+/// Like any fixed timestep in this version of Bevy, a frame that runs several
+/// ticks shows each of them the same input, so keep the tick rate at or below
+/// the frame rate to avoid handling a press twice.
+pub struct FixedCameraPlugin {
+   pub timestep: f32,
+}
+
+impl Default for FixedCameraPlugin {
+   fn default() -> Self {
+      Self {
+         timestep: 1.0 / 60.0,
+      }
+   }
+}
+
+impl Plugin for FixedCameraPlugin {
+   fn build(&self, app: &mut App) {
+      app.insert_resource(CameraTimestep::new(self.timestep))
+         .add_system_set(
+            SystemSet::new()
+               .with_run_criteria(CameraTick)
+               .with_system(record_camera_kinematics.after(FlyingCamSystem::Move)),
+         )
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            present_camera_kinematics
+               .label(FlyingCamSystem::Present)
+               .after(TransformSystem::TransformPropagate),
+         );
+   }
+}
+
+/// Fixed timestep of the camera systems, added by [`FixedCameraPlugin`].
+#[derive(Clone, Copy, Debug)]
+pub struct CameraTimestep {
+   /// Seconds per tick.
+   pub step: f32,
+   accumulator: f32,
+   /// Ticks run so far this frame.
+   ticks: u32,
+   looping: bool,
+   /// Whether every frame runs exactly one tick.
+   manual: bool,
+}
+
+impl CameraTimestep {
+   pub fn new(step: f32) -> Self {
+      Self {
+         step,
+         accumulator: 0.0,
+         ticks: 0,
+         looping: false,
+         manual: false,
+      }
+   }
+
+   /// Runs exactly one tick of `step` seconds per frame however long the
+   /// frame took, so tests and replays come out the same every time.
+   pub fn manual(step: f32) -> Self {
+      Self {
+         manual: true,
+         ..Self::new(step)
+      }
+   }
+
+   /// How far the current frame is between the last tick and the next, in
+   /// `[0, 1)`.
+   pub fn overstep(&self) -> f32 {
+      if self.step > 0.0 {
+         (self.accumulator / self.step).clamp(0.0, 1.0)
+      } else {
+         0.0
+      }
+   }
+
+   /// Ticks run so far this frame.
+   pub fn ticks(&self) -> u32 {
+      self.ticks
+   }
+}
+
+/// Label of the [`camera_tick`] run criteria, for running systems of your own
+/// on the camera ticks.
+#[derive(RunCriteriaLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CameraTick;
+
+/// Run criteria of the camera systems: skips them while there is no
+/// [`MovableCamera`] and, with a [`CameraTimestep`], runs them once per tick.
+/// The frame's [`CameraMouse`] input goes to the first tick only.
+pub fn camera_tick(
+   time: Res<Time>,
+   timestep: Option<ResMut<CameraTimestep>>,
+   mut mouse: ResMut<CameraMouse>,
+   q_cams: Query<(), With<MovableCamera>>,
+) -> ShouldRun {
+   let mut timestep = match timestep {
+      Some(timestep) if timestep.step > 0.0 => timestep,
+      _ => return crate::any_movable_camera(q_cams),
+   };
+   if !timestep.looping {
+      timestep.ticks = 0;
+      // Don't bank time for a camera that doesn't exist yet
+      if q_cams.is_empty() {
+         timestep.accumulator = 0.0;
+         return ShouldRun::No;
+      }
+      timestep.accumulator += if timestep.manual {
+         timestep.step
+      } else {
+         time.delta_seconds()
+      };
+   }
+   if timestep.accumulator >= timestep.step {
+      if timestep.ticks > 0 {
+         mouse.clear();
+      }
+      timestep.accumulator -= timestep.step;
+      timestep.ticks += 1;
+      timestep.looping = true;
+      ShouldRun::YesAndCheckAgain
+   } else {
+      timestep.looping = false;
+      ShouldRun::No
+   }
+}
+
+/// World-space poses of a camera at the last two ticks, which what is drawn
+/// is interpolated between. Added to movable cameras by [`FixedCameraPlugin`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraKinematics {
+   pub previous: GlobalTransform,
+   pub current: GlobalTransform,
+}
+
+/// Shifts the simulated pose of each camera into its [`CameraKinematics`] at
+/// the end of every tick, so a frame that runs several ticks still blends
+/// between the last two.
+pub fn record_camera_kinematics(
+   mut commands: Commands,
+   mut q_child: Query<
+      (Entity, &Parent, &Transform, Option<&mut CameraKinematics>),
+      With<MovableCamera>,
+   >,
+   q_root: Query<(&Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for (entity, parent, transform_child, kinematics) in q_child.iter_mut() {
+      // Nothing is propagated between ticks, so build the pose from the rig
+      let pose = match q_root.get(parent.0) {
+         Ok((transform_root, None)) => GlobalTransform::from(*transform_root),
+         Ok((transform_root, Some(frame))) => match q_globals.get(frame.0) {
+            Ok(global_frame) => global_frame.mul_transform(*transform_root),
+            Err(_) => continue,
+         },
+         Err(_) => continue,
+      }
+      .mul_transform(*transform_child);
+      match kinematics {
+         Some(mut kinematics) => {
+            // A camera at rest keeps its kinematics untouched
+            if kinematics.previous != kinematics.current || kinematics.current != pose {
+               kinematics.previous = kinematics.current;
+               kinematics.current = pose;
+            }
+         }
+         None => {
+            commands.entity(entity).insert(CameraKinematics {
+               previous: pose,
+               current: pose,
+            });
+         }
+      }
+   }
+}
+
+/// Draws each camera between its last two ticks. Only the [`GlobalTransform`]
+/// of the camera and its children is touched, so its [`Transform`] keeps the
+/// simulated pose.
+pub fn present_camera_kinematics(
+   timestep: Res<CameraTimestep>,
+   mut stack: Local<Vec<(Entity, GlobalTransform)>>,
+   mut q_child: Query<
+      (
+         &Parent,
+         &Transform,
+         &mut GlobalTransform,
+         &mut CameraKinematics,
+         Option<&Children>,
+      ),
+      With<MovableCamera>,
+   >,
+   mut q_others: Query<
+      (&Transform, &mut GlobalTransform, Option<&Children>),
+      Without<MovableCamera>,
+   >,
+) {
+   for (parent, transform_child, mut global, mut kinematics, children) in q_child.iter_mut() {
+      // Propagation skips unchanged rigs, so rebuild the simulated pose rather
+      // than trust the interpolated one left from last frame
+      let pose = match q_others.get(parent.0) {
+         Ok((_, global_parent, _)) => global_parent.mul_transform(*transform_child),
+         Err(_) => continue,
+      };
+      if pose != kinematics.current {
+         // Moved outside the ticks, as by a tween, so there's nothing to blend
+         kinematics.previous = pose;
+         kinematics.current = pose;
+      }
+      let t = timestep.overstep();
+      let (previous, current) = (kinematics.previous, kinematics.current);
+      let drawn = GlobalTransform {
+         translation: previous.translation.lerp(current.translation, t),
+         rotation: previous.rotation.slerp(current.rotation, t),
+         scale: previous.scale.lerp(current.scale, t),
+      };
+      if *global == drawn {
+         continue;
+      }
+      *global = drawn;
+      if let Some(children) = children {
+         present_children(&drawn, children, &mut stack, &mut q_others);
+      }
+   }
+}
+
+/// Moves the descendants of a camera along with its drawn pose. `stack` is
+/// kept across frames, so walking the hierarchy doesn't allocate.
+fn present_children(
+   global_camera: &GlobalTransform,
+   children: &Children,
+   stack: &mut Vec<(Entity, GlobalTransform)>,
+   q_others: &mut Query<
+      (&Transform, &mut GlobalTransform, Option<&Children>),
+      Without<MovableCamera>,
+   >,
+) {
+   stack.clear();
+   stack.extend(children.iter().map(|child| (*child, *global_camera)));
+   while let Some((child, global_parent)) = stack.pop() {
+      if let Ok((transform, mut global, grandchildren)) = q_others.get_mut(child) {
+         let drawn = global_parent.mul_transform(*transform);
+         if *global != drawn {
+            *global = drawn;
+         }
+         if let Some(grandchildren) = grandchildren {
+            stack.extend(grandchildren.iter().map(|grandchild| (*grandchild, drawn)));
+         }
+      }
+   }
+}
@@ -0,0 +1,144 @@
+//! [`CameraMode::Flight`]: six degrees of freedom with a throttle, for flight
+//! and space prototypes.
+
+use crate::{
+   free_rig, move_free, net_movement, rig_root, window_has_mouse, window_size, CameraActions,
+   CameraMode, CameraMouse, CameraTime, FlyingCamAction, MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::{Div, Mul};
+
+/// Throttle change for one line of scrolling.
+const FLIGHT_THROTTLE_SCROLL_STEP: f32 = 0.05;
+
+/// Settings of [`CameraMode::Flight`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlightParams {
+   /// Speed at full throttle.
+   pub max_speed: f32,
+   /// Turn rates at full stick, in radians per second, as (pitch, yaw, roll).
+   pub turn_rates: Vec3,
+   /// How hard banking turns the camera, in radians per second at a 90
+   /// degree bank.
+   pub bank_turn: f32,
+   /// Throttle change per second while the throttle keys are held.
+   pub throttle_rate: f32,
+}
+
+impl Default for FlightParams {
+   fn default() -> Self {
+      Self {
+         max_speed: 50.0,
+         turn_rates: Vec3::new(1.5, 0.8, 2.5),
+         bank_turn: 1.0,
+         throttle_rate: 0.5,
+      }
+   }
+}
+
+/// Flies flight cameras forward at their throttle. `Forward`/`Back` pitch,
+/// `Left`/`Right` roll, `RotateLeft`/`RotateRight` yaw, and `Up`/`Down` or
+/// scrolling set the throttle. Dragging with `Secondary` pitches and rolls.
+/// Banking pulls the nose around like a plane turning.
+pub fn flight_camera(
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(Entity, &Camera, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let motion_total = mouse.motion;
+   let scroll_total = mouse.scroll;
+
+   for (entity, camera, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+      let dt = time.camera_delta_seconds(entity);
+      if cam.mode() != CameraMode::Flight {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      if cam.mode_entered {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         let (free_parent, free_child) = free_rig(&root, &child);
+         let (free_parent, free_child) = space.to_local(&free_parent, &free_child);
+         *transform_parent = free_parent;
+         *transform_child = free_child;
+         cam.mode_entered = false;
+      }
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+
+      let rates = cam_params.flight.turn_rates;
+      // Stick input in [-1, 1] for (pitch, yaw, roll)
+      let mut stick = Vec3::new(
+         net_movement(
+            action_state,
+            FlyingCamAction::Forward,
+            FlyingCamAction::Back,
+         ),
+         net_movement(
+            action_state,
+            FlyingCamAction::RotateRight,
+            FlyingCamAction::RotateLeft,
+         ),
+         net_movement(action_state, FlyingCamAction::Right, FlyingCamAction::Left),
+      );
+      let mut throttle_input =
+         net_movement(action_state, FlyingCamAction::Down, FlyingCamAction::Up).mul(dt);
+
+      if window_has_mouse(&windows, camera.window) {
+         throttle_input += scroll_total.mul(FLIGHT_THROTTLE_SCROLL_STEP);
+         if let (true, Some(window_size)) = (
+            action_state.pressed(FlyingCamAction::Secondary),
+            window_size(&windows, camera.window),
+         ) {
+            // A drag across the whole window is a second of full stick, and
+            // pulling the mouse back raises the nose
+            if dt > 0.0 {
+               stick.x += motion_total
+                  .y
+                  .div(window_size.y)
+                  .div(dt)
+                  .mul(cam.angular_speed);
+               stick.z -= motion_total
+                  .x
+                  .div(window_size.x)
+                  .div(dt)
+                  .mul(cam.angular_speed);
+            }
+         }
+      }
+      cam.throttle =
+         (cam.throttle + throttle_input.mul(cam_params.flight.throttle_rate)).clamp(0.0, 1.0);
+
+      let speed = cam.throttle.mul(cam_params.flight.max_speed);
+      move_free(
+         space,
+         (&mut transform_parent, &mut transform_child),
+         |mut eye| {
+            let rotation = eye.rotation;
+            let pitch =
+               Quat::from_axis_angle(rotation.mul_vec3(Vec3::X), stick.x.mul(rates.x).mul(dt));
+            let yaw =
+               Quat::from_axis_angle(rotation.mul_vec3(Vec3::Y), stick.y.mul(rates.y).mul(dt));
+            let roll =
+               Quat::from_axis_angle(rotation.mul_vec3(Vec3::Z), stick.z.mul(rates.z).mul(dt));
+            // Lift tilts with the wings, so a bank turns the nose around the world up axis
+            let bank = rotation.mul_vec3(Vec3::X).y;
+            let turn = Quat::from_rotation_y(bank.mul(cam_params.flight.bank_turn).mul(dt));
+            eye.rotation = turn.mul(roll).mul(yaw).mul(pitch).mul(rotation).normalize();
+
+            let forward = eye.forward();
+            eye.translation += forward.mul(speed.mul(dt));
+            eye
+         },
+      );
+   }
+}
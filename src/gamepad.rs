@@ -0,0 +1,64 @@
+//! Looking around with a gamepad, so cameras sharing a window don't fight
+//! over the mouse.
+
+use bevy::prelude::*;
+use std::ops::Mul;
+
+/// Drives a camera's look and zoom from a gamepad instead of the mouse: the
+/// right stick looks around without holding `Secondary` and the analog
+/// triggers zoom. Give each split-screen camera its own gamepad here and in
+/// its `InputMap`.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GamepadLook {
+   pub gamepad: Gamepad,
+   /// Mouse-equivalent pixels per second at full stick deflection.
+   pub sensitivity: f32,
+   /// Scroll-equivalent lines per second at full trigger pull.
+   pub zoom_rate: f32,
+   /// Stick deflection below which input is ignored.
+   pub dead_zone: f32,
+}
+
+impl GamepadLook {
+   pub fn new(gamepad: Gamepad) -> Self {
+      Self {
+         gamepad,
+         sensitivity: 800.0,
+         zoom_rate: 4.0,
+         dead_zone: 0.15,
+      }
+   }
+
+   /// This frame's look motion and zoom in the units of mouse motion and
+   /// scroll.
+   pub fn read(
+      &self,
+      axes: &Axis<GamepadAxis>,
+      buttons: &Axis<GamepadButton>,
+      dt: f32,
+   ) -> (Vec2, f32) {
+      let axis = |axis_type| {
+         axes
+            .get(GamepadAxis(self.gamepad, axis_type))
+            .unwrap_or(0.0)
+      };
+      let button = |button_type| {
+         buttons
+            .get(GamepadButton(self.gamepad, button_type))
+            .unwrap_or(0.0)
+      };
+      let mut stick = Vec2::new(
+         axis(GamepadAxisType::RightStickX),
+         axis(GamepadAxisType::RightStickY),
+      );
+      if stick.length() < self.dead_zone {
+         stick = Vec2::ZERO;
+      }
+      // Stick up looks up, where mouse motion up is negative
+      let motion = Vec2::new(stick.x, -stick.y).mul(self.sensitivity.mul(dt));
+      let zoom = (button(GamepadButtonType::RightTrigger2)
+         - button(GamepadButtonType::LeftTrigger2))
+      .mul(self.zoom_rate.mul(dt));
+      (motion, zoom)
+   }
+}
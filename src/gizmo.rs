@@ -0,0 +1,101 @@
+//! Marker drawn at the orbit pivot so users can see what they orbit around.
+
+use crate::{MovableCamera, MovableCameraParams};
+use bevy::{pbr::AlphaMode, prelude::*};
+use std::ops::{Div, Mul};
+
+/// Seconds of inactivity before the pivot gizmo starts fading out.
+const PIVOT_GIZMO_HOLD: f32 = 1.0;
+/// Size of the pivot gizmo relative to the orbit distance, which keeps it
+/// roughly the same size on screen.
+const PIVOT_GIZMO_SCALE: f32 = 0.01;
+
+/// Tags the gizmo shown at the pivot of `camera`. It is spawned as a child of
+/// the camera's rig parent, so it sits on the pivot.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PivotGizmo {
+   pub camera: Entity,
+}
+
+/// Spawns, shows, and fades the pivot gizmo of every focused camera.
+pub fn update_pivot_gizmo(
+   mut commands: Commands,
+   cam_params: Res<MovableCameraParams>,
+   mut meshes: ResMut<Assets<Mesh>>,
+   mut materials: ResMut<Assets<StandardMaterial>>,
+   q_cams: Query<(Entity, &Parent, &Transform, &MovableCamera)>,
+   mut q_gizmos: Query<
+      (
+         &PivotGizmo,
+         &mut Transform,
+         &mut Visibility,
+         &Handle<StandardMaterial>,
+      ),
+      Without<MovableCamera>,
+   >,
+) {
+   for (entity, parent, transform_child, cam) in q_cams.iter() {
+      let gizmo = q_gizmos
+         .iter_mut()
+         .find(|(gizmo, ..)| gizmo.camera == entity);
+      let (_, mut transform, mut visibility, material) = match gizmo {
+         Some(gizmo) => gizmo,
+         None => {
+            if cam_params.pivot_gizmo && cam.is_orbiting() {
+               let gizmo = commands
+                  .spawn_bundle(PbrBundle {
+                     mesh: meshes.add(Mesh::from(shape::Icosphere {
+                        radius: 1.0,
+                        subdivisions: 2,
+                     })),
+                     material: materials.add(StandardMaterial {
+                        base_color: Color::WHITE,
+                        unlit: true,
+                        alpha_mode: AlphaMode::Blend,
+                        ..Default::default()
+                     }),
+                     ..Default::default()
+                  })
+                  .insert(PivotGizmo { camera: entity })
+                  .id();
+               commands.entity(parent.0).push_children(&[gizmo]);
+            }
+            continue;
+         }
+      };
+
+      let visible = cam_params.pivot_gizmo && cam.is_orbiting();
+      if visibility.is_visible != visible {
+         visibility.is_visible = visible;
+      }
+      if !visible {
+         continue;
+      }
+      let scale = Vec3::splat(
+         transform_child
+            .translation
+            .length()
+            .mul(PIVOT_GIZMO_SCALE)
+            .max(PIVOT_GIZMO_SCALE),
+      );
+      if transform.scale != scale {
+         transform.scale = scale;
+      }
+      let alpha = if cam_params.pivot_gizmo_fade > 0.0 {
+         1.0 - (cam.idle_time - PIVOT_GIZMO_HOLD)
+            .div(cam_params.pivot_gizmo_fade)
+            .clamp(0.0, 1.0)
+      } else {
+         1.0
+      };
+      // Writing the material marks it modified, which re-uploads it
+      let faded = materials
+         .get(material)
+         .map_or(false, |material| material.base_color.a() != alpha);
+      if faded {
+         if let Some(material) = materials.get_mut(material) {
+            material.base_color.set_a(alpha);
+         }
+      }
+   }
+}
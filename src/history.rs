@@ -0,0 +1,141 @@
+//! Browser-style back/forward navigation through previously focused pivots.
+
+use crate::{
+   move_rig, rig_root, CameraActions, CameraMode, FlyingCamAction, FocusTarget, MovableCamera,
+   MovableCameraParams,
+};
+use bevy::prelude::*;
+
+/// A focused rig layout that can be returned to, in world space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusPose {
+   /// Rig parent, whose translation is the pivot.
+   pub parent: Transform,
+   /// Camera relative to the rig parent, in world units.
+   pub child: Transform,
+   pub target: Option<Entity>,
+}
+
+/// Previously focused poses of a camera, with the current one at `cursor`.
+#[derive(Clone, Debug, Default)]
+pub struct FocusHistory {
+   entries: Vec<FocusPose>,
+   cursor: usize,
+}
+
+impl FocusHistory {
+   /// Records a newly focused pose, dropping any poses ahead of the current
+   /// one. `leaving` updates the current entry to where the user left it.
+   pub fn push(&mut self, leaving: Option<FocusPose>, pose: FocusPose, max_len: usize) {
+      self.update_current(leaving);
+      self.entries.truncate(self.cursor + 1);
+      self.entries.push(pose);
+      if self.entries.len() > max_len.max(1) {
+         self.entries.remove(0);
+      }
+      self.cursor = self.entries.len() - 1;
+   }
+
+   /// Steps back to the previous pose.
+   pub fn back(&mut self, leaving: Option<FocusPose>) -> Option<FocusPose> {
+      self.update_current(leaving);
+      if self.cursor == 0 {
+         return None;
+      }
+      self.cursor -= 1;
+      self.entries.get(self.cursor).copied()
+   }
+
+   /// Steps forward to the next pose, if the user went back before.
+   pub fn forward(&mut self, leaving: Option<FocusPose>) -> Option<FocusPose> {
+      self.update_current(leaving);
+      if self.cursor + 1 >= self.entries.len() {
+         return None;
+      }
+      self.cursor += 1;
+      self.entries.get(self.cursor).copied()
+   }
+
+   /// The pose the history is currently at.
+   pub fn current(&self) -> Option<FocusPose> {
+      self.entries.get(self.cursor).copied()
+   }
+
+   pub fn clear(&mut self) {
+      self.entries.clear();
+      self.cursor = 0;
+   }
+
+   fn update_current(&mut self, leaving: Option<FocusPose>) {
+      if let (Some(leaving), Some(current)) = (leaving, self.entries.get_mut(self.cursor)) {
+         *current = leaving;
+      }
+   }
+}
+
+/// Moves focused cameras through their [`FocusHistory`] on `FocusBack` and
+/// `FocusForward`. An unfocused camera going back returns to its last focus.
+pub fn navigate_focus_history(
+   mut commands: Commands,
+   cam_params: Res<MovableCameraParams>,
+   actions: CameraActions,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&FocusTarget>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for (entity, parent, mut transform_child, mut cam, focus_target) in q_child.iter_mut() {
+      if !cam.mode().is_manual() {
+         continue;
+      }
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      let (root, child) = space.to_world(&transform_parent, &transform_child);
+      let leaving = cam.is_orbiting().then(|| FocusPose {
+         parent: root,
+         child,
+         target: focus_target.map(|focus_target| focus_target.0),
+      });
+
+      let pose = if action_state.just_pressed(FlyingCamAction::FocusBack) {
+         if cam.is_orbiting() {
+            cam.focus_history.back(leaving)
+         } else {
+            cam.focus_history.current()
+         }
+      } else if action_state.just_pressed(FlyingCamAction::FocusForward) {
+         cam.focus_history.forward(leaving)
+      } else {
+         continue;
+      };
+
+      if let Some(pose) = pose {
+         move_rig(
+            &mut commands,
+            entity,
+            cam_params.focus_duration,
+            &mut transform_parent,
+            &mut transform_child,
+            space.to_local(&pose.parent, &pose.child),
+         );
+         cam.enter_laid_out_mode(CameraMode::Orbit);
+         cam.reference_point = Some(pose.parent.translation);
+         cam.orbit_velocity = Vec2::ZERO;
+         match pose.target {
+            Some(target) => commands.entity(entity).insert(FocusTarget(target)),
+            None => commands.entity(entity).remove::<FocusTarget>(),
+         };
+      }
+   }
+}
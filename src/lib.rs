@@ -1,34 +1,449 @@
 #![allow(clippy::too_many_arguments)]
 
+use bevy::utils::HashMap;
 use bevy::{
+   ecs::{schedule::ShouldRun, system::SystemParam},
    input::mouse::{MouseMotion, MouseWheel},
+   math::DVec3,
    prelude::*,
+   render::primitives::Aabb,
+   transform::TransformSystem,
+   window::WindowId,
 };
 use leafwing_input_manager::{prelude::ActionState, Actionlike};
 use std::ops::{Div, Mul, Neg};
 
+mod attach;
+mod bookmarks;
+mod chase;
+mod clip;
+mod companion;
+mod director;
+mod drone;
+mod first_person;
+mod fixed;
+mod flight;
+mod gamepad;
+mod gizmo;
+mod history;
+mod map;
+mod minimap;
+mod mode;
+mod movable;
+mod origin;
+mod ortho;
+mod path;
+#[cfg(feature = "serde")]
+mod persist;
+mod photo;
+mod recording;
+mod recovery;
+#[cfg(feature = "replication")]
+mod replication;
+pub mod rig;
+mod script;
+mod setup;
+mod spectator;
+mod state;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+mod third_person;
+mod top_down;
+mod tween;
+mod vehicle;
+mod volume;
+mod vr;
+mod walk;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+pub use attach::*;
+pub use bookmarks::*;
+pub use chase::*;
+pub use clip::*;
+pub use companion::*;
+pub use director::*;
+pub use drone::*;
+pub use first_person::*;
+pub use fixed::*;
+pub use flight::*;
+pub use gamepad::*;
+pub use gizmo::*;
+pub use history::*;
+pub use map::*;
+pub use minimap::*;
+pub use mode::*;
+pub use movable::{
+   movable_camera_focus, movable_camera_input, movable_camera_rotation, movable_camera_translation,
+   movable_camera_zoom, MovableCameraBehaviors, MovableCameraInput, MovableCameraSystem,
+};
+pub use origin::*;
+pub use ortho::*;
+pub use path::*;
+#[cfg(feature = "serde")]
+pub use persist::*;
+pub use photo::*;
+pub use recording::*;
+pub use recovery::*;
+#[cfg(feature = "replication")]
+pub use replication::*;
+pub use rig::{update_camera_rigs, AsAnyMut, CameraRig, RigDriver};
+pub use script::*;
+use setup::CursorHolders;
+pub use setup::*;
+pub use spectator::*;
+pub use state::*;
+pub use third_person::*;
+pub use top_down::*;
+pub use tween::*;
+pub use vehicle::*;
+pub use volume::*;
+pub use vr::*;
+pub use walk::*;
+#[cfg(target_arch = "wasm32")]
+pub use web::*;
+
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum FlyingCamAction {
    AdjustSpeed,
    Back,
+   Bookmark1,
+   Bookmark2,
+   Bookmark3,
+   Bookmark4,
+   Bookmark5,
+   Bookmark6,
+   Bookmark7,
+   Bookmark8,
+   Bookmark9,
    ClickHoldSecondary,
+   Detach,
    Down,
    Focus,
+   FocusBack,
+   FocusForward,
    Forward,
    Left,
+   NextFocus,
+   Pan,
+   PhotoMode,
+   PlayPath,
+   PrevFocus,
    Primary,
+   RecordKeyframe,
+   ReleaseCursor,
    Right,
+   RotateLeft,
+   RotateRight,
+   SaveBookmark,
+   Screenshot,
    Secondary,
+   SnapOrbit,
+   Spectate,
+   StopPath,
+   SwapShoulder,
+   Tilt,
    Up,
+   ZoomIn,
+   ZoomOut,
 }
 
-/// Struct for customizing camera behavior.
-#[derive(Component)]
+/// How keyboard fly speed reacts to the scene.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpeedModel {
+   /// Fly speed only depends on the speed settings.
+   Constant,
+   /// Fly speed is multiplied by the distance to [`MovableCamera::reference_point`],
+   /// so far-away navigation is fast and close-up inspection is precise.
+   DistanceProportional,
+}
+
+/// Filter applied to raw mouse deltas before they rotate the camera.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MouseSmoothing {
+   Off,
+   /// Exponential low-pass filter. `strength` is in `[0, 1)`, higher is smoother.
+   LowPass {
+      strength: f32,
+   },
+   /// Average over the last `window` frames, capped at [`MOUSE_FILTER_CAPACITY`].
+   MovingAverage {
+      window: usize,
+   },
+}
+
+/// Maximum number of frames [`MouseSmoothing::MovingAverage`] can average over.
+pub const MOUSE_FILTER_CAPACITY: usize = 16;
+
+/// Per-camera mouse history used by [`MouseSmoothing`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MouseFilter {
+   samples: [Vec2; MOUSE_FILTER_CAPACITY],
+   next: usize,
+   smoothed: Vec2,
+}
+
+impl MouseFilter {
+   /// Feeds one frame of mouse motion through the filter and returns the smoothed motion.
+   pub fn apply(&mut self, smoothing: MouseSmoothing, delta: Vec2) -> Vec2 {
+      match smoothing {
+         MouseSmoothing::Off => delta,
+         MouseSmoothing::LowPass { strength } => {
+            let strength = strength.clamp(0.0, 0.99);
+            self.smoothed = self.smoothed.lerp(delta, 1.0 - strength);
+            // Don't let the tail of the filter drift the camera forever
+            if self.smoothed.length_squared() < 1e-6 {
+               self.smoothed = Vec2::ZERO;
+            }
+            self.smoothed
+         }
+         MouseSmoothing::MovingAverage { window } => {
+            let window = window.clamp(1, MOUSE_FILTER_CAPACITY);
+            self.samples[self.next] = delta;
+            self.next = (self.next + 1) % MOUSE_FILTER_CAPACITY;
+            let mut sum = Vec2::ZERO;
+            for i in 1..=window {
+               sum += self.samples[(self.next + MOUSE_FILTER_CAPACITY - i) % MOUSE_FILTER_CAPACITY];
+            }
+            sum.div(window as f32)
+         }
+      }
+   }
+}
+
+/// Pulls the orbit yaw of a focused camera onto evenly spaced stops, for exact
+/// front and side views.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OrbitSnap {
+   Off,
+   /// Snap to multiples of `step` radians while `SnapOrbit` is held.
+   Held {
+      step: f32,
+   },
+   /// Always snap to multiples of `step` radians.
+   Always {
+      step: f32,
+   },
+}
+
+/// Where Focus orbits when there is no [`FocusTarget`] and nothing under the cursor.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FocusFallback {
+   /// Pivot on the camera itself, turning in place.
+   Camera,
+   /// Orbit a fixed world-space point, such as the origin.
+   Point(Vec3),
+   /// Orbit the point this many units in front of the camera.
+   Ahead(f32),
+}
+
+impl FocusFallback {
+   /// The fallback pivot for a camera at the world-space pose `eye`, or
+   /// `None` to pivot on the camera.
+   pub fn pivot(&self, eye: &Transform) -> Option<Vec3> {
+      match *self {
+         FocusFallback::Camera => None,
+         FocusFallback::Point(point) => Some(point),
+         FocusFallback::Ahead(distance) => Some(eye.translation + eye.forward().mul(distance)),
+      }
+   }
+}
+
+/// How the pivot chases a moving [`FocusTarget`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PivotFollow {
+   /// Lock the pivot to the target.
+   Instant,
+   /// Exponential lag with a time constant in seconds, which smooths out a
+   /// jittery target.
+   Lag { time_constant: f32 },
+   /// Move towards the target at no more than `speed` units per second.
+   MaxSpeed { speed: f32 },
+}
+
+impl PivotFollow {
+   /// Moves `pivot` towards `target` over `dt` seconds.
+   pub fn step(&self, pivot: Vec3, target: Vec3, dt: f32) -> Vec3 {
+      match *self {
+         PivotFollow::Lag { time_constant } if time_constant > 0.0 => {
+            pivot.lerp(target, 1.0 - dt.div(time_constant).neg().exp())
+         }
+         PivotFollow::MaxSpeed { speed } => {
+            let offset = target - pivot;
+            let max_step = speed.mul(dt).max(0.0);
+            if offset.length() <= max_step {
+               target
+            } else {
+               pivot + offset.normalize().mul(max_step)
+            }
+         }
+         _ => target,
+      }
+   }
+}
+
+/// Which shoulder a third-person or chase camera frames its target over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShoulderSide {
+   Left,
+   Right,
+}
+
+impl ShoulderSide {
+   /// `-1.0` for left and `1.0` for right.
+   pub fn sign(self) -> f32 {
+      match self {
+         ShoulderSide::Left => -1.0,
+         ShoulderSide::Right => 1.0,
+      }
+   }
+
+   pub fn other(self) -> Self {
+      match self {
+         ShoulderSide::Left => ShoulderSide::Right,
+         ShoulderSide::Right => ShoulderSide::Left,
+      }
+   }
+}
+
+/// Struct for customizing camera behavior. The resource applies to every
+/// camera; a copy inserted on a camera entity overrides the speed settings of
+/// that camera.
+#[derive(Component, Clone)]
 pub struct MovableCameraParams {
    pub default_speed: f32,
-   pub acceleration: f32,
+   /// Speed reached after moving for `speed_ramp_up_time`.
+   pub max_speed: f32,
+   /// Seconds of movement taken to speed up from `default_speed` to
+   /// `max_speed`.
+   pub speed_ramp_up_time: f32,
+   /// Seconds without movement taken to slow back down to `default_speed`.
+   pub speed_ramp_down_time: f32,
    pub slow_speed: f32,
    pub scroll_snap: f32,
+   /// Lowest and highest pitch, in radians, a free flying camera can look at.
+   pub pitch_limits: (f32, f32),
+   /// Keep the position of free flying cameras in `f64` in
+   /// [`MovableCamera::precise_translation`], so slow movement stays smooth
+   /// far from the origin. The `Transform` gets it rounded to `f32`.
+   pub precise_translation: bool,
+   /// Keep orbiting after the mouse is released while focused.
+   pub orbit_inertia: bool,
+   /// Exponential decay rate of the orbit velocity, per second.
+   pub orbit_friction: f32,
+   /// Seconds without input before a focused camera starts orbiting on its own.
+   pub idle_orbit_delay: Option<f32>,
+   /// Angular speed of the idle orbit in radians per second.
+   pub idle_orbit_speed: f32,
+   /// Movement keys pan the pivot while focused instead of leaving focus mode.
+   pub focused_strafe_pans: bool,
+   /// Scale pivot panning by orbit distance so screen-space motion stays constant.
+   pub distance_scaled_pan: bool,
+   pub speed_model: SpeedModel,
+   /// Seconds to ease between normal and slow speed when toggling `AdjustSpeed`.
+   pub speed_transition_time: f32,
+   /// Per-axis multipliers for keyboard movement as (strafe, vertical, forward),
+   /// applied after the input direction is normalized.
+   pub speed_multipliers: Vec3,
+   pub mouse_smoothing: MouseSmoothing,
+   /// Scale mouse look by the current field of view relative to `reference_fov`,
+   /// so looking around stays precise while zoomed in.
+   pub fov_scaled_sensitivity: bool,
+   /// Vertical field of view, in radians, at which look sensitivity is unscaled.
+   pub reference_fov: f32,
+   /// Keep the pivot on the [`FocusTarget`] as it moves.
+   pub follow_focus_target: bool,
+   pub pivot_follow: PivotFollow,
+   /// Point the camera aims at relative to a focus or chase target, in the
+   /// target's local orientation, e.g. a character's head. `None` aims at the
+   /// pivot.
+   pub look_offset: Option<Vec3>,
+   /// Half extents of a world-space box around the followed point that a
+   /// focus or chase target can move in without moving the camera.
+   pub follow_deadzone: Option<Vec3>,
+   /// Seconds taken to glide the pivot over to a newly cycled focus target.
+   pub focus_transition_time: f32,
+   /// How much larger than the framed object the view should be, `1.0` is a tight fit.
+   pub frame_margin: f32,
+   /// Seconds the camera takes to fly into orbit when focusing. Zero jumps.
+   pub focus_duration: f32,
+   pub focus_fallback: FocusFallback,
+   /// Offset of the orbit pivot from a focused entity's origin, in the
+   /// entity's local orientation, e.g. to orbit around a character's chest.
+   pub pivot_offset: Vec3,
+   pub orbit_snap: OrbitSnap,
+   /// Horizontal drag speed, in pixels per second, above which orbit snapping
+   /// lets go so the user can drag past a stop.
+   pub orbit_snap_max_speed: f32,
+   /// Lowest and highest angle, in radians, of the orbiting camera above the
+   /// pivot's horizon.
+   pub orbit_elevation_limits: (f32, f32),
+   /// Optional arc, in radians, the orbit yaw is confined to. Zero yaw puts
+   /// the camera on the +Z side of the pivot.
+   pub orbit_yaw_limits: Option<(f32, f32)>,
+   /// Plane, given as a unit normal and its offset along the normal from the
+   /// origin, that an orbiting camera may not dip below.
+   pub ground_plane: Option<(Vec3, f32)>,
+   /// Move the near and far planes with the camera; see [`ClipPlanes`].
+   pub clip_planes: Option<ClipPlanes>,
+   /// Ground height below a point, used to keep a free-standing pivot on
+   /// terrain when there is no [`PivotSurface`] hit.
+   pub pivot_surface: Option<fn(Vec3) -> Option<f32>>,
+   /// Orbiting an orthographic camera spins the view around its axis instead
+   /// of turning it around the pivot.
+   pub ortho_roll_orbit: bool,
+   /// Smallest and largest projection scale scrolling takes an orthographic
+   /// camera to.
+   pub ortho_scale_limits: (f32, f32),
+   /// Scrolling an orthographic camera keeps the point under the cursor in
+   /// place instead of zooming on the center of the view.
+   pub ortho_zoom_to_cursor: bool,
+   /// Most focus poses kept for `FocusBack` and `FocusForward`.
+   pub focus_history_len: usize,
+   /// Seconds between two Focus presses for them to count as a double press,
+   /// which resets the orbit while keeping the pivot.
+   pub focus_reset_window: f32,
+   /// Orbit distance restored by a double press of Focus.
+   pub focus_reset_distance: f32,
+   /// Orbit elevation, in radians, also restored by a double press of Focus.
+   pub focus_reset_elevation: Option<f32>,
+   /// Show a small marker at the pivot while focused.
+   pub pivot_gizmo: bool,
+   /// Seconds the pivot marker takes to fade out once the user is idle.
+   pub pivot_gizmo_fade: f32,
+   /// Restore the last orbit distance used around a target when it is focused again.
+   pub remember_orbit_distance: bool,
+   /// Also restore the last orbit orientation around a target.
+   pub remember_orbit_rotation: bool,
+   /// Settings of [`CameraMode::Chase`] cameras.
+   pub chase: ChaseParams,
+   /// Settings of [`CameraMode::ThirdPerson`] cameras.
+   pub third_person: ThirdPersonParams,
+   /// Settings of [`CameraMode::TopDown`] cameras.
+   pub top_down: TopDownParams,
+   /// Settings of [`CameraMode::Walk`] cameras.
+   pub walk: WalkParams,
+   /// Settings of [`CameraMode::Vr`] cameras.
+   pub vr: VrParams,
+   /// Settings of [`CameraMode::Map`] cameras.
+   pub map: MapParams,
+   /// Settings of [`CameraMode::Pan2d`] cameras.
+   pub pan_2d: Pan2dParams,
+   /// Settings of [`CameraMode::Flight`] cameras.
+   pub flight: FlightParams,
+   /// Settings of [`CameraMode::Drone`] cameras.
+   pub drone: DroneParams,
+   /// How the camera flies to a recalled bookmark.
+   pub bookmark_travel: TravelStyle,
+   /// How long the spectator takes to fly back to the gameplay camera.
+   pub spectator_return: TravelTime,
+   pub spectator_return_easing: Easing,
+   /// Seconds between a recorded path keyframe and the previous one when
+   /// the previous one wasn't recorded in this session.
+   pub keyframe_spacing: f32,
+   /// Whether the wheel still zooms while the cursor is over a UI node that
+   /// reacts to it, one with an [`Interaction`].
+   pub scroll_over_ui: bool,
    // pub forward: KeyCode,
    // pub backward: KeyCode,
    // pub left: KeyCode,
@@ -43,21 +458,151 @@ impl Default for MovableCameraParams {
    fn default() -> Self {
       Self {
          default_speed: 1.0,
-         acceleration: 1.0,
+         max_speed: 4.0,
+         speed_ramp_up_time: 3.0,
+         speed_ramp_down_time: 0.2,
          slow_speed: 0.1,
          scroll_snap: 1.0,
+         pitch_limits: (
+            std::f32::consts::FRAC_PI_2.neg(),
+            std::f32::consts::FRAC_PI_2,
+         ),
+         precise_translation: false,
+         orbit_inertia: false,
+         orbit_friction: 4.0,
+         idle_orbit_delay: None,
+         idle_orbit_speed: 0.2,
+         focused_strafe_pans: false,
+         distance_scaled_pan: true,
+         speed_model: SpeedModel::Constant,
+         speed_transition_time: 0.25,
+         speed_multipliers: Vec3::ONE,
+         mouse_smoothing: MouseSmoothing::Off,
+         fov_scaled_sensitivity: false,
+         reference_fov: std::f32::consts::FRAC_PI_4,
+         follow_focus_target: true,
+         pivot_follow: PivotFollow::Instant,
+         look_offset: None,
+         follow_deadzone: None,
+         focus_transition_time: 0.5,
+         frame_margin: 1.1,
+         focus_duration: 0.4,
+         focus_fallback: FocusFallback::Camera,
+         pivot_offset: Vec3::ZERO,
+         orbit_snap: OrbitSnap::Off,
+         orbit_snap_max_speed: 300.0,
+         orbit_elevation_limits: (
+            std::f32::consts::FRAC_PI_2.neg(),
+            std::f32::consts::FRAC_PI_2,
+         ),
+         orbit_yaw_limits: None,
+         ground_plane: None,
+         clip_planes: None,
+         pivot_surface: None,
+         ortho_roll_orbit: false,
+         ortho_scale_limits: (0.01, 100.0),
+         ortho_zoom_to_cursor: false,
+         focus_history_len: 32,
+         focus_reset_window: 0.3,
+         focus_reset_distance: 5.0,
+         focus_reset_elevation: None,
+         pivot_gizmo: false,
+         pivot_gizmo_fade: 0.5,
+         remember_orbit_distance: true,
+         remember_orbit_rotation: false,
+         chase: ChaseParams::default(),
+         third_person: ThirdPersonParams::default(),
+         top_down: TopDownParams::default(),
+         walk: WalkParams::default(),
+         vr: VrParams::default(),
+         map: MapParams::default(),
+         pan_2d: Pan2dParams::default(),
+         flight: FlightParams::default(),
+         drone: DroneParams::default(),
+         bookmark_travel: TravelStyle::duration(1.0),
+         spectator_return: TravelTime::Duration(0.75),
+         spectator_return_easing: Easing::EaseInOut,
+         keyframe_spacing: 2.0,
+         scroll_over_ui: false,
       }
    }
 }
 
-/// Tags an entity as being capable of moving, rotating, and orbiting.
+/// Tags an entity as being capable of moving, rotating, and orbiting. Its
+/// input comes from an `ActionState<FlyingCamAction>` on the same entity or
+/// on the rig root; see [`CameraActions`].
 #[derive(Component)]
 pub struct MovableCamera {
    pub speed: f32,
    pub angular_speed: f32,
    pub slow: bool,
    pub cursor_pos: Vec2,
-   pub focused: bool,
+   mode: CameraMode,
+   /// Set when the mode changes, until the system driving the new mode has
+   /// taken over the rig.
+   mode_entered: bool,
+   /// Mode last announced with [`CameraModeChanged`].
+   announced_mode: CameraMode,
+   /// Orbit velocity in mouse motion units per second, used for inertia.
+   pub orbit_velocity: Vec2,
+   /// Seconds since the last user input.
+   pub idle_time: f32,
+   /// Last known point of interest, e.g. the most recent focus pivot.
+   pub reference_point: Option<Vec3>,
+   /// Progress of the slow mode transition, from 0 (normal) to 1 (slow).
+   pub slow_blend: f32,
+   /// Progress of the speed ramp, from 0 (`default_speed`) to 1 (`max_speed`).
+   pub speed_ramp: f32,
+   pub mouse_filter: MouseFilter,
+   /// Where the pivot was when the current focus transition started.
+   pub pivot_transition_start: Vec3,
+   /// Progress of the pivot moving to a new focus target, from 0 to 1 (done).
+   pub pivot_transition: f32,
+   /// Last orbit distance and rotation used around each focus target.
+   pub orbit_memory: HashMap<Entity, (f32, Quat)>,
+   pub focus_history: FocusHistory,
+   /// Velocity of the chase spring.
+   pub chase_velocity: Vec3,
+   /// Boom length requested by the user in third-person mode.
+   pub boom_length: f32,
+   /// Shoulder the third-person or chase camera is moving to.
+   pub shoulder_side: ShoulderSide,
+   /// Current shoulder position, from `-1.0` over the left shoulder to `1.0`
+   /// over the right, blending towards `shoulder_side`.
+   pub shoulder_blend: f32,
+   /// Time, in seconds since startup, of the last Focus press.
+   pub last_focus_press: Option<f64>,
+   /// Pivot requested through [`MovableCamera::set_focus`], applied next frame.
+   pub pending_focus: Option<Vec3>,
+   /// Time, in seconds since startup, the last path keyframe was recorded.
+   pub last_keyframe_at: Option<f64>,
+   /// Yaw and pitch, in radians, a path camera is looking around by.
+   pub path_look: Vec2,
+   /// Velocity a map camera keeps coasting at after a pan.
+   pub map_pan_velocity: Vec3,
+   /// Throttle of a flight camera, from 0 to 1.
+   pub throttle: f32,
+   /// Velocity a drone camera drifts at.
+   pub drone_velocity: Vec3,
+   /// Lean of a drone camera, as pitch and roll in radians.
+   pub drone_tilt: Vec2,
+   /// Pitch of a drone camera's gimbal, in radians.
+   pub drone_pitch: f32,
+   /// Whether holding `Secondary` has the cursor locked in place.
+   pub cursor_locked: bool,
+   /// Whether a first-person camera holds the cursor.
+   pub cursor_captured: bool,
+   /// Whether the window has the cursor captured for `cursor_captured`.
+   capture_applied: bool,
+   /// Whether the user let go of the cursor with `ReleaseCursor`.
+   pub cursor_released: bool,
+   /// Point a following camera tracks, which trails the target through the
+   /// follow deadzone.
+   pub follow_anchor: Option<Vec3>,
+   /// Position of a free camera in `f64`, kept while
+   /// [`MovableCameraParams::precise_translation`] is on. Moving the camera's
+   /// `Transform` elsewhere moves this along with it.
+   pub precise_translation: Option<DVec3>,
 }
 
 impl Default for MovableCamera {
@@ -67,34 +612,523 @@ impl Default for MovableCamera {
          angular_speed: MovableCameraParams::default().default_speed,
          slow: false,
          cursor_pos: Vec2::default(),
-         focused: false,
+         mode: CameraMode::Free,
+         mode_entered: false,
+         announced_mode: CameraMode::Free,
+         orbit_velocity: Vec2::ZERO,
+         idle_time: 0.0,
+         reference_point: None,
+         slow_blend: 0.0,
+         speed_ramp: 0.0,
+         mouse_filter: MouseFilter::default(),
+         pivot_transition_start: Vec3::ZERO,
+         pivot_transition: 1.0,
+         orbit_memory: HashMap::default(),
+         focus_history: FocusHistory::default(),
+         chase_velocity: Vec3::ZERO,
+         boom_length: 5.0,
+         shoulder_side: ShoulderSide::Right,
+         shoulder_blend: 1.0,
+         last_focus_press: None,
+         pending_focus: None,
+         last_keyframe_at: None,
+         path_look: Vec2::ZERO,
+         map_pan_velocity: Vec3::ZERO,
+         throttle: 0.0,
+         drone_velocity: Vec3::ZERO,
+         drone_tilt: Vec2::ZERO,
+         drone_pitch: 0.0,
+         cursor_locked: false,
+         cursor_captured: false,
+         capture_applied: false,
+         cursor_released: false,
+         follow_anchor: None,
+         precise_translation: None,
+      }
+   }
+}
+
+impl MovableCamera {
+   /// Stores the current orbit around `focus_target` so it can be restored
+   /// when that target is focused again.
+   pub fn remember_orbit(
+      &mut self,
+      focus_target: Option<&FocusTarget>,
+      transform_parent: &Transform,
+      transform_child: &Transform,
+   ) {
+      if let (true, Some(focus_target)) = (self.is_orbiting(), focus_target) {
+         self.orbit_memory.insert(
+            focus_target.0,
+            (transform_child.translation.z, transform_parent.rotation),
+         );
+      }
+   }
+
+   /// Adds a newly focused rig layout to the focus history, saving where the
+   /// user left the previous focus.
+   pub fn record_focus(
+      &mut self,
+      cam_params: &MovableCameraParams,
+      focus_target: Option<&FocusTarget>,
+      (transform_parent, transform_child): (&Transform, &Transform),
+      (parent, child): (Transform, Transform),
+      target: Option<Entity>,
+   ) {
+      let leaving = self.is_orbiting().then(|| FocusPose {
+         parent: *transform_parent,
+         child: *transform_child,
+         target: focus_target.map(|focus_target| focus_target.0),
+      });
+      self.focus_history.push(
+         leaving,
+         FocusPose {
+            parent,
+            child,
+            target,
+         },
+         cam_params.focus_history_len,
+      );
+   }
+
+   /// Orbits the camera around `pivot`, as if Focus had been pressed with the
+   /// pivot under the cursor.
+   pub fn set_focus(&mut self, pivot: Vec3) {
+      self.pending_focus = Some(pivot);
+   }
+
+   /// Updates the followed point for a target at `target`. With a follow
+   /// deadzone the point only moves once the target leaves the deadzone.
+   pub fn track(&mut self, cam_params: &MovableCameraParams, target: Vec3) -> Vec3 {
+      let anchor = match (cam_params.follow_deadzone, self.follow_anchor) {
+         (Some(half_extents), Some(anchor)) => deadzone_anchor(anchor, target, half_extents),
+         _ => target,
+      };
+      self.follow_anchor = Some(anchor);
+      anchor
+   }
+
+   /// Drops the camera back to its default speed, as if it had been standing
+   /// still.
+   pub fn reset_speed_ramp(&mut self) {
+      self.speed_ramp = 0.0;
+   }
+
+   /// Moves a third-person camera over to the other shoulder.
+   pub fn swap_shoulder(&mut self) {
+      self.shoulder_side = self.shoulder_side.other();
+   }
+
+   /// Moves a third-person or chase camera over to `side`, blending over
+   /// [`ThirdPersonParams::shoulder_swap_time`]. Does nothing if it is
+   /// already headed there.
+   pub fn set_shoulder(&mut self, side: ShoulderSide) {
+      self.shoulder_side = side;
+   }
+
+   /// The shoulder the camera is on or moving to.
+   pub fn shoulder(&self) -> ShoulderSide {
+      self.shoulder_side
+   }
+
+   /// Advances the shoulder blend by `dt` seconds and returns the eased side,
+   /// in `[-1, 1]`, to mirror the lateral framing offset by.
+   pub(crate) fn step_shoulder(&mut self, swap_time: f32, dt: f32) -> f32 {
+      let step = if swap_time > 0.0 {
+         // Crossing from one shoulder to the other covers a distance of two
+         dt.div(swap_time).mul(2.0)
+      } else {
+         2.0
+      };
+      self.shoulder_blend += (self.shoulder_side.sign() - self.shoulder_blend).clamp(-step, step);
+      smoothstep((self.shoulder_blend + 1.0).div(2.0)).mul(2.0) - 1.0
+   }
+
+   /// Applies the remembered orbit around `target`, if any, to a rig layout.
+   pub fn recall_orbit(
+      &self,
+      cam_params: &MovableCameraParams,
+      target: Entity,
+      (mut parent, mut child): (Transform, Transform),
+   ) -> (Transform, Transform) {
+      if let Some((distance, rotation)) = self.orbit_memory.get(&target) {
+         if cam_params.remember_orbit_distance {
+            child.translation.z = *distance;
+         }
+         if cam_params.remember_orbit_rotation {
+            parent.rotation = *rotation;
+         }
+      }
+      (parent, child)
+   }
+}
+
+/// Axis-aligned box the camera is not allowed to leave. Add it to the camera
+/// entity to constrain movement.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraBounds {
+   pub min: Vec3,
+   pub max: Vec3,
+   /// Distance from a face at which movement towards it starts slowing down.
+   pub slowdown_margin: f32,
+}
+
+impl CameraBounds {
+   pub fn clamp(&self, position: Vec3) -> Vec3 {
+      position.clamp(self.min, self.max)
+   }
+
+   /// Damps a world-space movement so it eases to a stop when approaching a
+   /// face instead of hitting it at full speed.
+   pub fn damp(&self, position: Vec3, delta: Vec3) -> Vec3 {
+      let mut damped = delta;
+      for axis in 0..3 {
+         let room = if delta[axis] > 0.0 {
+            self.max[axis] - position[axis]
+         } else {
+            position[axis] - self.min[axis]
+         };
+         let factor = if self.slowdown_margin > 0.0 {
+            room.div(self.slowdown_margin).clamp(0.0, 1.0)
+         } else if room > 0.0 {
+            1.0
+         } else {
+            0.0
+         };
+         damped[axis] *= factor;
       }
+      self.clamp(position + damped) - position
+   }
+}
+
+/// The entity a camera orbits around when the `Focus` action is pressed.
+/// Without it the camera focuses at its own position.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FocusTarget(pub Entity);
+
+/// Marks an entity as a stop for the `NextFocus` and `PrevFocus` actions.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Focusable;
+
+/// World-space ray from the camera through the cursor, kept up to date for
+/// cameras that have this component. `direction` is zero while the cursor is
+/// outside the window.
+///
+/// Cast it into the scene with a physics or picking backend and store the
+/// result in [`CursorHit`] to make `Focus` orbit around the point under the
+/// cursor.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CursorRay {
+   pub origin: Vec3,
+   pub direction: Vec3,
+}
+
+/// The scene point under the cursor, written by a user-supplied raycast
+/// against [`CursorRay`]. When it holds a point, `Focus` orbits around it.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CursorHit(pub Option<Vec3>);
+
+/// Height of the ground straight below the orbit pivot, written by a
+/// user-supplied raycast from [`MovableCamera::reference_point`]. While it
+/// holds a height, a free-standing pivot is kept on the ground.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PivotSurface(pub Option<f32>);
+
+/// Event that puts every movable camera into orbit around an entity and makes
+/// it the camera's [`FocusTarget`].
+#[derive(Clone, Copy, Debug)]
+pub struct FocusOn(pub Entity);
+
+/// Event that orbits every movable camera around an entity at a distance
+/// where the entity and its children fill the view, like framing a selection
+/// in a 3D editor.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameEntity(pub Entity);
+
+/// Event that orbits a camera, or every movable camera when `camera` is
+/// `None`, around a point. Unlike [`FocusOn`] the pivot stays put.
+#[derive(Clone, Copy, Debug)]
+pub struct SetFocus {
+   pub camera: Option<Entity>,
+   pub pivot: Vec3,
+}
+
+/// Labels of the flying camera systems by when they write camera transforms,
+/// for ordering systems of your own around them. Camera transforms are only
+/// written ahead of [`TransformSystem::TransformPropagate`], so what is drawn
+/// never has a rig root and its camera a frame apart:
+/// - [`Move`](Self::Move) runs in `CoreStage::Update`.
+/// - [`Recover`](Self::Recover), [`Adjust`](Self::Adjust) and
+///   [`Follow`](Self::Follow) run in that order in `CoreStage::PostUpdate`,
+///   before propagation.
+/// - [`Present`](Self::Present) runs in `CoreStage::PostUpdate` after
+///   propagation and only touches `GlobalTransform`.
+///
+/// A system of your own that moves cameras belongs in `CoreStage::Update`, or
+/// before [`Recover`](Self::Recover) in `CoreStage::PostUpdate`.
+#[derive(SystemLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlyingCamSystem {
+   /// Everything that moves cameras: the camera modes, tweens, scripts,
+   /// recordings and rigs. Read camera poses after it.
+   Move,
+   /// Puts cameras that picked up a non-finite pose back.
+   Recover,
+   /// The last changes to camera poses: rebasing the floating origin and
+   /// putting the photo roll back on.
+   Adjust,
+   /// Follows the final camera poses with companion and minimap cameras, clip
+   /// planes and camera volumes.
+   Follow,
+   /// Interpolates what is drawn between fixed camera ticks. Read drawn
+   /// poses after it.
+   Present,
+}
+
+/// Registers the flying camera systems and events. Input handling is left to
+/// `InputManagerPlugin::<FlyingCamAction>`, which must be added separately.
+pub struct FlyingCamPlugin;
+
+impl Plugin for FlyingCamPlugin {
+   fn build(&self, app: &mut App) {
+      app.init_resource::<MovableCameraParams>()
+         .init_resource::<CameraModeHooks>()
+         .init_resource::<CameraBookmarks>()
+         .init_resource::<CameraDirector>()
+         .init_resource::<CameraStack>()
+         .init_resource::<CameraMouse>()
+         .init_resource::<ScrollConsumed>()
+         .init_resource::<MovableCameraBehaviors>()
+         .init_resource::<CursorHolders>()
+         .init_resource::<VirtualTimeScale>()
+         .add_asset::<CameraRecording>()
+         .add_event::<FocusOn>()
+         .add_event::<FrameEntity>()
+         .add_event::<SetFocus>()
+         .add_event::<CameraModeChanged>()
+         .add_event::<FocusEntered>()
+         .add_event::<FocusExited>()
+         .add_event::<ControlPath>()
+         .add_event::<PathFinished>()
+         .add_event::<SaveCameraBookmark>()
+         .add_event::<RecallCameraBookmark>()
+         .add_event::<FlyTo>()
+         .add_event::<LookAt>()
+         .add_event::<CutTo>()
+         .add_event::<BlendTo>()
+         .add_event::<ScriptStepFinished>()
+         .add_event::<ScriptFinished>()
+         .add_event::<CameraVolumeEntered>()
+         .add_event::<CameraVolumeExited>()
+         .add_event::<RecordingFinished>()
+         .add_event::<ToggleSpectator>()
+         .add_event::<SpectatorEntered>()
+         .add_event::<SpectatorExited>()
+         .add_event::<SpectatorHandoff>()
+         // Input-driven systems have nothing to do until a camera is spawned,
+         // and tick on the fixed timestep when there is one
+         .add_system_to_stage(CoreStage::PreUpdate, accumulate_camera_mouse)
+         // Rigs are built before the camera systems first see a camera, and
+         // taken down once everything this frame has had its say
+         .add_system_to_stage(CoreStage::PreUpdate, setup_movable_cameras)
+         .add_system_to_stage(CoreStage::Last, teardown_movable_cameras)
+         .add_system_to_stage(CoreStage::Last, release_removed_cameras)
+         .add_system_set(
+            SystemSet::new()
+               .label(FlyingCamSystem::Move)
+               .with_run_criteria(camera_tick.label(CameraTick))
+               .with_system(lock_cursor)
+               .with_system(capture_cursor)
+               // Speeds are stepped before the movable systems use them, as in
+               // `FlyingCamState::tick`
+               .with_system(adjust_cam_speed.before(MovableCameraSystem::Input))
+               .with_system(focus_on)
+               .with_system(frame_entity)
+               .with_system(set_focus)
+               .with_system(cycle_focus)
+               .with_system(navigate_focus_history)
+               .with_system(follow_focus_target)
+               .with_system(fly_to)
+               .with_system(look_at)
+               .with_system(movable_camera_focus.label(MovableCameraSystem::Focus))
+               .with_system(
+                  movable_camera_input
+                     .label(MovableCameraSystem::Input)
+                     .after(MovableCameraSystem::Focus),
+               )
+               .with_system(
+                  movable_camera_rotation
+                     .label(MovableCameraSystem::Rotation)
+                     .after(MovableCameraSystem::Input),
+               )
+               .with_system(
+                  movable_camera_zoom
+                     .label(MovableCameraSystem::Zoom)
+                     .after(MovableCameraSystem::Rotation),
+               )
+               .with_system(
+                  movable_camera_translation
+                     .label(MovableCameraSystem::Translation)
+                     .after(MovableCameraSystem::Zoom),
+               )
+               .with_system(pan_2d_camera)
+               .with_system(chase_camera)
+               .with_system(third_person_camera)
+               .with_system(attach_camera)
+               .with_system(top_down_camera)
+               .with_system(flight_camera)
+               .with_system(map_camera)
+               .with_system(vehicle_camera)
+               .with_system(drone_camera)
+               .with_system(vr_camera)
+               .with_system(walk_camera)
+               .with_system(record_camera_path)
+               .with_system(camera_bookmarks)
+               .with_system(bookmark_tour)
+               .with_system(follow_camera_path)
+               .with_system(update_pivot_gizmo),
+         )
+         .add_system(update_cursor_ray)
+         .add_system(tween_camera.label(FlyingCamSystem::Move))
+         .add_system(control_path)
+         .add_system(run_camera_scripts.label(FlyingCamSystem::Move))
+         .add_system(play_recordings.label(FlyingCamSystem::Move))
+         .add_system(apply_camera_stack)
+         .add_system(spectator_camera.label(FlyingCamSystem::Move))
+         .add_system(direct_cameras)
+         .add_system(update_camera_rigs.label(FlyingCamSystem::Move))
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            recover_cameras
+               .label(FlyingCamSystem::Recover)
+               .before(FlyingCamSystem::Adjust)
+               .before(TransformSystem::TransformPropagate),
+         )
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            adjust_clip_planes
+               .label(FlyingCamSystem::Follow)
+               .after(FlyingCamSystem::Adjust)
+               .before(TransformSystem::TransformPropagate),
+         )
+         // Runs after the camera systems so its overrides have the last word
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            camera_volumes
+               .label(FlyingCamSystem::Follow)
+               .after(FlyingCamSystem::Adjust)
+               .before(TransformSystem::TransformPropagate),
+         )
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            sync_companion_cameras
+               .label(FlyingCamSystem::Follow)
+               .after(FlyingCamSystem::Adjust)
+               .before(TransformSystem::TransformPropagate),
+         )
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            sync_minimap_cameras
+               .label(FlyingCamSystem::Follow)
+               .after(FlyingCamSystem::Adjust)
+               .before(TransformSystem::TransformPropagate),
+         )
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            place_director_proxy
+               .after(TransformSystem::TransformPropagate)
+               .after(FlyingCamSystem::Present),
+         )
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            record_cameras
+               .after(TransformSystem::TransformPropagate)
+               .after(FlyingCamSystem::Present),
+         )
+         // Runs after every system that can change modes this frame
+         .add_system_to_stage(CoreStage::PostUpdate, announce_mode);
+      #[cfg(feature = "serde")]
+      app.add_system_to_stage(CoreStage::PostUpdate, persist_bookmarks);
+      #[cfg(feature = "egui")]
+      app.add_system_to_stage(CoreStage::PostUpdate, egui_consumes_scroll);
+      #[cfg(target_arch = "wasm32")]
+      app.init_resource::<WebPointerLock>()
+         .add_system(install_pointer_lock);
+      #[cfg(feature = "replication")]
+      app.init_resource::<ReplicationSettings>()
+         .add_event::<OutgoingCameraState>()
+         .add_event::<IncomingCameraState>()
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            send_camera_state.after(FlyingCamSystem::Adjust),
+         )
+         .add_system(apply_remote_camera_state.label(FlyingCamSystem::Move));
    }
 }
 
-/// Takes a quaternion as input and clamps it between -tau/4 and tau/4.
+/// Run criteria that skips a system while there is no [`MovableCamera`], so
+/// apps that spawn their camera late, or despawn it, don't pay for it.
+pub fn any_movable_camera(q_cams: Query<(), With<MovableCamera>>) -> ShouldRun {
+   if q_cams.is_empty() {
+      ShouldRun::No
+   } else {
+      ShouldRun::Yes
+   }
+}
+
+/// Clamps the pitch of a camera rotation between -tau/4 and tau/4, keeping
+/// its yaw and roll.
 pub fn limit_pitch(tq: Quat) -> Quat {
-   // Produce new quaternion with zeroed x and z and normalized y and w
-   // from the input quaternion.
-   // This results in a quaternion that represents the yaw component
-   // of the original quaternion.
-   // Not tested for quaternions that have roll other than 0.
-   let qy = Quat::from_xyzw(0.0, tq.y, 0.0, tq.w).normalize();
-   // Remove yaw from input quaternion, leaving pitch
-   let qp = qy.inverse().mul(tq);
-   // Convert quaternion to Euler angle
-   let pitch = Vec3::X.dot(qp.xyz()).asin().mul(2.0);
-   // Clamp angle to range
    let quarter_tau = std::f32::consts::TAU / 4.0;
-   let clamped_pitch = pitch.clamp(quarter_tau.neg(), quarter_tau);
-   // Convert angle back to quaternion
-   let qp = Quat::from_rotation_x(clamped_pitch);
-   // Multiply yaw quaternion by pitch quaternion to get constrained quaternion
-   qy.mul(qp)
+   limit_pitch_to(tq, (quarter_tau.neg(), quarter_tau))
 }
 
-/// Rotates a camera quat by a linear amount.
+/// Clamps the pitch of a camera rotation between `min_pitch` and `max_pitch`
+/// radians, negative looking down, keeping its yaw and roll. A roll of more
+/// than a quarter turn either way is taken as having pitched over the top,
+/// so the camera can't be turned upside down by pitching.
+pub fn limit_pitch_to(tq: Quat, (min_pitch, max_pitch): (f32, f32)) -> Quat {
+   // Split the rotation into a swing that points the view, made of yaw about
+   // the global y axis then pitch about the local x axis, and a twist about
+   // the view axis, which is the roll
+   let forward = tq.mul_vec3(Vec3::Z.neg());
+   let mut pitch = forward.y.clamp(-1.0, 1.0).asin();
+   let mut heading = Vec2::new(forward.x, forward.z);
+   if heading.length_squared() < 1e-8 {
+      // Looking straight up or down, where yaw and roll are the same thing;
+      // take the heading from the up vector and leave no roll
+      let up = tq.mul_vec3(Vec3::Y).mul(forward.y.signum().neg());
+      heading = Vec2::new(up.x, up.z);
+   }
+   let mut yaw = heading.x.neg().atan2(heading.y.neg());
+   let swing = Quat::from_rotation_y(yaw).mul(Quat::from_rotation_x(pitch));
+   let twist = swing.inverse().mul(tq);
+   let mut roll = twist.z.atan2(twist.w).mul(2.0);
+   if roll.abs() > std::f32::consts::PI {
+      roll -= std::f32::consts::TAU.copysign(roll);
+   }
+   if roll.abs() > std::f32::consts::FRAC_PI_2 {
+      // Upside down: the same rotation is a pitch past vertical, facing the
+      // other way
+      let half_turn = std::f32::consts::PI;
+      yaw += half_turn;
+      pitch = half_turn.copysign(pitch) - pitch;
+      roll -= half_turn.copysign(roll);
+   }
+   let clamped_pitch = pitch.clamp(min_pitch, max_pitch);
+   if clamped_pitch == pitch {
+      return tq;
+   }
+   Quat::from_rotation_y(yaw)
+      .mul(Quat::from_rotation_x(clamped_pitch))
+      .mul(Quat::from_rotation_z(roll))
+}
+
+/// Rotates a camera quat by a linear amount. A window without area, such as
+/// a minimized one, leaves the rotation as it is.
 pub fn rotate_cam_quat(window_size: Vec2, motion: Vec2, speed: f32, mut tq: Quat) -> Quat {
+   if window_size.x <= 0.0 || window_size.y <= 0.0 {
+      return tq;
+   }
    let delta_x = motion
       .x
       .div(window_size.x)
@@ -113,9 +1147,518 @@ pub fn rotate_cam_quat(window_size: Vec2, motion: Vec2, speed: f32, mut tq: Quat
    limit_pitch(tq)
 }
 
-fn get_primary_window_size(windows: &ResMut<Windows>) -> Vec2 {
-   let window = windows.get_primary().unwrap();
-   Vec2::new(window.width() as f32, window.height() as f32)
+/// Raises an orbit rotation just enough that the camera, `distance` behind
+/// `pivot`, stays on the positive side of the plane with unit `normal` at
+/// `offset` from the origin.
+pub fn clamp_orbit_above_plane(
+   pivot: Vec3,
+   rotation: Quat,
+   distance: f32,
+   normal: Vec3,
+   offset: f32,
+) -> Quat {
+   if distance <= 0.0 {
+      return rotation;
+   }
+   let back = rotation.mul_vec3(Vec3::Z);
+   let pivot_height = normal.dot(pivot) - offset;
+   // Smallest allowed component of the boom direction along the normal
+   let min_alignment = pivot_height.neg().div(distance).clamp(-1.0, 1.0);
+   let alignment = normal.dot(back).clamp(-1.0, 1.0);
+   if alignment >= min_alignment {
+      return rotation;
+   }
+   let axis = back.cross(normal);
+   if axis.length_squared() <= f32::EPSILON {
+      return rotation;
+   }
+   // Swing the boom towards the normal until it clears the plane
+   let angle = alignment.acos() - min_alignment.acos();
+   Quat::from_axis_angle(axis.normalize(), angle).mul(rotation)
+}
+
+/// Heading of a rotation around the global Y axis, in radians. Zero faces -Z.
+pub fn yaw_angle(rotation: Quat) -> f32 {
+   // Take yaw from the right vector, which stays level even when looking straight down
+   let right = rotation.mul_vec3(Vec3::X);
+   right.z.neg().atan2(right.x)
+}
+
+/// Clamps an orbit rotation to elevation limits and an optional yaw arc.
+/// Orbit rotations carry no roll, so any roll in `rotation` is dropped.
+pub fn limit_orbit(rotation: Quat, elevation: (f32, f32), yaw: Option<(f32, f32)>) -> Quat {
+   let forward = rotation.mul_vec3(Vec3::Z.neg());
+   let mut orbit_yaw = yaw_angle(rotation);
+   // Looking down means the camera is above the pivot
+   let orbit_elevation = forward.y.clamp(-1.0, 1.0).asin().neg();
+   if let Some((min, max)) = yaw {
+      orbit_yaw = orbit_yaw.clamp(min, max);
+   }
+   let orbit_elevation = orbit_elevation.clamp(elevation.0, elevation.1);
+   Quat::from_rotation_y(orbit_yaw).mul(Quat::from_rotation_x(orbit_elevation.neg()))
+}
+
+/// Computes the free-flight rig layout `(parent, child)` that renders exactly
+/// the same view as the current one. The child's full local transform is
+/// composed with the parent, so any offset or rotation it picked up while
+/// orbiting is kept and the view never jumps.
+pub fn free_rig(
+   transform_parent: &Transform,
+   transform_child: &Transform,
+) -> (Transform, Transform) {
+   (
+      Transform::default(),
+      transform_parent.mul_transform(*transform_child),
+   )
+}
+
+/// Where a camera rig sits in the world: the global transform of the entity
+/// its root is parented to, if any, and the root's own scale. The movable
+/// camera systems work on the rig in world space, with unit scale, and write
+/// it back through this, so rigs under rotated or scaled entities still move
+/// and orbit in world terms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RigSpace {
+   pub frame: GlobalTransform,
+   pub root_scale: Vec3,
+}
+
+impl Default for RigSpace {
+   fn default() -> Self {
+      Self {
+         frame: GlobalTransform::identity(),
+         root_scale: Vec3::ONE,
+      }
+   }
+}
+
+impl RigSpace {
+   /// Space of the rig rooted at `root`, placed under `frame`.
+   pub fn new(frame: Option<&GlobalTransform>, root: &Transform) -> Self {
+      Self {
+         frame: frame.copied().unwrap_or_else(GlobalTransform::identity),
+         root_scale: root.scale,
+      }
+   }
+
+   /// Whether the rig's transforms already are in world space.
+   pub fn is_identity(&self) -> bool {
+      self.frame == GlobalTransform::identity() && self.root_scale == Vec3::ONE
+   }
+
+   /// The rig `(root, camera)` in world space, with the root unscaled and
+   /// the camera's offset from it in world units.
+   pub fn to_world(&self, root: &Transform, child: &Transform) -> (Transform, Transform) {
+      if self.is_identity() {
+         return (*root, *child);
+      }
+      (
+         Transform {
+            translation: self.frame.mul_vec3(root.translation),
+            rotation: self.frame.rotation.mul(root.rotation),
+            scale: Vec3::ONE,
+         },
+         Transform {
+            translation: child.translation.mul(self.frame.scale.mul(self.root_scale)),
+            ..*child
+         },
+      )
+   }
+
+   /// Undoes [`to_world`](Self::to_world), giving back the root's scale.
+   pub fn to_local(&self, root: &Transform, child: &Transform) -> (Transform, Transform) {
+      if self.is_identity() {
+         return (*root, *child);
+      }
+      let inverse = self.frame.rotation.inverse();
+      (
+         Transform {
+            translation: inverse
+               .mul_vec3(root.translation - self.frame.translation)
+               .div(self.frame.scale),
+            rotation: inverse.mul(root.rotation),
+            scale: self.root_scale,
+         },
+         Transform {
+            translation: child.translation.div(self.frame.scale.mul(self.root_scale)),
+            ..*child
+         },
+      )
+   }
+
+   /// Writes the world-space rig `after` back to `root` and `child`. Each is
+   /// only written if it changed from `before`, its world-space rig at the
+   /// start of the frame.
+   pub fn write_back(
+      &self,
+      before: (Transform, Transform),
+      after: (Transform, Transform),
+      (root, child): (&mut Mut<Transform>, &mut Mut<Transform>),
+   ) {
+      let (local_root, local_child) = self.to_local(&after.0, &after.1);
+      if after.0 != before.0 {
+         **root = local_root;
+      }
+      if after.1 != before.1 {
+         **child = local_child;
+      }
+   }
+}
+
+/// Root of the rig a camera is parented to, and the space it sits in.
+pub(crate) fn rig_root<'a>(
+   parent: &Parent,
+   q_parent: &'a mut Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: &Query<&GlobalTransform>,
+) -> Option<(Mut<'a, Transform>, RigSpace)> {
+   let (transform_parent, root_parent) = q_parent.get_mut(parent.0).ok()?;
+   let frame = root_parent.and_then(|root_parent| q_globals.get(root_parent.0).ok());
+   let space = RigSpace::new(frame, &transform_parent);
+   Some((transform_parent, space))
+}
+
+/// Moves a free camera with `fly`, which takes and returns its world-space
+/// pose, and writes the result back into its rig.
+pub(crate) fn move_free(
+   space: RigSpace,
+   (transform_parent, transform_child): (&mut Mut<Transform>, &mut Mut<Transform>),
+   fly: impl FnOnce(Transform) -> Transform,
+) {
+   let before = space.to_world(transform_parent, transform_child);
+   let (root, child) = before;
+   let eye = root.mul_transform(child);
+   let flown = fly(eye);
+   if flown == eye {
+      return;
+   }
+   let inverse = root.rotation.inverse();
+   let child = Transform {
+      translation: inverse.mul_vec3(flown.translation - root.translation),
+      rotation: inverse.mul(flown.rotation),
+      scale: child.scale,
+   };
+   space.write_back(before, (root, child), (transform_parent, transform_child));
+}
+
+/// The orbit pivot for a focused entity, `offset` away from its origin in the
+/// entity's local orientation.
+pub fn target_pivot(target: &GlobalTransform, offset: Vec3) -> Vec3 {
+   target.translation + target.rotation.mul_vec3(offset)
+}
+
+/// Moves `anchor` just enough that `target` is inside the box with
+/// `half_extents` around it.
+pub fn deadzone_anchor(anchor: Vec3, target: Vec3, half_extents: Vec3) -> Vec3 {
+   let offset = target - anchor;
+   target - offset.clamp(half_extents.abs().neg(), half_extents.abs())
+}
+
+/// Computes the rig layout `(parent, child)` that orbits around `pivot` from
+/// the camera's world-space pose `eye`. The camera keeps its position and
+/// turns to face the pivot.
+pub fn orbit_around(pivot: Vec3, eye: Transform) -> (Transform, Transform) {
+   let offset = eye.translation - pivot;
+   let distance = offset.length();
+   // Looking straight up or down has no well defined yaw, so keep the old orientation
+   let rotation =
+      if distance > f32::EPSILON && offset.cross(Vec3::Y).length_squared() > f32::EPSILON {
+         Transform::from_translation(eye.translation)
+            .looking_at(pivot, Vec3::Y)
+            .rotation
+      } else {
+         eye.rotation
+      };
+   (
+      Transform {
+         translation: pivot,
+         rotation,
+         ..Default::default()
+      },
+      Transform::from_xyz(0.0, 0.0, distance),
+   )
+}
+
+/// Computes the world-space bounding box of an entity and all of its
+/// descendants from their [`Aabb`]s. Returns `(min, max)`.
+pub fn world_bounds(
+   root: Entity,
+   q_bounds: &Query<(Option<&Aabb>, &GlobalTransform, Option<&Children>)>,
+) -> Option<(Vec3, Vec3)> {
+   let mut bounds: Option<(Vec3, Vec3)> = None;
+   let mut stack = vec![root];
+   while let Some(entity) = stack.pop() {
+      let (aabb, global, children) = match q_bounds.get(entity) {
+         Ok(item) => item,
+         Err(_) => continue,
+      };
+      if let Some(aabb) = aabb {
+         let center = Vec3::from(aabb.center);
+         let half_extents = Vec3::from(aabb.half_extents);
+         for i in 0..8 {
+            let sign = Vec3::new(
+               if i & 1 == 0 { -1.0 } else { 1.0 },
+               if i & 2 == 0 { -1.0 } else { 1.0 },
+               if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let corner = global.mul_vec3(center + half_extents.mul(sign));
+            bounds = Some(match bounds {
+               Some((min, max)) => (min.min(corner), max.max(corner)),
+               None => (corner, corner),
+            });
+         }
+      }
+      if let Some(children) = children {
+         stack.extend(children.iter().copied());
+      }
+   }
+   bounds
+}
+
+/// Distance from which a sphere of `radius` fits in a perspective view.
+pub fn framing_distance(radius: f32, fov: f32, aspect_ratio: f32, margin: f32) -> f32 {
+   let half_fov_y = fov.div(2.0);
+   let half_fov_x = (half_fov_y.tan() * aspect_ratio).atan();
+   let half_fov = half_fov_y.min(half_fov_x);
+   radius.mul(margin).div(half_fov.sin())
+}
+
+/// Computes the world-space ray through a point of the window, given in
+/// pixels from the bottom left corner. Returns `(origin, direction)`.
+pub fn viewport_ray(
+   window_size: Vec2,
+   cursor: Vec2,
+   fov: f32,
+   aspect_ratio: f32,
+   camera: &GlobalTransform,
+) -> (Vec3, Vec3) {
+   let ndc = cursor.div(window_size).mul(2.0) - Vec2::ONE;
+   let half_height = fov.div(2.0).tan();
+   let view_direction = Vec3::new(
+      ndc.x * half_height * aspect_ratio,
+      ndc.y * half_height,
+      -1.0,
+   );
+   (
+      camera.translation,
+      camera.rotation.mul_vec3(view_direction).normalize(),
+   )
+}
+
+/// Size of window `id`, or `None` while it has no area, such as when it is
+/// minimized, so nothing divides by it.
+fn window_size(windows: &Windows, id: WindowId) -> Option<Vec2> {
+   windows
+      .get(id)
+      .map(|window| Vec2::new(window.width() as f32, window.height() as f32))
+      .filter(|size| size.x > 0.0 && size.y > 0.0)
+}
+
+/// Whether mouse input belongs to cameras rendering to window `id`, which is
+/// the case while that window has focus.
+fn window_has_mouse(windows: &Windows, id: WindowId) -> bool {
+   windows.get(id).map_or(false, |window| window.is_focused())
+}
+
+/// Finds the input driving each camera: the [`ActionState`] on the camera
+/// entity, or else on its rig root, or else on the entity the rig was set up
+/// under. Cameras with none of them are left alone.
+#[derive(SystemParam)]
+pub struct CameraActions<'w, 's> {
+   q_actions: Query<'w, 's, &'static ActionState<FlyingCamAction>>,
+   q_parents: Query<'w, 's, &'static Parent>,
+}
+
+impl<'w, 's> CameraActions<'w, 's> {
+   pub fn get(&self, camera: Entity) -> Option<&ActionState<FlyingCamAction>> {
+      self.q_actions.get(camera).ok().or_else(|| {
+         let root = self.q_parents.get(camera).ok()?;
+         self.q_actions.get(root.0).ok().or_else(|| {
+            let parent = self.q_parents.get(root.0).ok()?;
+            self.q_actions.get(parent.0).ok()
+         })
+      })
+   }
+}
+
+/// Mouse movement and scrolling this frame, read once by
+/// [`accumulate_camera_mouse`] and shared by every camera system. Mouse events
+/// aren't tied to a window, so cameras only take them while their window has
+/// the mouse.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CameraMouse {
+   pub motion: Vec2,
+   pub scroll: f32,
+}
+
+impl CameraMouse {
+   pub fn clear(&mut self) {
+      *self = Self::default();
+   }
+}
+
+/// Set when something other than the cameras took the scroll, such as a list
+/// the app scrolls itself. Checked and cleared as the next frame's mouse input
+/// is read, so set it every frame the cursor is over such a widget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScrollConsumed(pub bool);
+
+/// Sums up this frame's mouse events into [`CameraMouse`]. Scrolling is
+/// dropped when it's [`ScrollConsumed`] or, unless `scroll_over_ui` is set,
+/// when the cursor is over interactive UI.
+pub fn accumulate_camera_mouse(
+   cam_params: Res<MovableCameraParams>,
+   mut mouse: ResMut<CameraMouse>,
+   mut consumed: ResMut<ScrollConsumed>,
+   mut motion: EventReader<MouseMotion>,
+   mut scroll_evr: EventReader<MouseWheel>,
+   q_ui: Query<&Interaction>,
+) {
+   mouse.clear();
+   for ev in motion.iter() {
+      mouse.motion += ev.delta;
+   }
+   let over_ui = !cam_params.scroll_over_ui
+      && q_ui
+         .iter()
+         .any(|interaction| *interaction != Interaction::None);
+   let blocked = over_ui || consumed.0;
+   for ev in scroll_evr.iter() {
+      if !blocked {
+         mouse.scroll += ev.y;
+      }
+   }
+   if consumed.0 {
+      consumed.0 = false;
+   }
+}
+
+/// Consumes the scroll while an egui panel wants the pointer.
+#[cfg(feature = "egui")]
+pub fn egui_consumes_scroll(
+   mut egui: ResMut<bevy_egui::EguiContext>,
+   mut consumed: ResMut<ScrollConsumed>,
+) {
+   if egui.ctx_mut().wants_pointer_input() {
+      consumed.0 = true;
+   }
+}
+
+/// Which clock a camera moves by. Cameras without one follow virtual time.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeSource {
+   /// Game time, slowed down or paused by [`VirtualTimeScale`], for cameras
+   /// that are part of the game, like cinematics.
+   Virtual,
+   /// Wall-clock time, for cameras that should keep moving at full speed
+   /// whatever the game does, like a debug fly-cam.
+   Real,
+}
+
+impl Default for TimeSource {
+   fn default() -> Self {
+      Self::Virtual
+   }
+}
+
+/// How fast virtual time runs next to real time: 0.5 for half-speed slow
+/// motion, 0.0 to pause. Only cameras on [`TimeSource::Virtual`] are affected.
+#[derive(Clone, Copy, Debug)]
+pub struct VirtualTimeScale(pub f32);
+
+impl Default for VirtualTimeScale {
+   fn default() -> Self {
+      Self(1.0)
+   }
+}
+
+impl VirtualTimeScale {
+   /// How much a camera with `source` scales real time by.
+   pub fn for_source(&self, source: Option<&TimeSource>) -> f32 {
+      match source {
+         Some(TimeSource::Real) => 1.0,
+         _ => self.0.max(0.0),
+      }
+   }
+}
+
+/// Time as seen by the camera systems: the fixed [`CameraTimestep`] when there
+/// is one, otherwise the frame time.
+#[derive(SystemParam)]
+pub struct CameraTime<'w, 's> {
+   time: Res<'w, Time>,
+   timestep: Option<Res<'w, CameraTimestep>>,
+   scale: Option<Res<'w, VirtualTimeScale>>,
+   sources: Query<'w, 's, &'static TimeSource>,
+}
+
+impl<'w, 's> CameraTime<'w, 's> {
+   /// Real seconds covered by this run of the system.
+   pub fn delta_seconds(&self) -> f32 {
+      match &self.timestep {
+         Some(timestep) if timestep.step > 0.0 => timestep.step,
+         _ => self.time.delta_seconds(),
+      }
+   }
+
+   /// Seconds covered by this run of the system for `camera`, on the clock
+   /// its [`TimeSource`] picks.
+   pub fn camera_delta_seconds(&self, camera: Entity) -> f32 {
+      let scale = self.scale.as_deref().copied().unwrap_or_default();
+      self
+         .delta_seconds()
+         .mul(scale.for_source(self.sources.get(camera).ok()))
+   }
+
+   /// Wall-clock seconds since startup.
+   pub fn seconds_since_startup(&self) -> f64 {
+      self.time.seconds_since_startup()
+   }
+}
+
+/// Smallest orbit distance used when scaling pan speed, so panning never stalls at the pivot.
+const MIN_PAN_DISTANCE: f32 = 0.1;
+
+/// How quickly, per second, orbit snapping pulls the yaw onto a stop.
+const ORBIT_SNAP_STIFFNESS: f32 = 10.0;
+
+/// Scales a pan speed by the orbit distance so the pivot moves at a constant
+/// rate in screen space.
+pub fn distance_scaled_speed(speed: f32, distance: f32) -> f32 {
+   speed.mul(distance.max(MIN_PAN_DISTANCE))
+}
+
+/// World units covered by one pixel of a window `window_height` pixels tall,
+/// at `distance` in front of a perspective camera.
+pub fn pan_units_per_pixel(distance: f32, fov: f32, window_height: f32) -> f32 {
+   if window_height > 0.0 {
+      distance.mul(2.0).mul(fov.div(2.0).tan()).div(window_height)
+   } else {
+      0.0
+   }
+}
+
+/// Ratio of look sensitivity at `fov` compared to `reference_fov`.
+pub fn fov_sensitivity_scale(fov: f32, reference_fov: f32) -> f32 {
+   let reference = reference_fov.div(2.0).tan();
+   if reference > 0.0 {
+      fov.div(2.0).tan().div(reference)
+   } else {
+      1.0
+   }
+}
+
+/// Eases `t` in `[0, 1]` in and out.
+fn smoothstep(t: f32) -> f32 {
+   t * t * (3.0 - 2.0 * t)
+}
+
+fn movement_pressed(action_state: &ActionState<FlyingCamAction>) -> bool {
+   action_state.pressed(FlyingCamAction::Forward)
+      || action_state.pressed(FlyingCamAction::Back)
+      || action_state.pressed(FlyingCamAction::Left)
+      || action_state.pressed(FlyingCamAction::Right)
+      || action_state.pressed(FlyingCamAction::Up)
+      || action_state.pressed(FlyingCamAction::Down)
 }
 
 fn net_movement(
@@ -133,216 +1676,593 @@ fn net_movement(
    }
 }
 
-/// Prevents the cursor from moving.
+/// Updates [`CursorRay`] from the cursor position in each camera's window.
+pub fn update_cursor_ray(
+   windows: Res<Windows>,
+   mut q_cam: Query<(
+      &Camera,
+      &GlobalTransform,
+      Option<&PerspectiveProjection>,
+      Option<&OrthographicProjection>,
+      &mut CursorRay,
+   )>,
+) {
+   for (camera, global, perspective, orthographic, mut ray) in q_cam.iter_mut() {
+      let window = match windows.get(camera.window) {
+         Some(window) => window,
+         None => continue,
+      };
+      // Minimized, so there is nothing to point at
+      let window_size = match window_size(&windows, camera.window) {
+         Some(window_size) => window_size,
+         None => continue,
+      };
+      *ray = match window.cursor_position() {
+         Some(cursor) => {
+            let (origin, direction) = match (perspective, orthographic) {
+               (_, Some(orthographic)) => {
+                  ortho_viewport_ray(window_size, cursor, orthographic, global)
+               }
+               (perspective, None) => viewport_ray(
+                  window_size,
+                  cursor,
+                  perspective.map_or(std::f32::consts::FRAC_PI_4, |projection| projection.fov),
+                  perspective.map_or(window_size.x.div(window_size.y), |projection| {
+                     projection.aspect_ratio
+                  }),
+                  global,
+               ),
+            };
+            CursorRay { origin, direction }
+         }
+         None => CursorRay {
+            origin: global.translation,
+            direction: Vec3::ZERO,
+         },
+      };
+   }
+}
+
+/// Keeps the cursor in place while `Secondary` is held. The lock is let go as
+/// soon as the button is up, the window loses focus or the input goes away,
+/// and taken again if the window gets focus back with the button still held.
+///
+/// This is the only camera system that changes windows; it also applies the
+/// first-person capture decided by [`capture_cursor`], so the others can all
+/// share read-only access to [`Windows`]; only [`release_removed_cameras`]
+/// also hands the cursor back, for cameras that are gone. In a browser the
+/// page owns the cursor instead, and `WebPointerLock` locks it.
 pub fn lock_cursor(
    mut windows: ResMut<Windows>,
-   action_state: Query<&ActionState<FlyingCamAction>>,
-   mut cam: Query<&mut MovableCamera>,
+   mut holders: ResMut<CursorHolders>,
+   actions: CameraActions,
+   mut cam: Query<(Entity, &Camera, &mut MovableCamera)>,
 ) {
-   let action_state = action_state.single();
-   let mut cam = cam.single_mut();
-   if action_state.just_pressed(FlyingCamAction::Secondary) {
-      if let Some(window) = windows.get_primary_mut() {
-         window.set_cursor_lock_mode(true);
-         if let Some(pos) = window.cursor_position() {
-            cam.cursor_pos = pos;
+   let owns_cursor = !cfg!(target_arch = "wasm32");
+   for (entity, camera, mut cam) in cam.iter_mut() {
+      let window = match windows.get_mut(camera.window) {
+         Some(window) => window,
+         // Closed mid-drag, which took the lock with it
+         None => {
+            cam.cursor_locked = false;
+            cam.capture_applied = false;
+            holders.0.remove(&entity);
+            continue;
+         }
+      };
+      if cam.cursor_captured != cam.capture_applied {
+         if owns_cursor {
+            window.set_cursor_lock_mode(cam.cursor_captured);
+            window.set_cursor_visibility(!cam.cursor_captured);
          }
+         cam.capture_applied = cam.cursor_captured;
       }
-   }
+      // First-person cameras keep the cursor captured instead
+      if cam.mode() == CameraMode::FirstPerson {
+         cam.cursor_locked = false;
+      } else {
+         let held = actions.get(entity).map_or(false, |action_state| {
+            action_state.pressed(FlyingCamAction::Secondary)
+         });
+         let lock = held && window.is_focused();
+         if lock && !cam.cursor_locked {
+            if owns_cursor {
+               window.set_cursor_lock_mode(true);
+            }
+            if let Some(pos) = window.cursor_position() {
+               cam.cursor_pos = pos;
+            }
+            cam.cursor_locked = true;
+         } else if !lock && cam.cursor_locked {
+            if owns_cursor {
+               window.set_cursor_lock_mode(false);
+            }
+            cam.cursor_locked = false;
+         }
 
-   if action_state.just_released(FlyingCamAction::Secondary) {
-      if let Some(window) = windows.get_primary_mut() {
-         window.set_cursor_lock_mode(false);
+         // Browsers can't move the cursor, so there it drags instead
+         if cam.cursor_locked && owns_cursor {
+            window.set_cursor_position(cam.cursor_pos);
+         }
       }
-   }
 
-   if action_state.pressed(FlyingCamAction::Secondary) {
-      if let Some(window) = windows.get_primary_mut() {
-         window.set_cursor_position(cam.cursor_pos);
+      if cam.cursor_locked || cam.capture_applied {
+         holders.0.insert(entity, camera.window);
+      } else {
+         holders.0.remove(&entity);
       }
    }
 }
 
-/// Adjusts the camera speed based on user input.
+/// Adjusts the speed of each camera based on its own input, with the camera's
+/// [`MovableCameraParams`] if it has them.
 pub fn adjust_cam_speed(
-   time: Res<Time>,
-   action_state: Query<&ActionState<FlyingCamAction>>,
+   time: CameraTime,
+   actions: CameraActions,
    cam_params: Res<MovableCameraParams>,
-   mut cam: Query<&mut MovableCamera>,
+   mut q_cam: Query<(Entity, &mut MovableCamera, Option<&MovableCameraParams>)>,
+) {
+   for (entity, mut cam, own_params) in q_cam.iter_mut() {
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      adjust_speed(
+         time.camera_delta_seconds(entity),
+         action_state,
+         own_params.unwrap_or(&cam_params),
+         &mut cam,
+      );
+   }
+}
+
+fn adjust_speed(
+   dt: f32,
+   action_state: &ActionState<FlyingCamAction>,
+   cam_params: &MovableCameraParams,
+   cam: &mut MovableCamera,
 ) {
-   let action_state = action_state.single();
-   let mut cam = cam.single_mut();
    if action_state.just_pressed(FlyingCamAction::AdjustSpeed) {
       cam.slow = !cam.slow;
-      if !cam.slow {
-         cam.speed = cam_params.default_speed;
-         cam.angular_speed = cam_params.default_speed;
-      }
    }
+   let (speed, angular_speed) = step_speed(
+      cam_params,
+      dt,
+      movement_pressed(action_state),
+      cam.slow,
+      (&mut cam.slow_blend, &mut cam.speed_ramp),
+   );
+   cam.speed = speed;
+   cam.angular_speed = angular_speed;
+}
 
-   if cam.slow {
-      cam.speed = cam_params.slow_speed;
-      cam.angular_speed = cam_params.slow_speed;
-   } else if action_state.pressed(FlyingCamAction::Forward)
-      || action_state.pressed(FlyingCamAction::Back)
-      || action_state.pressed(FlyingCamAction::Left)
-      || action_state.pressed(FlyingCamAction::Right)
-      || action_state.pressed(FlyingCamAction::Up)
-      || action_state.pressed(FlyingCamAction::Down)
-   {
-      cam.speed += cam_params.acceleration.mul(time.delta_seconds());
+/// Advances the slow mode blend and the speed ramp by `dt` seconds and
+/// returns the movement and turning speeds.
+fn step_speed(
+   cam_params: &MovableCameraParams,
+   dt: f32,
+   moving: bool,
+   slow: bool,
+   (slow_blend, speed_ramp): (&mut f32, &mut f32),
+) -> (f32, f32) {
+   // Walk the blend towards the requested mode over the transition time
+   let target_blend = if slow { 1.0 } else { 0.0 };
+   if cam_params.speed_transition_time > 0.0 {
+      let step = dt.div(cam_params.speed_transition_time);
+      *slow_blend += (target_blend - *slow_blend).clamp(step.neg(), step);
    } else {
-      cam.speed = cam_params.default_speed;
-      cam.angular_speed = cam_params.default_speed;
+      *slow_blend = target_blend;
    }
+
+   // Ramp up while any movement is held, so turning mid-flight keeps the
+   // speed, and ease back down once it stops
+   let ramp_step = |ramp_time: f32| {
+      if ramp_time > 0.0 {
+         dt.div(ramp_time)
+      } else {
+         1.0
+      }
+   };
+   *speed_ramp = if moving {
+      (*speed_ramp + ramp_step(cam_params.speed_ramp_up_time)).min(1.0)
+   } else {
+      (*speed_ramp - ramp_step(cam_params.speed_ramp_down_time)).max(0.0)
+   };
+   let max_speed = cam_params.max_speed.max(cam_params.default_speed);
+   let ramped =
+      cam_params.default_speed + (max_speed - cam_params.default_speed) * smoothstep(*speed_ramp);
+
+   // Smoothstep so the speed change eases in and out
+   let t = smoothstep(*slow_blend);
+   (
+      ramped + (cam_params.slow_speed - ramped) * t,
+      cam_params.default_speed + (cam_params.slow_speed - cam_params.default_speed) * t,
+   )
 }
 
-/// Move the camera with QWEASD, zoom with wheel, focus at
-/// camera pos with F, and rotate/orbit with right mouse button.
-pub fn movable_camera(
-   windows: ResMut<Windows>,
-   time: Res<Time>,
-   action_state: Query<&ActionState<FlyingCamAction>>,
-   mut motion: EventReader<MouseMotion>,
-   mut scroll_evr: EventReader<MouseWheel>,
+/// Handles [`FocusOn`] events by orbiting cameras around the requested entity.
+pub fn focus_on(
+   mut commands: Commands,
+   mut events: EventReader<FocusOn>,
    cam_params: Res<MovableCameraParams>,
    mut q_child: Query<(
+      Entity,
       &Parent,
       &mut Transform,
       &mut MovableCamera,
-      &PerspectiveProjection,
+      Option<&FocusTarget>,
    )>,
-   mut q_parent: Query<(&mut Transform, &GlobalTransform), Without<PerspectiveProjection>>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
 ) {
-   let action_state = action_state.single();
-   for (parent, mut transform_child, mut cam, ..) in q_child.iter_mut() {
-      // Focused Camera
-      if cam.focused {
-         if action_state.pressed(FlyingCamAction::Forward)
-            || action_state.pressed(FlyingCamAction::Back)
-            || action_state.pressed(FlyingCamAction::Left)
-            || action_state.pressed(FlyingCamAction::Right)
-            || action_state.pressed(FlyingCamAction::Up)
-            || action_state.pressed(FlyingCamAction::Down)
-         {
-            if let Ok((mut transform_parent, ..)) = q_parent.get_mut(parent.0) {
-               let zoom = transform_child.translation.z;
-               // Set child transform to parent transform
-               *transform_child = *transform_parent;
-               // Offset child by its zoom
-               transform_child.translation += zoom.mul(transform_parent.back());
-               // Set parent transform to origin
-               *transform_parent = Transform::default();
-            }
-            cam.focused = false;
+   for FocusOn(target) in events.iter() {
+      let pivot = match q_globals.get(*target) {
+         Ok(global) => target_pivot(global, cam_params.pivot_offset),
+         Err(_) => continue,
+      };
+      for (entity, parent, mut transform_child, mut cam, focus_target) in q_child.iter_mut() {
+         if !cam.mode().is_manual() {
+            continue;
          }
-      } else if action_state.just_pressed(FlyingCamAction::Focus) {
-         if let Ok((mut transform_parent, ..)) = q_parent.get_mut(parent.0) {
-            // Hand off position and orientation information to parent
-            *transform_parent = *transform_child;
+         if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+            let (root, child) = space.to_world(&transform_parent, &transform_child);
+            cam.remember_orbit(focus_target, &root, &child);
+            let eye = root.mul_transform(child);
+            let rig = cam.recall_orbit(&cam_params, *target, orbit_around(pivot, eye));
+            cam.record_focus(
+               &cam_params,
+               focus_target,
+               (&root, &child),
+               rig,
+               Some(*target),
+            );
+            move_rig(
+               &mut commands,
+               entity,
+               cam_params.focus_duration,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&rig.0, &rig.1),
+            );
+            cam.enter_laid_out_mode(CameraMode::Orbit);
+            cam.reference_point = Some(pivot);
+            cam.orbit_velocity = Vec2::ZERO;
+            commands.entity(entity).insert(FocusTarget(*target));
          }
-         *transform_child = Transform::default();
-         cam.focused = true;
       }
+   }
+}
 
-      let mut rotation_move = Vec2::ZERO;
-      let mut scroll = 0.0;
+/// Handles [`SetFocus`] events and [`MovableCamera::set_focus`] requests by
+/// orbiting cameras around the requested point.
+pub fn set_focus(
+   mut commands: Commands,
+   mut events: EventReader<SetFocus>,
+   mut pending: Local<Vec<SetFocus>>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&FocusTarget>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   pending.clear();
+   pending.extend(events.iter().copied());
+   for (entity, parent, mut transform_child, mut cam, focus_target) in q_child.iter_mut() {
+      if !cam.mode().is_manual() {
+         continue;
+      }
+      let pivot = pending
+         .iter()
+         .rev()
+         .find(|event| event.camera.map_or(true, |camera| camera == entity))
+         .map(|event| event.pivot)
+         .or_else(|| cam.pending_focus.take());
+      let pivot = match pivot {
+         Some(pivot) => pivot,
+         None => continue,
+      };
+      cam.pending_focus = None;
+      if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         cam.remember_orbit(focus_target, &root, &child);
+         let eye = root.mul_transform(child);
+         let rig = orbit_around(pivot, eye);
+         cam.record_focus(&cam_params, focus_target, (&root, &child), rig, None);
+         move_rig(
+            &mut commands,
+            entity,
+            cam_params.focus_duration,
+            &mut transform_parent,
+            &mut transform_child,
+            space.to_local(&rig.0, &rig.1),
+         );
+         cam.enter_laid_out_mode(CameraMode::Orbit);
+         cam.reference_point = Some(pivot);
+         cam.orbit_velocity = Vec2::ZERO;
+         commands.entity(entity).remove::<FocusTarget>();
+      }
+   }
+}
 
-      if action_state.pressed(FlyingCamAction::Secondary) {
-         for ev in motion.iter() {
-            rotation_move += ev.delta;
+/// Handles [`FrameEntity`] events by orbiting cameras around the target's
+/// bounds at a distance where it fits the view. The view direction is kept.
+/// Orthographic cameras center the bounds and fit them by projection scale.
+pub fn frame_entity(
+   mut commands: Commands,
+   mut events: EventReader<FrameEntity>,
+   cam_params: Res<MovableCameraParams>,
+   q_bounds: Query<(Option<&Aabb>, &GlobalTransform, Option<&Children>)>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&PerspectiveProjection>,
+      Option<&mut OrthographicProjection>,
+      Option<&FocusTarget>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for FrameEntity(target) in events.iter() {
+      let (center, radius) = match world_bounds(*target, &q_bounds) {
+         Some((min, max)) => ((min + max).div(2.0), (max - min).length().div(2.0)),
+         None => match q_bounds.get(*target) {
+            // Nothing to measure, so just look at the entity's origin
+            Ok((_, global, _)) => (global.translation, 0.0),
+            Err(_) => continue,
+         },
+      };
+      for (entity, parent, mut transform_child, mut cam, perspective, orthographic, focus_target) in
+         q_child.iter_mut()
+      {
+         if !cam.mode().is_manual() {
+            continue;
+         }
+         if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+            let (root, child) = space.to_world(&transform_parent, &transform_child);
+            cam.remember_orbit(focus_target, &root, &child);
+            let eye = root.mul_transform(child);
+            let rig = if let Some(mut projection) = orthographic {
+               let (min_scale, max_scale) = cam_params.ortho_scale_limits;
+               projection.scale = ortho_framing_scale(radius, &projection, cam_params.frame_margin)
+                  .clamp(min_scale, max_scale);
+               ortho::centered_rig(center, eye)
+            } else {
+               let (fov, aspect_ratio) = perspective
+                  .map_or((std::f32::consts::FRAC_PI_4, 1.0), |projection| {
+                     (projection.fov, projection.aspect_ratio)
+                  });
+               let distance = framing_distance(radius, fov, aspect_ratio, cam_params.frame_margin);
+               (
+                  Transform {
+                     translation: center,
+                     rotation: eye.rotation,
+                     ..Default::default()
+                  },
+                  Transform::from_xyz(0.0, 0.0, distance),
+               )
+            };
+            cam.record_focus(&cam_params, focus_target, (&root, &child), rig, None);
+            move_rig(
+               &mut commands,
+               entity,
+               cam_params.focus_duration,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&rig.0, &rig.1),
+            );
+            cam.enter_laid_out_mode(CameraMode::Orbit);
+            cam.reference_point = Some(center);
+            cam.orbit_velocity = Vec2::ZERO;
+            // The pivot is the bounds center rather than the entity's origin,
+            // so don't let target following drag it away
+            commands.entity(entity).remove::<FocusTarget>();
          }
       }
+   }
+}
 
-      for ev in scroll_evr.iter() {
-         scroll += ev.y;
+/// Moves the pivot of focused cameras along with their [`FocusTarget`], and
+/// aims them at the target's look offset.
+pub fn follow_focus_target(
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   q_targets: Query<&GlobalTransform>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &FocusTarget,
+      Option<&CameraTween>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+) {
+   for (entity, parent, mut transform_child, mut cam, focus_target, tween) in q_child.iter_mut() {
+      if !cam.is_orbiting() {
+         continue;
       }
+      let dt = time.camera_delta_seconds(entity);
+      let global = match q_targets.get(focus_target.0) {
+         Ok(global) => global,
+         Err(_) => continue,
+      };
+      let target = target_pivot(global, cam_params.pivot_offset);
+      if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_targets) {
+         let before = space.to_world(&transform_parent, &transform_child);
+         let (mut root, mut child) = before;
+         if cam.pivot_transition < 1.0 {
+            // Glide over to a newly selected target
+            let step = if cam_params.focus_transition_time > 0.0 {
+               dt.div(cam_params.focus_transition_time)
+            } else {
+               1.0
+            };
+            cam.pivot_transition = (cam.pivot_transition + step).min(1.0);
+            root.translation = cam
+               .pivot_transition_start
+               .lerp(target, smoothstep(cam.pivot_transition));
+            cam.follow_anchor = Some(target);
+         } else if cam_params.follow_focus_target {
+            let target = cam.track(&cam_params, target);
+            // A resting target leaves the rig untouched
+            root.translation = cam_params.pivot_follow.step(root.translation, target, dt);
+         }
+         cam.reference_point = Some(root.translation);
 
-      if cam.focused {
-         // Orbit the camera
-         if rotation_move.length_squared() > 0.0 {
-            if let Ok((mut transform_parent, ..)) = q_parent.get_mut(parent.0) {
-               let window_size = get_primary_window_size(&windows);
-               transform_parent.rotation = rotate_cam_quat(
-                  window_size,
-                  rotation_move,
-                  cam.angular_speed,
-                  transform_parent.rotation,
-               );
+         // Aim at the look point while still orbiting the pivot
+         if let (Some(look_offset), None) = (cam_params.look_offset, tween) {
+            let look = root
+               .compute_matrix()
+               .inverse()
+               .transform_point3(target_pivot(global, look_offset));
+            let to_look = look - child.translation;
+            if to_look.cross(Vec3::Y).length_squared() > f32::EPSILON {
+               child.rotation = Transform::from_translation(child.translation)
+                  .looking_at(look, Vec3::Y)
+                  .rotation;
             }
          }
+         space.write_back(
+            before,
+            (root, child),
+            (&mut transform_parent, &mut transform_child),
+         );
+      }
+   }
+}
 
-         // Zoom the camera. Parent has orientation information so just
-         // mutate child's z
-         if scroll.abs() > 0.0 {
-            transform_child.translation -= Vec3::new(0.0, 0.0, 1.0)
-               .mul(cam_params.scroll_snap)
-               .mul(scroll)
-               .mul(cam.speed);
-            // Clamp the child's translation so it can't go past focus (the parent)
-            transform_child.translation = transform_child.translation.max(Vec3::new(0.0, 0.0, 0.0));
-         }
-      // Free Camera
+/// Cycles the focus of every movable camera through the [`Focusable`]
+/// entities, ordered by entity id, on `NextFocus` and `PrevFocus`.
+pub fn cycle_focus(
+   mut commands: Commands,
+   cam_params: Res<MovableCameraParams>,
+   actions: CameraActions,
+   q_focusable: Query<(Entity, &GlobalTransform), With<Focusable>>,
+   mut focusables: Local<Vec<(Entity, Vec3)>>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&FocusTarget>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let mut gathered = false;
+
+   for (entity, parent, mut transform_child, mut cam, focus_target) in q_child.iter_mut() {
+      if !cam.mode().is_manual() {
+         continue;
+      }
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let step = if action_state.just_pressed(FlyingCamAction::NextFocus) {
+         1
+      } else if action_state.just_pressed(FlyingCamAction::PrevFocus) {
+         -1
       } else {
-         // Rotate the camera
-         if rotation_move.length_squared() > 0.0 {
-            let window_size = get_primary_window_size(&windows);
-            transform_child.rotation = rotate_cam_quat(
-               window_size,
-               rotation_move,
-               cam.angular_speed,
-               transform_child.rotation,
-            );
-         }
+         continue;
+      };
 
-         // Zoom the camera relative to camera orientation
-         if scroll.abs() > 0.0 {
-            let transform_clone = *transform_child;
-            transform_child.translation += transform_clone
-               .forward()
-               .mul(cam_params.scroll_snap)
-               .mul(scroll)
-               .mul(cam.speed);
-         }
+      // Only gather the focusable entities once someone asks to cycle
+      if !gathered {
+         focusables.clear();
+         focusables.extend(
+            q_focusable
+               .iter()
+               .map(|(entity, global)| (entity, target_pivot(global, cam_params.pivot_offset))),
+         );
+         focusables.sort_by_key(|(entity, _)| *entity);
+         gathered = true;
+      }
+      if focusables.is_empty() {
+         continue;
+      }
+      let count = focusables.len() as isize;
 
-         let mut translate_move = Vec3::new(
-            net_movement(action_state, FlyingCamAction::Right, FlyingCamAction::Left),
-            net_movement(action_state, FlyingCamAction::Down, FlyingCamAction::Up),
-            net_movement(
-               action_state,
-               FlyingCamAction::Back,
-               FlyingCamAction::Forward,
-            ),
-         )
-         .normalize_or_zero();
-
-         // Translate the camera
-         if translate_move.length_squared() > 0.0 {
-            translate_move = translate_move.mul(time.delta_seconds()).mul(cam.speed);
-            // Clone the child's transform so we can use its immutable methods
-            let transform_clone = *transform_child;
-            // Translate camera along each of its local axes
-            transform_child.translation += transform_clone.left().mul(translate_move.x);
-            transform_child.translation += transform_clone.up().mul(translate_move.y);
-            transform_child.translation += transform_clone.forward().mul(translate_move.z);
+      let current = focus_target
+         .and_then(|focus_target| focusables.iter().position(|(e, _)| *e == focus_target.0));
+      let index = match current {
+         Some(index) => (index as isize + step).rem_euclid(count) as usize,
+         None if step > 0 => 0,
+         None => focusables.len() - 1,
+      };
+      let (target, pivot) = focusables[index];
+      if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         if cam.is_orbiting() {
+            cam.remember_orbit(focus_target, &root, &child);
+            // Let `follow_focus_target` glide the pivot over, while the
+            // remembered orbit for the new target eases in
+            cam.pivot_transition_start = root.translation;
+            cam.pivot_transition = 0.0;
+            let rig = cam.recall_orbit(&cam_params, target, (root, child));
+            let arrived = (
+               Transform {
+                  translation: pivot,
+                  ..rig.0
+               },
+               rig.1,
+            );
+            cam.record_focus(
+               &cam_params,
+               focus_target,
+               (&root, &child),
+               arrived,
+               Some(target),
+            );
+            move_rig(
+               &mut commands,
+               entity,
+               cam_params.focus_transition_time,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&rig.0, &rig.1),
+            );
+         } else {
+            let eye = root.mul_transform(child);
+            let rig = cam.recall_orbit(&cam_params, target, orbit_around(pivot, eye));
+            cam.record_focus(
+               &cam_params,
+               focus_target,
+               (&root, &child),
+               rig,
+               Some(target),
+            );
+            move_rig(
+               &mut commands,
+               entity,
+               cam_params.focus_duration,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&rig.0, &rig.1),
+            );
+            cam.enter_laid_out_mode(CameraMode::Orbit);
+            cam.orbit_velocity = Vec2::ZERO;
          }
+         cam.reference_point = Some(pivot);
+         commands.entity(entity).insert(FocusTarget(target));
       }
    }
 }
 
-/// Spawn a camera like this. Note the extra bundle.
+/// Spawn a camera like this. The rig is built around it on the next frame,
+/// under whatever parent the camera has.
 pub fn spawn_camera(mut commands: Commands) {
-   let mut cam = PerspectiveCameraBundle {
-      transform: Transform::from_xyz(0.0, 3.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
-      ..Default::default()
-   };
-   cam.camera.near = -1.0;
    commands
-      .spawn_bundle((
-         Transform::from_xyz(0.0, 0.0, 0.0),
-         GlobalTransform::default(),
-      ))
-      .with_children(|parent| {
-         parent.spawn_bundle(cam).insert(MovableCamera::default());
-      });
+      .spawn_bundle(PerspectiveCameraBundle {
+         transform: Transform::from_xyz(0.0, 3.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+         ..Default::default()
+      })
+      .insert(MovableCamera::default());
 }
@@ -0,0 +1,205 @@
+//! [`CameraMode::Map`]: the map viewer camera of GIS and mapping apps, which
+//! works with the ground point under the cursor and never tips over the
+//! horizon.
+
+use crate::{
+   ground_intersection, rig_root, viewport_ray, window_has_mouse, window_size, yaw_angle,
+   CameraActions, CameraMode, CameraMouse, CameraTime, FlyingCamAction, MovableCamera,
+   MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::{Div, Mul, Neg};
+
+/// Fraction of the distance that one line of scrolling zooms by.
+const MAP_ZOOM_STEP: f32 = 0.1;
+
+/// Settings of [`CameraMode::Map`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapParams {
+   /// Shallowest and steepest pitch, in radians below the horizon.
+   pub pitch_limits: (f32, f32),
+   /// Closest and farthest the camera gets to the ground point it looks at.
+   pub distance_limits: (f32, f32),
+}
+
+impl Default for MapParams {
+   fn default() -> Self {
+      Self {
+         pitch_limits: (0.2, std::f32::consts::FRAC_PI_2 - 0.01),
+         distance_limits: (1.0, 10_000.0),
+      }
+   }
+}
+
+/// Rig layout for a map camera looking at `center` from `distance` away,
+/// turned by `yaw` and tilted `pitch` radians below the horizon.
+pub fn map_rig(center: Vec3, yaw: f32, pitch: f32, distance: f32) -> (Transform, Transform) {
+   (
+      Transform {
+         translation: center,
+         rotation: Quat::from_rotation_y(yaw).mul(Quat::from_rotation_x(pitch.neg())),
+         ..Default::default()
+      },
+      Transform::from_xyz(0.0, 0.0, distance),
+   )
+}
+
+/// Drives map cameras: `Pan` drags the ground along with the cursor and
+/// coasts on release, `Secondary` turns around the ground point under the
+/// cursor, `Tilt` changes the pitch within [`MapParams::pitch_limits`], and
+/// scrolling zooms towards the cursor.
+pub fn map_camera(
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      Entity,
+      &Camera,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &GlobalTransform,
+      &PerspectiveProjection,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let motion_total = mouse.motion;
+   let scroll_total = mouse.scroll;
+   let ground = cam_params.top_down.ground_height;
+   let (min_pitch, max_pitch) = cam_params.map.pitch_limits;
+   let (min_distance, max_distance) = cam_params.map.distance_limits;
+
+   for (entity, camera, parent, mut transform_child, mut cam, global, projection) in
+      q_child.iter_mut()
+   {
+      let dt = time.camera_delta_seconds(entity);
+      if cam.mode() != CameraMode::Map {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+
+      // Look at the ground point at the center of the current view
+      if cam.mode_entered {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         let eye = root.mul_transform(child);
+         let center = ground_intersection(eye.translation, eye.forward(), ground)
+            .unwrap_or_else(|| Vec3::new(eye.translation.x, ground, eye.translation.z));
+         let distance = eye
+            .translation
+            .distance(center)
+            .clamp(min_distance, max_distance);
+         let pitch = eye.forward().y.neg().asin().clamp(min_pitch, max_pitch);
+         let (rig_parent, rig_child) = map_rig(center, yaw_angle(eye.rotation), pitch, distance);
+         let (rig_parent, rig_child) = space.to_local(&rig_parent, &rig_child);
+         *transform_parent = rig_parent;
+         *transform_child = rig_child;
+         cam.map_pan_velocity = Vec3::ZERO;
+         cam.mode_entered = false;
+      }
+
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let before = space.to_world(&transform_parent, &transform_child);
+      let (root, child) = before;
+      let mut center = root.translation;
+      let mut yaw = yaw_angle(root.rotation);
+      let forward = root.rotation.mul_vec3(Vec3::Z.neg());
+      let mut pitch = forward.y.neg().asin();
+      let mut distance = child.translation.z;
+
+      let has_mouse = window_has_mouse(&windows, camera.window);
+      let cursor = windows
+         .get(camera.window)
+         .and_then(|window| window.cursor_position());
+      // Ground point under a window position, seen from last frame's camera
+      let ground_at = |position: Vec2| {
+         let window_size = window_size(&windows, camera.window)?;
+         let (origin, direction) = viewport_ray(
+            window_size,
+            position,
+            projection.fov,
+            projection.aspect_ratio,
+            global,
+         );
+         ground_intersection(origin, direction, ground)
+      };
+
+      let mut panned = false;
+      if let (true, Some(cursor)) = (has_mouse, cursor) {
+         if action_state.pressed(FlyingCamAction::Pan) {
+            // Mouse motion points down the window, cursor positions point up
+            let previous = cursor - Vec2::new(motion_total.x, motion_total.y.neg());
+            if let (Some(from), Some(to)) = (ground_at(previous), ground_at(cursor)) {
+               let delta = from - to;
+               center += delta;
+               if dt > 0.0 {
+                  cam.map_pan_velocity = delta.div(dt);
+               }
+               panned = true;
+            }
+         } else if action_state.pressed(FlyingCamAction::Secondary) {
+            if action_state.just_pressed(FlyingCamAction::Secondary) {
+               cam.reference_point = ground_at(cursor);
+            }
+            if let (Some(anchor), Some(window_size)) =
+               (cam.reference_point, window_size(&windows, camera.window))
+            {
+               let turn = motion_total
+                  .x
+                  .div(window_size.x)
+                  .mul(std::f32::consts::TAU)
+                  .mul(cam.angular_speed)
+                  .neg();
+               center = anchor + Quat::from_rotation_y(turn).mul_vec3(center - anchor);
+               yaw += turn;
+            }
+         } else if action_state.pressed(FlyingCamAction::Tilt) {
+            if let Some(window_size) = window_size(&windows, camera.window) {
+               pitch += motion_total
+                  .y
+                  .div(window_size.y)
+                  .mul(std::f32::consts::PI)
+                  .mul(cam.angular_speed);
+            }
+         }
+
+         if scroll_total.abs() > 0.0 {
+            let new_distance = distance
+               .mul((1.0 - MAP_ZOOM_STEP).powf(scroll_total))
+               .clamp(min_distance, max_distance);
+            if let Some(point) = ground_at(cursor) {
+               center += (point - center).mul(1.0 - new_distance.div(distance));
+            }
+            distance = new_distance;
+         }
+      }
+
+      // Coast after a pan is let go
+      if !panned {
+         center += cam.map_pan_velocity.mul(dt);
+         cam.map_pan_velocity = cam
+            .map_pan_velocity
+            .mul(cam_params.orbit_friction.neg().mul(dt).exp());
+         if cam.map_pan_velocity.length_squared() < 1e-4 {
+            cam.map_pan_velocity = Vec3::ZERO;
+         }
+      }
+
+      // The pitch limits keep the camera above the horizon and off the zenith
+      pitch = pitch.clamp(min_pitch, max_pitch);
+      let (rig_parent, rig_child) = map_rig(center, yaw, pitch, distance);
+      space.write_back(
+         before,
+         (rig_parent, rig_child),
+         (&mut transform_parent, &mut transform_child),
+      );
+   }
+}
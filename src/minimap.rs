@@ -0,0 +1,83 @@
+//! An orthographic top-down map of the area around a flying camera.
+
+use crate::MovableCamera;
+use bevy::prelude::*;
+
+/// An orthographic camera looking straight down on the flying camera `main`,
+/// kept in sync by [`sync_minimap_cameras`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MinimapCamera {
+   pub main: Entity,
+   /// World-space height the minimap looks down from.
+   pub height: f32,
+   /// Orthographic scale; larger shows more of the map.
+   pub scale: f32,
+   /// Turn the map with the main camera's heading, so forward is always up.
+   /// Otherwise -Z is up.
+   pub rotate_with_yaw: bool,
+}
+
+impl MinimapCamera {
+   pub fn new(main: Entity) -> Self {
+      Self {
+         main,
+         height: 100.0,
+         scale: 20.0,
+         rotate_with_yaw: false,
+      }
+   }
+}
+
+/// Spawns `minimap` as an orthographic camera. `camera` picks where it
+/// renders; this version of Bevy has no viewports or image targets, so that
+/// is a second window or a named camera drawn by the app's render graph.
+pub fn spawn_minimap_camera(
+   commands: &mut Commands,
+   minimap: MinimapCamera,
+   camera: Camera,
+) -> Entity {
+   commands
+      .spawn_bundle(OrthographicCameraBundle {
+         camera,
+         ..OrthographicCameraBundle::new_3d()
+      })
+      .insert(minimap)
+      .id()
+}
+
+/// Moves minimap cameras over their main camera. Runs before transform
+/// propagation so the map is never a frame behind.
+pub fn sync_minimap_cameras(
+   q_main: Query<(&Parent, &Transform), With<MovableCamera>>,
+   q_parent: Query<&Transform, (Without<MovableCamera>, Without<MinimapCamera>)>,
+   mut q_minimap: Query<
+      (&MinimapCamera, &mut Transform, &mut OrthographicProjection),
+      Without<MovableCamera>,
+   >,
+) {
+   for (minimap, mut transform, mut projection) in q_minimap.iter_mut() {
+      let (parent, transform_child) = match q_main.get(minimap.main) {
+         Ok(main) => main,
+         Err(_) => continue,
+      };
+      let transform_parent = match q_parent.get(parent.0) {
+         Ok(transform_parent) => transform_parent,
+         Err(_) => continue,
+      };
+      let eye = transform_parent.mul_transform(*transform_child);
+      let forward = eye.forward();
+      let up = if minimap.rotate_with_yaw {
+         Vec3::new(forward.x, 0.0, forward.z)
+            .try_normalize()
+            .unwrap_or(-Vec3::Z)
+      } else {
+         -Vec3::Z
+      };
+      let below = Vec3::new(eye.translation.x, 0.0, eye.translation.z);
+      *transform =
+         Transform::from_translation(below + Vec3::Y * minimap.height).looking_at(below, up);
+      if projection.scale != minimap.scale {
+         projection.scale = minimap.scale;
+      }
+   }
+}
@@ -0,0 +1,303 @@
+//! Per-camera mode state machine: which system drives a [`MovableCamera`],
+//! which changes between modes are allowed, and what happens on a change.
+
+use crate::{MovableCamera, RigSpace};
+use bevy::prelude::*;
+use std::fmt;
+
+/// What drives a [`MovableCamera`]. Change it with [`MovableCamera::set_mode`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraMode {
+   /// User controlled flight.
+   Free,
+   /// User controlled orbiting around the rig parent.
+   Orbit,
+   /// Follow behind `target` at `offset`, given in the target's local space,
+   /// on a spring.
+   Chase { target: Entity, offset: Vec3 },
+   /// Orbit `target` on a boom, for character controllers. The mouse orbits
+   /// and scrolling changes the boom length.
+   ThirdPerson { target: Entity },
+   /// Ride along with `target`, inheriting its position and orientation,
+   /// from `offset` in the target's local space. The mouse looks around and
+   /// `Detach` returns to free flight.
+   AttachTo { target: Entity, offset: Vec3 },
+   /// Free flight with the cursor captured and the mouse always looking
+   /// around.
+   FirstPerson,
+   /// Six degrees of freedom with a throttle, like a plane or spaceship.
+   Flight,
+   /// Drive like a tank or drone, with the turn rates and acceleration of the
+   /// camera's [`VehicleHandling`](crate::VehicleHandling).
+   Vehicle,
+   /// Hover like a quadcopter, drifting with momentum and leaning into its
+   /// acceleration.
+   Drone,
+   /// Map viewer: zoom to the cursor, turn and tilt around the ground.
+   Map,
+   /// Pan and zoom an orthographic camera over a 2D canvas.
+   Pan2d,
+   /// Strategy game camera: pan over the ground, turn around the center of
+   /// the view, and zoom towards the cursor.
+   TopDown,
+   /// Walk the scene at eye height.
+   Walk,
+   /// Move the rig root around a head-tracked camera without ever writing
+   /// the camera's own transform.
+   Vr,
+   /// Fly along the camera's [`CameraPath`](crate::CameraPath).
+   Path,
+   /// Left alone by every camera system.
+   Disabled,
+}
+
+impl Default for CameraMode {
+   fn default() -> Self {
+      CameraMode::Free
+   }
+}
+
+/// The kind of a [`CameraMode`], without its settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CameraModeKind {
+   Free,
+   Orbit,
+   /// [`CameraMode::Chase`], [`CameraMode::ThirdPerson`] and [`CameraMode::AttachTo`].
+   Follow,
+   FirstPerson,
+   Flight,
+   Vehicle,
+   Drone,
+   Map,
+   Pan2d,
+   TopDown,
+   Walk,
+   Vr,
+   Path,
+   Disabled,
+}
+
+impl CameraMode {
+   pub fn kind(&self) -> CameraModeKind {
+      match self {
+         CameraMode::Free => CameraModeKind::Free,
+         CameraMode::Orbit => CameraModeKind::Orbit,
+         CameraMode::Chase { .. }
+         | CameraMode::ThirdPerson { .. }
+         | CameraMode::AttachTo { .. } => CameraModeKind::Follow,
+         CameraMode::FirstPerson => CameraModeKind::FirstPerson,
+         CameraMode::Flight => CameraModeKind::Flight,
+         CameraMode::Vehicle => CameraModeKind::Vehicle,
+         CameraMode::Drone => CameraModeKind::Drone,
+         CameraMode::Map => CameraModeKind::Map,
+         CameraMode::Pan2d => CameraModeKind::Pan2d,
+         CameraMode::TopDown => CameraModeKind::TopDown,
+         CameraMode::Walk => CameraModeKind::Walk,
+         CameraMode::Vr => CameraModeKind::Vr,
+         CameraMode::Path => CameraModeKind::Path,
+         CameraMode::Disabled => CameraModeKind::Disabled,
+      }
+   }
+
+   /// Free flight or orbiting, the modes driven by the flying controls.
+   pub fn is_manual(&self) -> bool {
+      matches!(self, CameraMode::Free | CameraMode::Orbit)
+   }
+
+   /// Whether a camera in this mode may switch to `to`. The flying controls
+   /// are the hub: free flight and orbiting lead to every other mode, every
+   /// mode leads back to free flight or can be disabled, and nothing else
+   /// goes. A disabled camera can only be re-enabled into free flight, and
+   /// orbiting is entered from free flight. Changes within a kind, such as
+   /// following another target, are always allowed.
+   pub fn can_transition_to(&self, to: &CameraMode) -> bool {
+      use CameraModeKind::*;
+      match (self.kind(), to.kind()) {
+         (from, to) if from == to => true,
+         (Disabled, to) => to == Free,
+         (_, Free | Disabled) => true,
+         (from, Orbit) => from == Free,
+         (Free | Orbit, _) => true,
+         (
+            Follow | FirstPerson | Flight | Vehicle | Drone | Map | Pan2d | TopDown | Walk | Vr
+            | Path,
+            _,
+         ) => false,
+      }
+   }
+}
+
+/// Returned by [`MovableCamera::set_mode`] for a change that isn't allowed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvalidModeTransition {
+   pub from: CameraMode,
+   pub to: CameraMode,
+}
+
+impl fmt::Display for InvalidModeTransition {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(
+         f,
+         "camera can't switch from {:?} to {:?}",
+         self.from.kind(),
+         self.to.kind()
+      )
+   }
+}
+
+impl std::error::Error for InvalidModeTransition {}
+
+impl MovableCamera {
+   /// The mode driving this camera.
+   pub fn mode(&self) -> CameraMode {
+      self.mode
+   }
+
+   /// Whether the camera is orbiting around its rig parent.
+   pub fn is_orbiting(&self) -> bool {
+      self.mode == CameraMode::Orbit
+   }
+
+   /// Switches the camera to `mode`, if the change is allowed. The system
+   /// driving the new mode picks the camera up from its current pose.
+   pub fn set_mode(&mut self, mode: CameraMode) -> Result<(), InvalidModeTransition> {
+      if !self.mode.can_transition_to(&mode) {
+         return Err(InvalidModeTransition {
+            from: self.mode,
+            to: mode,
+         });
+      }
+      if mode != self.mode {
+         self.mode = mode;
+         self.mode_entered = true;
+      }
+      Ok(())
+   }
+
+   /// Switches to `mode` without the checks of [`set_mode`](Self::set_mode),
+   /// for the crate's own systems. The system driving `mode` picks the camera
+   /// up from its current pose, even if it already was in `mode`.
+   pub(crate) fn enter_mode(&mut self, mode: CameraMode) {
+      self.mode = mode;
+      self.mode_entered = true;
+   }
+
+   /// Switches to `mode` like [`enter_mode`](Self::enter_mode), for callers
+   /// that already laid the rig out for it.
+   pub(crate) fn enter_laid_out_mode(&mut self, mode: CameraMode) {
+      self.mode = mode;
+      self.mode_entered = false;
+   }
+
+   /// Starts the camera in `mode`.
+   pub fn with_mode(mut self, mode: CameraMode) -> Self {
+      self.mode = mode;
+      self.mode_entered = true;
+      self
+   }
+}
+
+/// Sent when a camera's mode changes, after the frame's camera systems ran.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraModeChanged {
+   pub camera: Entity,
+   pub from: CameraMode,
+   pub to: CameraMode,
+}
+
+/// Sent when a camera starts orbiting around `pivot`.
+#[derive(Clone, Copy, Debug)]
+pub struct FocusEntered {
+   pub camera: Entity,
+   pub pivot: Vec3,
+}
+
+/// Sent when a camera stops orbiting.
+#[derive(Clone, Copy, Debug)]
+pub struct FocusExited {
+   pub camera: Entity,
+}
+
+/// Called with the camera and its new or old mode when it enters or leaves a
+/// mode.
+pub type CameraModeHook = fn(&mut Commands, Entity, CameraMode);
+
+/// Callbacks run when cameras enter or leave a kind of mode, e.g. to show
+/// orbit hints or swap post-processing.
+#[derive(Default)]
+pub struct CameraModeHooks {
+   enter: Vec<(CameraModeKind, CameraModeHook)>,
+   exit: Vec<(CameraModeKind, CameraModeHook)>,
+}
+
+impl CameraModeHooks {
+   pub fn on_enter(&mut self, kind: CameraModeKind, hook: CameraModeHook) -> &mut Self {
+      self.enter.push((kind, hook));
+      self
+   }
+
+   pub fn on_exit(&mut self, kind: CameraModeKind, hook: CameraModeHook) -> &mut Self {
+      self.exit.push((kind, hook));
+      self
+   }
+}
+
+/// Announces mode changes with [`CameraModeChanged`], [`FocusEntered`] and
+/// [`FocusExited`], and runs the [`CameraModeHooks`].
+pub fn announce_mode(
+   mut commands: Commands,
+   hooks: Res<CameraModeHooks>,
+   mut changed: EventWriter<CameraModeChanged>,
+   mut entered: EventWriter<FocusEntered>,
+   mut exited: EventWriter<FocusExited>,
+   mut q_child: Query<(Entity, &Parent, &mut MovableCamera)>,
+   q_parent: Query<(&Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for (entity, parent, mut cam) in q_child.iter_mut() {
+      let (from, to) = (cam.announced_mode, cam.mode);
+      if from == to {
+         continue;
+      }
+      cam.announced_mode = to;
+      changed.send(CameraModeChanged {
+         camera: entity,
+         from,
+         to,
+      });
+      if from.kind() == to.kind() {
+         continue;
+      }
+      if from == CameraMode::Orbit {
+         exited.send(FocusExited { camera: entity });
+      }
+      if to == CameraMode::Orbit {
+         let pivot = q_parent
+            .get(parent.0)
+            .map(|(transform_parent, root_parent)| {
+               let frame = root_parent.and_then(|root_parent| q_globals.get(root_parent.0).ok());
+               let space = RigSpace::new(frame, transform_parent);
+               space
+                  .to_world(transform_parent, &Transform::identity())
+                  .0
+                  .translation
+            })
+            .unwrap_or_default();
+         entered.send(FocusEntered {
+            camera: entity,
+            pivot,
+         });
+      }
+      for (kind, hook) in hooks.exit.iter() {
+         if *kind == from.kind() {
+            hook(&mut commands, entity, from);
+         }
+      }
+      for (kind, hook) in hooks.enter.iter() {
+         if *kind == to.kind() {
+            hook(&mut commands, entity, to);
+         }
+      }
+   }
+}
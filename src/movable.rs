@@ -0,0 +1,949 @@
+//! The systems behind [`CameraMode::Free`] and [`CameraMode::Orbit`], split
+//! into focus handoff, input, rotation, zoom and translation. They run on
+//! cameras of any projection; orthographic ones center the pivot rather than
+//! turn to it, and zoom by projection scale.
+
+use crate::{
+   clamp_orbit_above_plane, distance_scaled_speed, fov_sensitivity_scale, free_rig, limit_orbit,
+   limit_pitch_to, move_free, move_rig, movement_pressed, net_movement, orbit_around,
+   ortho::{centered_rig, ortho_zoom, units_per_pixel},
+   ortho_scale_for_distance, pan_units_per_pixel, rig_root, rotate_cam_quat, target_pivot,
+   window_has_mouse, window_size, yaw_angle, CameraActions, CameraBounds, CameraMode, CameraMouse,
+   CameraTime, CameraTween, CursorHit, FlyingCamAction, FocusTarget, GamepadLook, MovableCamera,
+   MovableCameraParams, OrbitSnap, PivotSurface, SpeedModel, MIN_PAN_DISTANCE,
+   ORBIT_SNAP_STIFFNESS,
+};
+use bevy::{math::DVec3, prelude::*};
+use std::{
+   f32::consts::{FRAC_PI_4, TAU},
+   ops::{Div, Mul, Neg},
+};
+
+/// Labels of the movable camera systems, which run in this order. A system
+/// standing in for one of them should be ordered the same way.
+#[derive(SystemLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MovableCameraSystem {
+   /// [`movable_camera_focus`]
+   Focus,
+   /// [`movable_camera_input`]
+   Input,
+   /// [`movable_camera_rotation`]
+   Rotation,
+   /// [`movable_camera_zoom`]
+   Zoom,
+   /// [`movable_camera_translation`]
+   Translation,
+}
+
+/// Which of the movable camera systems run. Turn one off to replace it with
+/// a system of your own, labeled or ordered with [`MovableCameraSystem`].
+#[derive(Clone, Copy, Debug)]
+pub struct MovableCameraBehaviors {
+   pub focus: bool,
+   pub input: bool,
+   pub rotation: bool,
+   pub zoom: bool,
+   pub translation: bool,
+}
+
+impl Default for MovableCameraBehaviors {
+   fn default() -> Self {
+      Self {
+         focus: true,
+         input: true,
+         rotation: true,
+         zoom: true,
+         translation: true,
+      }
+   }
+}
+
+/// What the user asked a movable camera to do this frame, written by
+/// [`movable_camera_input`] and read by the rotation, zoom and translation
+/// systems. Added to movable cameras automatically.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MovableCameraInput {
+   /// Motion to turn or orbit by, in mouse pixels, after smoothing.
+   pub look: Vec2,
+   /// Lines to zoom by.
+   pub scroll: f32,
+   /// Motion dragging the pivot, in mouse pixels.
+   pub pan: Vec2,
+   /// Movement as (left, up, forward), each from -1 to 1.
+   pub movement: Vec3,
+   /// Whether the user is turning the camera, as opposed to it coasting.
+   pub looking: bool,
+   /// Whether `SnapOrbit` is held.
+   pub snap: bool,
+}
+
+/// Whether `cam` is in a mode these systems drive.
+fn is_movable_mode(cam: &MovableCamera) -> bool {
+   cam.mode().is_manual() || cam.mode() == CameraMode::FirstPerson
+}
+
+/// Turn speed of `cam`, scaled by the field of view if asked to and the
+/// camera has one.
+fn look_speed(
+   cam: &MovableCamera,
+   cam_params: &MovableCameraParams,
+   projection: Option<&PerspectiveProjection>,
+) -> f32 {
+   match projection {
+      Some(projection) if cam_params.fov_scaled_sensitivity => cam.angular_speed.mul(
+         fov_sensitivity_scale(projection.fov, cam_params.reference_fov),
+      ),
+      _ => cam.angular_speed,
+   }
+}
+
+/// Precise position to carry on from, picked back up unless something else
+/// moved the camera.
+fn pick_up_precise(
+   cam: &MovableCamera,
+   cam_params: &MovableCameraParams,
+   translation: Vec3,
+) -> Option<DVec3> {
+   cam_params.precise_translation.then(|| {
+      cam.precise_translation
+         .filter(|precise| precise.as_vec3() == translation)
+         .unwrap_or_else(|| translation.as_dvec3())
+   })
+}
+
+/// Adopts rigs switched to free or orbit mode from outside, and hands off
+/// between the two: `Focus` starts orbiting, pressed twice resets the orbit,
+/// and moving leaves it. Unlike the other movable camera systems it also sees
+/// cameras flying to their focus, so the second press isn't lost.
+pub fn movable_camera_focus(
+   mut commands: Commands,
+   behaviors: Res<MovableCameraBehaviors>,
+   time: CameraTime,
+   actions: CameraActions,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&FocusTarget>,
+      Option<&CursorHit>,
+      Option<&mut OrthographicProjection>,
+      Option<&CameraTween>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   if !behaviors.focus {
+      return;
+   }
+   for (
+      entity,
+      parent,
+      mut transform_child,
+      mut cam,
+      focus_target,
+      cursor_hit,
+      mut orthographic,
+      tween,
+   ) in q_child.iter_mut()
+   {
+      // Other modes are driven by their own systems
+      if !is_movable_mode(&cam) {
+         continue;
+      }
+      // Adopt a rig that was switched to this mode from the outside
+      if cam.mode_entered && tween.is_none() {
+         if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+            let (root, child) = space.to_world(&transform_parent, &transform_child);
+            let eye = root.mul_transform(child);
+            let rig = if cam.is_orbiting() {
+               match (
+                  cam_params.focus_fallback.pivot(&eye),
+                  orthographic.is_some(),
+               ) {
+                  (pivot, true) => centered_rig(pivot.unwrap_or(eye.translation), eye),
+                  (Some(pivot), false) => orbit_around(pivot, eye),
+                  (None, false) => (eye, Transform::default()),
+               }
+            } else {
+               free_rig(&root, &child)
+            };
+            let (rig_parent, rig_child) = space.to_local(&rig.0, &rig.1);
+            *transform_parent = rig_parent;
+            *transform_child = rig_child;
+         }
+         cam.mode_entered = false;
+      }
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      // Flying to a focus, where only pressing Focus again does anything
+      if tween.is_some()
+         && !(cam.is_orbiting() && action_state.just_pressed(FlyingCamAction::Focus))
+      {
+         continue;
+      }
+
+      if cam.is_orbiting() {
+         if movement_pressed(action_state) && !cam_params.focused_strafe_pans && tween.is_none() {
+            if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals)
+            {
+               let (root, child) = space.to_world(&transform_parent, &transform_child);
+               cam.remember_orbit(focus_target, &root, &child);
+               let (free_parent, free_child) = free_rig(&root, &child);
+               let (free_parent, free_child) = space.to_local(&free_parent, &free_child);
+               *transform_parent = free_parent;
+               *transform_child = free_child;
+            }
+            cam.enter_laid_out_mode(CameraMode::Free);
+            cam.orbit_velocity = Vec2::ZERO;
+         } else if action_state.just_pressed(FlyingCamAction::Focus) {
+            let now = time.seconds_since_startup();
+            let double_press = cam.last_focus_press.map_or(false, |last| {
+               now - last <= cam_params.focus_reset_window as f64
+            });
+            if let (true, Some(projection)) = (double_press, orthographic.as_mut()) {
+               // The view has no depth, so the scale stands in for the distance
+               let (min_scale, max_scale) = cam_params.ortho_scale_limits;
+               let scale = ortho_scale_for_distance(
+                  cam_params.focus_reset_distance,
+                  cam_params.reference_fov,
+                  projection,
+               )
+               .clamp(min_scale, max_scale);
+               projection.scale = scale;
+               cam.last_focus_press = None;
+            } else if double_press {
+               if let Some((mut transform_parent, space)) =
+                  rig_root(parent, &mut q_parent, &q_globals)
+               {
+                  let (mut reset_parent, _) = space.to_world(&transform_parent, &transform_child);
+                  if let Some(elevation) = cam_params.focus_reset_elevation {
+                     reset_parent.rotation =
+                        limit_orbit(reset_parent.rotation, (elevation, elevation), None);
+                  }
+                  move_rig(
+                     &mut commands,
+                     entity,
+                     cam_params.focus_duration,
+                     &mut transform_parent,
+                     &mut transform_child,
+                     space.to_local(
+                        &reset_parent,
+                        &Transform::from_xyz(0.0, 0.0, cam_params.focus_reset_distance),
+                     ),
+                  );
+                  cam.orbit_velocity = Vec2::ZERO;
+               }
+               cam.last_focus_press = None;
+            } else {
+               cam.last_focus_press = Some(now);
+            }
+         }
+      } else if action_state.just_pressed(FlyingCamAction::Focus) && cam.mode() == CameraMode::Free
+      {
+         cam.last_focus_press = Some(time.seconds_since_startup());
+         if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+            // Prefer an explicit target, then whatever is under the cursor, then the fallback
+            let (root, child) = space.to_world(&transform_parent, &transform_child);
+            let eye = root.mul_transform(child);
+            let target = focus_target.and_then(|focus_target| {
+               q_globals.get(focus_target.0).ok().map(|global| {
+                  (
+                     focus_target.0,
+                     target_pivot(global, cam_params.pivot_offset),
+                  )
+               })
+            });
+            let pivot = target
+               .map(|(_, pivot)| pivot)
+               .or_else(|| cursor_hit.and_then(|hit| hit.0))
+               .or_else(|| cam_params.focus_fallback.pivot(&eye))
+               .or_else(|| orthographic.is_some().then(|| eye.translation));
+            if let Some(pivot) = pivot {
+               let rig = match (orthographic.is_some(), target) {
+                  // Center the pivot rather than turn to face it
+                  (true, _) => centered_rig(pivot, eye),
+                  (false, Some((target, _))) => {
+                     cam.recall_orbit(&cam_params, target, orbit_around(pivot, eye))
+                  }
+                  (false, None) => orbit_around(pivot, eye),
+               };
+               cam.record_focus(
+                  &cam_params,
+                  focus_target,
+                  (&root, &child),
+                  rig,
+                  target.map(|(target, _)| target),
+               );
+               move_rig(
+                  &mut commands,
+                  entity,
+                  cam_params.focus_duration,
+                  &mut transform_parent,
+                  &mut transform_child,
+                  space.to_local(&rig.0, &rig.1),
+               );
+               cam.reference_point = Some(rig.0.translation);
+            } else {
+               // Hand off position and orientation information to parent
+               let rig = (eye, Transform::default());
+               let (rig_parent, rig_child) = space.to_local(&rig.0, &rig.1);
+               *transform_parent = rig_parent;
+               *transform_child = rig_child;
+               cam.record_focus(&cam_params, None, (&rig.0, &rig.1), rig, None);
+               cam.reference_point = Some(eye.translation);
+            }
+         }
+         cam.enter_laid_out_mode(CameraMode::Orbit);
+      }
+   }
+}
+
+/// Reads the mouse, gamepad and actions of each movable camera into its
+/// [`MovableCameraInput`], and keeps track of how long it has been idle.
+pub fn movable_camera_input(
+   mut commands: Commands,
+   behaviors: Res<MovableCameraBehaviors>,
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   gamepad_axes: Res<Axis<GamepadAxis>>,
+   gamepad_buttons: Res<Axis<GamepadButton>>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<
+      (
+         Entity,
+         &Camera,
+         &mut MovableCamera,
+         Option<&GamepadLook>,
+         Option<&mut MovableCameraInput>,
+      ),
+      Without<CameraTween>,
+   >,
+) {
+   if !behaviors.input {
+      return;
+   }
+   for (entity, camera, mut cam, gamepad_look, stored) in q_child.iter_mut() {
+      if !is_movable_mode(&cam) {
+         continue;
+      }
+      let mut input = MovableCameraInput::default();
+      if let Some(action_state) = actions.get(entity) {
+         if let Some(gamepad_look) = gamepad_look {
+            let (look, zoom) = gamepad_look.read(
+               &gamepad_axes,
+               &gamepad_buttons,
+               time.camera_delta_seconds(entity),
+            );
+            input.look = look;
+            input.scroll = zoom;
+         } else if window_has_mouse(&windows, camera.window) {
+            // Panning takes over the drag, even when it shares a button with rotation
+            if action_state.pressed(FlyingCamAction::Pan) {
+               input.pan = mouse.motion;
+            } else if action_state.pressed(FlyingCamAction::Secondary) || cam.cursor_captured {
+               input.look = mouse.motion;
+            }
+            input.scroll = mouse.scroll;
+         }
+         input.look = cam
+            .mouse_filter
+            .apply(cam_params.mouse_smoothing, input.look);
+
+         // A gamepad stick looks without a button held
+         input.looking = action_state.pressed(FlyingCamAction::Secondary)
+            || (gamepad_look.is_some() && input.look.length_squared() > 0.0);
+         input.movement = Vec3::new(
+            net_movement(action_state, FlyingCamAction::Right, FlyingCamAction::Left),
+            net_movement(action_state, FlyingCamAction::Down, FlyingCamAction::Up),
+            net_movement(
+               action_state,
+               FlyingCamAction::Back,
+               FlyingCamAction::Forward,
+            ),
+         );
+         input.snap = action_state.pressed(FlyingCamAction::SnapOrbit);
+
+         if movement_pressed(action_state)
+            || input.looking
+            || action_state.pressed(FlyingCamAction::Pan)
+            || action_state.pressed(FlyingCamAction::Focus)
+            || action_state.pressed(FlyingCamAction::AdjustSpeed)
+            || input.scroll.abs() > 0.0
+         {
+            cam.idle_time = 0.0;
+         } else {
+            cam.idle_time += time.camera_delta_seconds(entity);
+         }
+      }
+      match stored {
+         Some(mut stored) => {
+            if *stored != input {
+               *stored = input;
+            }
+         }
+         None => {
+            commands.entity(entity).insert(input);
+         }
+      }
+   }
+}
+
+/// Turns free cameras and orbits focused ones, with orbit inertia, the idle
+/// turntable and yaw snapping.
+pub fn movable_camera_rotation(
+   behaviors: Res<MovableCameraBehaviors>,
+   windows: Res<Windows>,
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<
+      (
+         Entity,
+         &Camera,
+         &Parent,
+         &mut Transform,
+         &mut MovableCamera,
+         Option<&PerspectiveProjection>,
+         Option<&OrthographicProjection>,
+         &MovableCameraInput,
+      ),
+      Without<CameraTween>,
+   >,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   if !behaviors.rotation {
+      return;
+   }
+   for (entity, camera, parent, mut transform_child, mut cam, projection, orthographic, input) in
+      q_child.iter_mut()
+   {
+      if !is_movable_mode(&cam) {
+         continue;
+      }
+      let dt = time.camera_delta_seconds(entity);
+      let roll_orbit = orthographic.is_some() && cam_params.ortho_roll_orbit;
+      let angular_speed = look_speed(&cam, &cam_params, projection);
+      let window_size = window_size(&windows, camera.window);
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+
+      if !cam.is_orbiting() {
+         move_free(
+            space,
+            (&mut transform_parent, &mut transform_child),
+            |eye| free_look(eye, &cam_params, input.look, window_size, angular_speed),
+         );
+         continue;
+      }
+      let before = space.to_world(&transform_parent, &transform_child);
+      let (mut root, child) = before;
+
+      let mut rotation_move = input.look;
+      if input.looking {
+         // Track how fast the user is dragging so a release can keep spinning
+         if dt > 0.0 {
+            cam.orbit_velocity = rotation_move.div(dt);
+         }
+      } else if cam_params.orbit_inertia {
+         rotation_move = cam.orbit_velocity.mul(dt);
+         cam.orbit_velocity = cam
+            .orbit_velocity
+            .mul(cam_params.orbit_friction.neg().mul(dt).exp());
+         if cam.orbit_velocity.length_squared() < 1e-4 {
+            cam.orbit_velocity = Vec2::ZERO;
+         }
+      }
+
+      // Turntable around the pivot once the user has been idle long enough
+      if let (Some(delay), false) = (cam_params.idle_orbit_delay, roll_orbit) {
+         if cam.idle_time >= delay {
+            let delta_yaw = Quat::from_rotation_y(cam_params.idle_orbit_speed.mul(dt));
+            root.rotation = limit_orbit(
+               delta_yaw.mul(root.rotation),
+               cam_params.orbit_elevation_limits,
+               cam_params.orbit_yaw_limits,
+            );
+         }
+      }
+
+      // Orbit the camera, or spin an orthographic view around its axis
+      if let (true, Some(window_size)) = (rotation_move.length_squared() > 0.0, window_size) {
+         if roll_orbit {
+            let angle = rotation_move
+               .x
+               .div(window_size.x)
+               .mul(TAU)
+               .mul(cam.angular_speed)
+               .neg();
+            root.rotation = Quat::from_axis_angle(root.back(), angle).mul(root.rotation);
+         } else {
+            root.rotation = limit_orbit(
+               rotate_cam_quat(window_size, rotation_move, angular_speed, root.rotation),
+               cam_params.orbit_elevation_limits,
+               cam_params.orbit_yaw_limits,
+            );
+         }
+      }
+
+      // Settle onto the nearest yaw stop while the drag is slow
+      let snap_step = match cam_params.orbit_snap {
+         OrbitSnap::Held { step } if input.snap => Some(step),
+         OrbitSnap::Always { step } => Some(step),
+         _ => None,
+      };
+      if let Some(step) = snap_step.filter(|step| *step > 0.0 && dt > 0.0 && !roll_orbit) {
+         if rotation_move.x.abs().div(dt) < cam_params.orbit_snap_max_speed {
+            let yaw = yaw_angle(root.rotation);
+            let stop = yaw.div(step).round().mul(step);
+            let pull = 1.0 - ORBIT_SNAP_STIFFNESS.neg().mul(dt).exp();
+            let delta_yaw = Quat::from_rotation_y((stop - yaw).mul(pull));
+            root.rotation = limit_orbit(
+               delta_yaw.mul(root.rotation),
+               cam_params.orbit_elevation_limits,
+               cam_params.orbit_yaw_limits,
+            );
+         }
+      }
+      // Pressed against a limit or settled on a stop, the rotation may not
+      // change at all, and then nothing is written
+      space.write_back(
+         before,
+         (root, child),
+         (&mut transform_parent, &mut transform_child),
+      );
+   }
+}
+
+/// Zooms free cameras along their view and focused ones towards the pivot.
+/// Orthographic cameras zoom by projection scale instead, towards the cursor
+/// with `ortho_zoom_to_cursor`.
+pub fn movable_camera_zoom(
+   mut commands: Commands,
+   behaviors: Res<MovableCameraBehaviors>,
+   windows: Res<Windows>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<
+      (
+         Entity,
+         &Camera,
+         &Parent,
+         &mut Transform,
+         &mut MovableCamera,
+         &MovableCameraInput,
+         Option<&CameraBounds>,
+         Option<&FocusTarget>,
+         Option<&mut OrthographicProjection>,
+      ),
+      Without<CameraTween>,
+   >,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   if !behaviors.zoom {
+      return;
+   }
+   for (
+      entity,
+      camera,
+      parent,
+      mut transform_child,
+      mut cam,
+      input,
+      bounds,
+      focus_target,
+      orthographic,
+   ) in q_child.iter_mut()
+   {
+      if !is_movable_mode(&cam) || input.scroll == 0.0 {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      if let Some(mut projection) = orthographic {
+         if projection.scale <= 0.0 {
+            continue;
+         }
+         let cursor = match (
+            cam_params.ortho_zoom_to_cursor,
+            window_size(&windows, camera.window),
+         ) {
+            (true, Some(window_size)) => windows
+               .get(camera.window)
+               .and_then(|window| window.cursor_position())
+               .map(|cursor| (window_size, cursor)),
+            _ => None,
+         };
+         let (scale, shift) = ortho_zoom(
+            &projection,
+            input.scroll,
+            cam_params.ortho_scale_limits,
+            cursor,
+         );
+         if shift != Vec2::ZERO {
+            // Shift the view so the point under the cursor stays put
+            let before = space.to_world(&transform_parent, &transform_child);
+            let (mut root, mut child) = before;
+            let eye = root.mul_transform(child);
+            let delta = eye.right().mul(shift.x) + eye.up().mul(shift.y);
+            if cam.is_orbiting() {
+               root.translation += delta;
+               cam.reference_point = Some(root.translation);
+               if focus_target.is_some() {
+                  commands.entity(entity).remove::<FocusTarget>();
+               }
+            } else {
+               child.translation += root.rotation.inverse().mul_vec3(delta);
+            }
+            space.write_back(
+               before,
+               (root, child),
+               (&mut transform_parent, &mut transform_child),
+            );
+         }
+         if scale != projection.scale {
+            projection.scale = scale;
+         }
+      } else if cam.is_orbiting() {
+         let before = space.to_world(&transform_parent, &transform_child);
+         let (root, mut child) = before;
+         // Parent has orientation information so just mutate child's z
+         child.translation -= Vec3::new(0.0, 0.0, 1.0)
+            .mul(cam_params.scroll_snap)
+            .mul(input.scroll)
+            .mul(cam.speed);
+         // Clamp the child's translation so it can't go past focus (the parent)
+         child.translation = child.translation.max(Vec3::new(0.0, 0.0, 0.0));
+         space.write_back(
+            before,
+            (root, child),
+            (&mut transform_parent, &mut transform_child),
+         );
+      } else {
+         let mut precise = None;
+         move_free(
+            space,
+            (&mut transform_parent, &mut transform_child),
+            |eye| {
+               precise = pick_up_precise(&cam, &cam_params, eye.translation);
+               free_zoom(
+                  eye,
+                  precise.as_mut(),
+                  &cam_params,
+                  input.scroll,
+                  cam.speed,
+                  bounds,
+               )
+            },
+         );
+         if precise != cam.precise_translation {
+            cam.precise_translation = precise;
+         }
+      }
+   }
+}
+
+/// Flies free cameras along their movement, and moves the pivot of focused
+/// ones: dragged with `Pan`, strafed with the movement keys when
+/// `focused_strafe_pans` is on, and kept on the ground.
+pub fn movable_camera_translation(
+   mut commands: Commands,
+   behaviors: Res<MovableCameraBehaviors>,
+   windows: Res<Windows>,
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<
+      (
+         Entity,
+         &Camera,
+         &Parent,
+         &mut Transform,
+         &mut MovableCamera,
+         Option<&PerspectiveProjection>,
+         Option<&OrthographicProjection>,
+         &MovableCameraInput,
+         Option<&CameraBounds>,
+         Option<&FocusTarget>,
+         Option<&PivotSurface>,
+      ),
+      Without<CameraTween>,
+   >,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   if !behaviors.translation {
+      return;
+   }
+   for (
+      entity,
+      camera,
+      parent,
+      mut transform_child,
+      mut cam,
+      perspective,
+      orthographic,
+      input,
+      bounds,
+      focus_target,
+      pivot_surface,
+   ) in q_child.iter_mut()
+   {
+      if !is_movable_mode(&cam) {
+         continue;
+      }
+      let dt = time.camera_delta_seconds(entity);
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+
+      if !cam.is_orbiting() {
+         let mut precise = None;
+         move_free(
+            space,
+            (&mut transform_parent, &mut transform_child),
+            |eye| {
+               precise = pick_up_precise(&cam, &cam_params, eye.translation);
+               free_translate(
+                  eye,
+                  precise.as_mut(),
+                  &cam_params,
+                  input.movement,
+                  cam.speed,
+                  cam.reference_point,
+                  bounds,
+                  dt,
+               )
+            },
+         );
+         if precise != cam.precise_translation {
+            cam.precise_translation = precise;
+         }
+         continue;
+      }
+      let before = space.to_world(&transform_parent, &transform_child);
+      let (mut root, child) = before;
+
+      // Drag the pivot so the point under the cursor follows it
+      if let (true, Some(window_size)) = (
+         input.pan.length_squared() > 0.0,
+         window_size(&windows, camera.window),
+      ) {
+         let distance = if cam_params.distance_scaled_pan {
+            child.translation.z.max(MIN_PAN_DISTANCE)
+         } else {
+            1.0
+         };
+         let units_per_pixel = match (perspective, orthographic) {
+            (_, Some(orthographic)) => units_per_pixel(orthographic, window_size.x),
+            (perspective, None) => {
+               let fov = perspective.map_or(FRAC_PI_4, |projection| projection.fov);
+               pan_units_per_pixel(distance, fov, window_size.y)
+            }
+         };
+         let mut delta = root.left().mul(input.pan.x.mul(units_per_pixel))
+            + root.up().mul(input.pan.y.mul(units_per_pixel));
+         if let Some(bounds) = bounds {
+            delta = bounds.damp(root.translation, delta);
+         }
+         root.translation += delta;
+         cam.reference_point = Some(root.translation);
+         // The pivot has left the target, so stop following it
+         if focus_target.is_some() {
+            commands.entity(entity).remove::<FocusTarget>();
+         }
+      }
+
+      // Pan the pivot in the camera's screen plane
+      if cam_params.focused_strafe_pans {
+         let mut pan_move = input.movement.normalize_or_zero();
+         if pan_move.length_squared() > 0.0 {
+            let speed = if cam_params.distance_scaled_pan {
+               distance_scaled_speed(cam.speed, child.translation.z)
+            } else {
+               cam.speed
+            };
+            pan_move = pan_move
+               .mul(cam_params.speed_multipliers)
+               .mul(dt)
+               .mul(speed);
+            let mut delta = root.left().mul(pan_move.x)
+               + root.up().mul(pan_move.y)
+               + root.forward().mul(pan_move.z);
+            if let Some(bounds) = bounds {
+               delta = bounds.damp(root.translation, delta);
+            }
+            root.translation += delta;
+         }
+      }
+
+      // Rest a free-standing pivot on the ground below it
+      if focus_target.is_none() {
+         let height = pivot_surface.and_then(|surface| surface.0).or_else(|| {
+            cam_params
+               .pivot_surface
+               .and_then(|surface| surface(root.translation))
+         });
+         if let Some(height) = height {
+            if height != root.translation.y {
+               root.translation.y = height;
+               cam.reference_point = Some(root.translation);
+            }
+         }
+      }
+
+      // Keep the orbiting camera above the ground
+      if let Some((normal, offset)) = cam_params.ground_plane {
+         root.rotation = clamp_orbit_above_plane(
+            root.translation,
+            root.rotation,
+            child.translation.z,
+            normal,
+            offset,
+         );
+      }
+      // Only what changed is written, so the rig isn't needlessly marked changed
+      space.write_back(
+         before,
+         (root, child),
+         (&mut transform_parent, &mut transform_child),
+      );
+   }
+}
+
+/// Turns a free camera by `look`, in mouse pixels.
+fn free_look(
+   mut transform: Transform,
+   cam_params: &MovableCameraParams,
+   look: Vec2,
+   window_size: Option<Vec2>,
+   angular_speed: f32,
+) -> Transform {
+   if let (true, Some(window_size)) = (look.length_squared() > 0.0, window_size) {
+      transform.rotation = limit_pitch_to(
+         rotate_cam_quat(window_size, look, angular_speed, transform.rotation),
+         cam_params.pitch_limits,
+      );
+   }
+   transform
+}
+
+/// Moves a free camera along its view by `scroll` lines.
+fn free_zoom(
+   mut transform: Transform,
+   mut precise: Option<&mut DVec3>,
+   cam_params: &MovableCameraParams,
+   scroll: f32,
+   speed: f32,
+   bounds: Option<&CameraBounds>,
+) -> Transform {
+   if scroll.abs() > 0.0 {
+      let zoom = transform
+         .forward()
+         .mul(cam_params.scroll_snap)
+         .mul(scroll)
+         .mul(speed);
+      offset_translation(&mut transform, &mut precise, zoom);
+      if let Some(bounds) = bounds {
+         let clamped = bounds.clamp(transform.translation);
+         if clamped != transform.translation {
+            transform.translation = clamped;
+            if let Some(precise) = precise.as_deref_mut() {
+               *precise = clamped.as_dvec3();
+            }
+         }
+      }
+   }
+   transform
+}
+
+/// Moves a free camera along `movement`, given as (left, up, forward).
+fn free_translate(
+   mut transform: Transform,
+   mut precise: Option<&mut DVec3>,
+   cam_params: &MovableCameraParams,
+   movement: Vec3,
+   speed: f32,
+   reference_point: Option<Vec3>,
+   bounds: Option<&CameraBounds>,
+   dt: f32,
+) -> Transform {
+   let mut translate_move = movement.normalize_or_zero();
+   if translate_move.length_squared() > 0.0 {
+      let speed = match (cam_params.speed_model, reference_point) {
+         (SpeedModel::DistanceProportional, Some(point)) => {
+            distance_scaled_speed(speed, transform.translation.distance(point))
+         }
+         _ => speed,
+      };
+      translate_move = translate_move
+         .mul(cam_params.speed_multipliers)
+         .mul(dt)
+         .mul(speed);
+      // Translate camera along each of its local axes
+      let mut delta = transform.left().mul(translate_move.x)
+         + transform.up().mul(translate_move.y)
+         + transform.forward().mul(translate_move.z);
+      // Ease into the bounds rather than stopping dead at them
+      if let Some(bounds) = bounds {
+         delta = bounds.damp(transform.translation, delta);
+      }
+      offset_translation(&mut transform, &mut precise, delta);
+   }
+   transform
+}
+
+/// One step of free flight: turns by `look`, zooms along the view by `scroll`
+/// and moves along `movement`, given as (left, up, forward). With `precise`,
+/// the movement is added up there in `f64`.
+pub(crate) fn fly_free(
+   transform: Transform,
+   mut precise: Option<&mut DVec3>,
+   cam_params: &MovableCameraParams,
+   (look, scroll, movement): (Vec2, f32, Vec3),
+   window_size: Option<Vec2>,
+   (speed, angular_speed): (f32, f32),
+   reference_point: Option<Vec3>,
+   bounds: Option<&CameraBounds>,
+   dt: f32,
+) -> Transform {
+   let transform = free_look(transform, cam_params, look, window_size, angular_speed);
+   let transform = free_zoom(
+      transform,
+      precise.as_deref_mut(),
+      cam_params,
+      scroll,
+      speed,
+      bounds,
+   );
+   free_translate(
+      transform,
+      precise,
+      cam_params,
+      movement,
+      speed,
+      reference_point,
+      bounds,
+      dt,
+   )
+}
+
+/// Moves `transform` by `delta`, adding it to `precise` in `f64` if given.
+fn offset_translation(transform: &mut Transform, precise: &mut Option<&mut DVec3>, delta: Vec3) {
+   match precise {
+      Some(precise) => {
+         **precise += delta.as_dvec3();
+         transform.translation = precise.as_vec3();
+      }
+      None => transform.translation += delta,
+   }
+}
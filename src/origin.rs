@@ -0,0 +1,170 @@
+//! Floating origin: shifts the world back under a camera that has flown far
+//! from the origin, so positions near it keep their `f32` precision.
+
+use crate::{CameraKinematics, FlyingCamSystem, MovableCamera};
+use bevy::{math::DVec3, prelude::*, transform::TransformSystem};
+
+/// Moves the camera, its rig and every [`FloatingOriginRoot`] back towards
+/// the origin whenever the camera is more than `threshold` units out, and
+/// sends [`OriginRebased`] with the offset.
+///
+/// Only the camera rigs and the tagged roots are moved. Anything else kept in
+/// world space, such as bookmarks, paths, bounds or a coordinate grid of
+/// another crate, should be shifted by the app on [`OriginRebased`].
+pub struct FloatingOriginPlugin {
+   pub threshold: f32,
+}
+
+impl Default for FloatingOriginPlugin {
+   fn default() -> Self {
+      Self { threshold: 1000.0 }
+   }
+}
+
+impl Plugin for FloatingOriginPlugin {
+   fn build(&self, app: &mut App) {
+      app.insert_resource(FloatingOrigin::new(self.threshold))
+         .add_event::<OriginRebased>()
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            rebase_origin
+               .label(FlyingCamSystem::Adjust)
+               .after(FlyingCamSystem::Recover)
+               .before(TransformSystem::TransformPropagate),
+         );
+   }
+}
+
+/// Where the floating origin is, added by [`FloatingOriginPlugin`].
+#[derive(Clone, Copy, Debug)]
+pub struct FloatingOrigin {
+   /// Distance from the origin at which the camera triggers a rebase.
+   pub threshold: f32,
+   /// Camera the origin follows. `None` follows the first movable camera.
+   pub camera: Option<Entity>,
+   /// Position of the current origin in the original world coordinates.
+   pub origin: DVec3,
+}
+
+impl FloatingOrigin {
+   pub fn new(threshold: f32) -> Self {
+      Self {
+         threshold,
+         camera: None,
+         origin: DVec3::ZERO,
+      }
+   }
+
+   /// Position in the original world coordinates of `translation`, given
+   /// relative to the current origin.
+   pub fn to_absolute(&self, translation: Vec3) -> DVec3 {
+      self.origin + translation.as_dvec3()
+   }
+
+   /// Position relative to the current origin of `absolute`, given in the
+   /// original world coordinates.
+   pub fn to_local(&self, absolute: DVec3) -> Vec3 {
+      (absolute - self.origin).as_vec3()
+   }
+}
+
+/// Marks a top-level entity that moves with the world when the origin is
+/// rebased, such as the root of a level.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FloatingOriginRoot;
+
+/// Sent when the world is shifted back towards the camera.
+#[derive(Clone, Copy, Debug)]
+pub struct OriginRebased {
+   /// How far everything was moved back; subtract it from world positions.
+   pub offset: Vec3,
+   /// Position of the new origin in the original world coordinates.
+   pub origin: DVec3,
+}
+
+/// Rebases the origin onto the followed camera once it is past the
+/// threshold.
+pub fn rebase_origin(
+   mut origin: ResMut<FloatingOrigin>,
+   mut events: EventWriter<OriginRebased>,
+   mut q_cams: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&mut CameraKinematics>,
+   )>,
+   mut q_transforms: Query<&mut Transform, Without<MovableCamera>>,
+   q_roots: Query<Entity, With<FloatingOriginRoot>>,
+) {
+   let followed = match origin.camera {
+      Some(camera) => q_cams.get(camera).ok(),
+      None => q_cams.iter().next(),
+   };
+   let offset = match followed {
+      Some((_, parent, transform_child, ..)) => match q_transforms.get(parent.0) {
+         Ok(transform_parent) => transform_parent.mul_transform(*transform_child).translation,
+         Err(_) => return,
+      },
+      None => return,
+   };
+   if offset.length() <= origin.threshold {
+      return;
+   }
+   origin.origin += offset.as_dvec3();
+   events.send(OriginRebased {
+      offset,
+      origin: origin.origin,
+   });
+
+   let mut rig_roots = Vec::new();
+   for (_, parent, mut transform_child, mut cam, kinematics) in q_cams.iter_mut() {
+      let cam = &mut *cam;
+      // Shift the precise position along with the camera, unless something
+      // else has already moved the camera away from it
+      let precise = cam
+         .precise_translation
+         .filter(|precise| precise.as_vec3() == transform_child.translation);
+      if let Ok(mut transform_parent) = q_transforms.get_mut(parent.0) {
+         // A free rig keeps its pose in the child, anything else in the root
+         if transform_parent.translation == Vec3::ZERO
+            && transform_parent.rotation == Quat::IDENTITY
+         {
+            transform_child.translation -= offset;
+         } else {
+            transform_parent.translation -= offset;
+            rig_roots.push(parent.0);
+         }
+      }
+      if let Some(precise) = precise {
+         let precise = precise - offset.as_dvec3();
+         cam.precise_translation = Some(precise);
+         if !rig_roots.contains(&parent.0) {
+            transform_child.translation = precise.as_vec3();
+         }
+      }
+      for point in [
+         cam.reference_point.as_mut(),
+         cam.pending_focus.as_mut(),
+         cam.follow_anchor.as_mut(),
+         Some(&mut cam.pivot_transition_start),
+      ]
+      .into_iter()
+      .flatten()
+      {
+         *point -= offset;
+      }
+      if let Some(mut kinematics) = kinematics {
+         kinematics.previous.translation -= offset;
+         kinematics.current.translation -= offset;
+      }
+   }
+   for root in q_roots.iter() {
+      if rig_roots.contains(&root) {
+         continue;
+      }
+      if let Ok(mut transform) = q_transforms.get_mut(root) {
+         transform.translation -= offset;
+      }
+   }
+}
@@ -0,0 +1,255 @@
+//! Orthographic camera helpers, where zooming changes the projection scale
+//! rather than the distance to the pivot, and the [`CameraMode::Pan2d`]
+//! canvas mode. Free flight and orbit of orthographic cameras run in the
+//! [movable camera systems](crate::MovableCameraSystem).
+
+use crate::{
+   free_rig, move_free, net_movement, rig_root, window_has_mouse, window_size, CameraActions,
+   CameraMode, CameraMouse, CameraTime, FlyingCamAction, MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::{Div, Mul, Neg};
+
+/// Fraction of the view that one line of scrolling zooms by.
+const ORTHO_ZOOM_STEP: f32 = 0.1;
+
+/// Settings of [`CameraMode::Pan2d`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pan2dParams {
+   /// Smallest and largest projection scale.
+   pub scale_limits: (f32, f32),
+   /// Let `RotateLeft` and `RotateRight` spin the view, at
+   /// [`TopDownParams::rotate_speed`](crate::TopDownParams::rotate_speed).
+   pub rotation: bool,
+   /// Snap the camera to whole pixels so pixel art stays crisp.
+   pub pixel_snap: bool,
+}
+
+impl Default for Pan2dParams {
+   fn default() -> Self {
+      Self {
+         scale_limits: (0.01, 100.0),
+         rotation: false,
+         pixel_snap: false,
+      }
+   }
+}
+
+/// Projection scale at which `radius` around the pivot fills the smaller side
+/// of the view, with `margin` to spare.
+pub fn ortho_framing_scale(radius: f32, projection: &OrthographicProjection, margin: f32) -> f32 {
+   let extent = (projection.right - projection.left).min(projection.top - projection.bottom);
+   if extent <= 0.0 || radius <= 0.0 {
+      return projection.scale;
+   }
+   radius.mul(2.0).mul(margin).div(extent)
+}
+
+/// Projection scale showing as much as a perspective camera with `fov` shows
+/// `distance` away, so orbit distances carry over to orthographic cameras.
+pub fn ortho_scale_for_distance(
+   distance: f32,
+   fov: f32,
+   projection: &OrthographicProjection,
+) -> f32 {
+   let extent = projection.top - projection.bottom;
+   if extent <= 0.0 {
+      return projection.scale;
+   }
+   distance.mul(2.0).mul(fov.div(2.0).tan()).div(extent)
+}
+
+/// World units covered by one pixel of a window `window_width` pixels wide.
+pub(crate) fn units_per_pixel(projection: &OrthographicProjection, window_width: f32) -> f32 {
+   (projection.right - projection.left)
+      .mul(projection.scale)
+      .div(window_width)
+}
+
+/// Projection scale after zooming by `scroll` lines within `limits`, and how
+/// far to shift the view along its (right, up) axes so the point under
+/// `cursor`, given with the window size, stays put.
+pub(crate) fn ortho_zoom(
+   projection: &OrthographicProjection,
+   scroll: f32,
+   (min_scale, max_scale): (f32, f32),
+   cursor: Option<(Vec2, Vec2)>,
+) -> (f32, Vec2) {
+   let scale = projection
+      .scale
+      .mul((1.0 - ORTHO_ZOOM_STEP).powf(scroll))
+      .clamp(min_scale, max_scale);
+   let shift = match cursor {
+      Some((window_size, cursor)) if window_size.x > 0.0 && projection.scale > 0.0 => {
+         let pixel = units_per_pixel(projection, window_size.x);
+         let offset = cursor - window_size.div(2.0);
+         offset.mul(pixel - pixel.mul(scale.div(projection.scale)))
+      }
+      _ => Vec2::ZERO,
+   };
+   (scale, shift)
+}
+
+/// Computes the world-space ray through a point of the window for an
+/// orthographic camera, like [`viewport_ray`](crate::viewport_ray). The rays
+/// are parallel, starting from the point on the view plane.
+pub fn ortho_viewport_ray(
+   window_size: Vec2,
+   cursor: Vec2,
+   projection: &OrthographicProjection,
+   camera: &GlobalTransform,
+) -> (Vec3, Vec3) {
+   let fraction = cursor.div(window_size);
+   let view_point = Vec3::new(
+      (projection.left + (projection.right - projection.left).mul(fraction.x))
+         .mul(projection.scale),
+      (projection.bottom + (projection.top - projection.bottom).mul(fraction.y))
+         .mul(projection.scale),
+      0.0,
+   );
+   (
+      camera.translation + camera.rotation.mul_vec3(view_point),
+      camera.rotation.mul_vec3(Vec3::Z.neg()),
+   )
+}
+
+/// Pans [`CameraMode::Pan2d`] cameras in their view plane by dragging with
+/// `Pan` or `Secondary` or with the movement keys, and zooms them towards the
+/// cursor. With [`Pan2dParams::rotation`] the `RotateLeft` and `RotateRight`
+/// actions spin the view.
+pub fn pan_2d_camera(
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      Entity,
+      &Camera,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &mut OrthographicProjection,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let motion_total = mouse.motion;
+   let scroll_total = mouse.scroll;
+
+   for (entity, camera, parent, mut transform_child, mut cam, mut projection) in q_child.iter_mut()
+   {
+      let dt = time.camera_delta_seconds(entity);
+      if cam.mode() != CameraMode::Pan2d {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      if cam.mode_entered {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         let (free_parent, free_child) = free_rig(&root, &child);
+         let (free_parent, free_child) = space.to_local(&free_parent, &free_child);
+         *transform_parent = free_parent;
+         *transform_child = free_child;
+         cam.mode_entered = false;
+      }
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let window_size = match window_size(&windows, camera.window) {
+         Some(window_size) if window_size.x > 0.0 => window_size,
+         _ => continue,
+      };
+      let has_mouse = window_has_mouse(&windows, camera.window);
+      // Movement across the view, in world units along its right and up
+      let mut pan = Vec2::ZERO;
+      let pixel = units_per_pixel(&projection, window_size.x);
+
+      // Dragging moves the canvas with the cursor
+      if has_mouse
+         && (action_state.pressed(FlyingCamAction::Pan)
+            || action_state.pressed(FlyingCamAction::Secondary))
+      {
+         pan += Vec2::new(motion_total.x.neg(), motion_total.y).mul(pixel);
+      }
+
+      // Keys cross the view at `speed` view heights per second
+      let keys = Vec2::new(
+         net_movement(action_state, FlyingCamAction::Left, FlyingCamAction::Right),
+         net_movement(
+            action_state,
+            FlyingCamAction::Back,
+            FlyingCamAction::Forward,
+         ),
+      )
+      .normalize_or_zero();
+      if keys.length_squared() > 0.0 {
+         let step = cam.speed.mul(window_size.y.mul(pixel)).mul(dt);
+         pan += keys.mul(step);
+      }
+
+      // Zoom so the point under the cursor stays put
+      if has_mouse && scroll_total.abs() > 0.0 {
+         let cursor = windows
+            .get(camera.window)
+            .and_then(|window| window.cursor_position());
+         let (scale, shift) = ortho_zoom(
+            &projection,
+            scroll_total,
+            cam_params.pan_2d.scale_limits,
+            cursor.map(|cursor| (window_size, cursor)),
+         );
+         pan += shift;
+         projection.scale = scale;
+      }
+
+      let turn = if cam_params.pan_2d.rotation {
+         net_movement(
+            action_state,
+            FlyingCamAction::RotateRight,
+            FlyingCamAction::RotateLeft,
+         )
+         .mul(cam_params.top_down.rotate_speed)
+         .mul(dt)
+      } else {
+         0.0
+      };
+
+      // Keep the view on whole pixels of the current zoom
+      let grid = units_per_pixel(&projection, window_size.x);
+      let snap = cam_params.pan_2d.pixel_snap && grid > 0.0;
+
+      move_free(
+         space,
+         (&mut transform_parent, &mut transform_child),
+         |mut eye| {
+            eye.translation += eye.right().mul(pan.x) + eye.up().mul(pan.y);
+            if turn != 0.0 {
+               eye.rotation = Quat::from_axis_angle(eye.back(), turn).mul(eye.rotation);
+            }
+            if snap {
+               eye.translation.x = eye.translation.x.div(grid).round().mul(grid);
+               eye.translation.y = eye.translation.y.div(grid).round().mul(grid);
+            }
+            eye
+         },
+      );
+   }
+}
+
+/// Rig layout that keeps the camera's orientation and distance along its view
+/// axis while centering `pivot` in the view.
+pub(crate) fn centered_rig(pivot: Vec3, eye: Transform) -> (Transform, Transform) {
+   let depth = (eye.translation - pivot).dot(eye.back());
+   (
+      Transform {
+         translation: pivot,
+         rotation: eye.rotation,
+         ..Default::default()
+      },
+      Transform::from_xyz(0.0, 0.0, depth),
+   )
+}
@@ -0,0 +1,552 @@
+//! [`CameraMode::Path`]: fly the camera along a spline through keyframes.
+
+use crate::{
+   free_rig, smoothstep, window_has_mouse, window_size, CameraActions, CameraMode, CameraMouse,
+   CameraTime, FlyingCamAction, MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::{
+   f32::consts::{FRAC_PI_2, PI, TAU},
+   ops::{Div, Mul, Neg},
+};
+
+/// How a path segment speeds up and slows down between two keyframes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+   Linear,
+   EaseIn,
+   EaseOut,
+   EaseInOut,
+}
+
+impl Easing {
+   /// Maps linear progress in `[0, 1]` to eased progress.
+   pub fn apply(&self, t: f32) -> f32 {
+      let t = t.clamp(0.0, 1.0);
+      match self {
+         Easing::Linear => t,
+         Easing::EaseIn => t.mul(t),
+         Easing::EaseOut => 1.0 - (1.0 - t).mul(1.0 - t),
+         Easing::EaseInOut => smoothstep(t),
+      }
+   }
+}
+
+impl Default for Easing {
+   fn default() -> Self {
+      Easing::Linear
+   }
+}
+
+/// A camera pose on a [`CameraPath`], reached `time` seconds into the path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraKeyframe {
+   pub position: Vec3,
+   pub rotation: Quat,
+   /// Vertical field of view in radians.
+   pub fov: f32,
+   pub time: f32,
+   /// Easing of the segment from this keyframe to the next one.
+   pub easing: Easing,
+}
+
+/// Keyframes a [`CameraMode::Path`] camera flies through on a Catmull-Rom
+/// spline, sorted by time.
+#[derive(Component, Clone, Debug, Default)]
+pub struct CameraPath {
+   pub keyframes: Vec<CameraKeyframe>,
+   /// Start over from the first keyframe after the last one.
+   pub looping: bool,
+}
+
+impl CameraPath {
+   /// Time of the first keyframe.
+   pub fn start(&self) -> f32 {
+      self.keyframes.first().map_or(0.0, |keyframe| keyframe.time)
+   }
+
+   /// Time of the last keyframe. The path runs for `end() - start()` seconds.
+   pub fn end(&self) -> f32 {
+      self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+   }
+
+   /// Interpolated pose at `time` seconds into the path, or `None` for an
+   /// empty path. Times outside the path are clamped, or wrapped when looping.
+   pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+      let count = self.keyframes.len();
+      let first = *self.keyframes.first()?;
+      let end = self.end();
+      let time = if self.looping && end > first.time {
+         first.time + (time - first.time).rem_euclid(end - first.time)
+      } else {
+         time.clamp(first.time, end)
+      };
+      let next = self
+         .keyframes
+         .iter()
+         .position(|keyframe| keyframe.time > time)
+         .unwrap_or(count);
+      if next == 0 || next == count {
+         let keyframe = self.keyframes[next.saturating_sub(1).min(count - 1)];
+         return Some(CameraKeyframe { time, ..keyframe });
+      }
+
+      let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+      let span = to.time - from.time;
+      let t = if span > 0.0 {
+         from.easing.apply((time - from.time).div(span))
+      } else {
+         1.0
+      };
+      // Neighbours for the spline tangents, wrapping around on looping paths
+      let neighbour = |index: isize| {
+         let index = if self.looping {
+            index.rem_euclid(count as isize)
+         } else {
+            index.clamp(0, count as isize - 1)
+         };
+         self.keyframes[index as usize].position
+      };
+      let position = catmull_rom(
+         neighbour(next as isize - 2),
+         from.position,
+         to.position,
+         neighbour(next as isize + 1),
+         t,
+      );
+      Some(CameraKeyframe {
+         position,
+         rotation: from.rotation.slerp(to.rotation, t),
+         fov: from.fov + (to.fov - from.fov).mul(t),
+         time,
+         easing: from.easing,
+      })
+   }
+}
+
+/// A [`CameraPath`] sampled at a fixed rate, as parallel keyframe tracks.
+/// This version of Bevy has no `AnimationClip` to export to, so the tracks are
+/// kept in the layout animation curves use: once there is one, a baked path
+/// can be turned into a clip targeting the camera without resampling the
+/// spline. Until then, play it back with a [`CameraPath`] or your own system.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BakedCameraPath {
+   pub times: Vec<f32>,
+   pub translations: Vec<Vec3>,
+   pub rotations: Vec<Quat>,
+   pub fovs: Vec<f32>,
+}
+
+impl CameraPath {
+   /// Samples the path `sample_rate` times per second, from its first to its
+   /// last keyframe, with the eased spline baked in.
+   pub fn bake(&self, sample_rate: f32) -> BakedCameraPath {
+      let mut baked = BakedCameraPath::default();
+      let (start, end) = (self.start(), self.end());
+      if self.keyframes.is_empty() || sample_rate <= 0.0 {
+         return baked;
+      }
+      let samples = ((end - start).mul(sample_rate).ceil() as usize).max(1);
+      for i in 0..=samples {
+         let time = (start + (i as f32).div(sample_rate)).min(end);
+         // Sample just short of the end of a looping path so it doesn't wrap
+         let sample_time = if self.looping && i == samples {
+            end - f32::EPSILON.mul(end.abs().max(1.0))
+         } else {
+            time
+         };
+         if let Some(pose) = self.sample(sample_time) {
+            baked.times.push(time);
+            baked.translations.push(pose.position);
+            baked.rotations.push(pose.rotation);
+            baked.fovs.push(pose.fov);
+         }
+      }
+      baked
+   }
+}
+
+/// Point `t` of the way from `p1` to `p2` on a uniform Catmull-Rom spline.
+pub fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+   let (t2, t3) = (t * t, t * t * t);
+   (p1.mul(2.0)
+      + (p2 - p0).mul(t)
+      + (p0.mul(2.0) - p1.mul(5.0) + p2.mul(4.0) - p3).mul(t2)
+      + (p1.mul(3.0) - p0 - p2.mul(3.0) + p3).mul(t3))
+   .mul(0.5)
+}
+
+/// Where a [`CameraMode::Path`] camera is on its [`CameraPath`]. Added when
+/// the camera enters the mode. Tools can scrub the camera by writing `time`,
+/// which is shown even while paused.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct PathPlayback {
+   /// Seconds into the path.
+   pub time: f32,
+   /// Playback rate, `1.0` is real time and negative plays backwards.
+   pub speed: f32,
+   pub paused: bool,
+}
+
+impl Default for PathPlayback {
+   fn default() -> Self {
+      Self {
+         time: 0.0,
+         speed: 1.0,
+         paused: false,
+      }
+   }
+}
+
+impl PathPlayback {
+   /// Pauses at `time` seconds into the path.
+   pub fn seek(&mut self, time: f32) {
+      self.time = time;
+      self.paused = true;
+   }
+
+   /// Pauses and moves `frames` frames of `frame_rate` per second along the
+   /// path, backwards for negative `frames`.
+   pub fn step(&mut self, frames: i32, frame_rate: f32) {
+      if frame_rate > 0.0 {
+         self.seek(self.time + (frames as f32).div(frame_rate));
+      }
+   }
+}
+
+/// Playback commands for [`ControlPath`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathControl {
+   Play,
+   Pause,
+   TogglePause,
+   /// Jump back to the start of the path.
+   Rewind,
+   SetLooping(bool),
+   /// Pause at a time along the path.
+   Seek(f32),
+   /// Pause and move a number of frames at a frame rate.
+   Step {
+      frames: i32,
+      frame_rate: f32,
+   },
+   SetSpeed(f32),
+}
+
+/// Event that controls the path playback of a camera.
+#[derive(Clone, Copy, Debug)]
+pub struct ControlPath {
+   pub camera: Entity,
+   pub control: PathControl,
+}
+
+/// Sent when a camera reaches the end of a path that doesn't loop.
+#[derive(Clone, Copy, Debug)]
+pub struct PathFinished {
+   pub camera: Entity,
+}
+
+/// Applies [`ControlPath`] events.
+pub fn control_path(
+   mut events: EventReader<ControlPath>,
+   mut q_path: Query<(&mut CameraPath, Option<&mut PathPlayback>)>,
+) {
+   for ControlPath { camera, control } in events.iter() {
+      let (mut path, playback) = match q_path.get_mut(*camera) {
+         Ok(path) => path,
+         Err(_) => continue,
+      };
+      if let PathControl::SetLooping(looping) = control {
+         path.looping = *looping;
+         continue;
+      }
+      let mut playback = match playback {
+         Some(playback) => playback,
+         None => continue,
+      };
+      match control {
+         PathControl::Play => {
+            // Playing a finished path plays it again
+            if !path.looping {
+               if playback.speed >= 0.0 && playback.time >= path.end() {
+                  playback.time = path.start();
+               } else if playback.speed < 0.0 && playback.time <= path.start() {
+                  playback.time = path.end();
+               }
+            }
+            playback.paused = false;
+         }
+         PathControl::Pause => playback.paused = true,
+         PathControl::TogglePause => playback.paused = !playback.paused,
+         PathControl::Rewind => playback.time = path.start(),
+         PathControl::Seek(time) => playback.seek(*time),
+         PathControl::Step { frames, frame_rate } => playback.step(*frames, *frame_rate),
+         PathControl::SetSpeed(speed) => playback.speed = *speed,
+         PathControl::SetLooping(_) => {}
+      }
+   }
+}
+
+/// Authors paths in-game. `RecordKeyframe` appends the camera's pose to its
+/// [`CameraPath`], timed by how long after the previous keyframe it was
+/// recorded. `PlayPath` starts playing the path, or pauses and resumes it,
+/// and `StopPath` returns to free flight.
+pub fn record_camera_path(
+   mut commands: Commands,
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   actions: CameraActions,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &Transform,
+      &mut MovableCamera,
+      Option<&mut CameraPath>,
+      Option<&mut PathPlayback>,
+      Option<&PerspectiveProjection>,
+   )>,
+   q_parent: Query<&Transform, Without<MovableCamera>>,
+) {
+   let now = time.seconds_since_startup();
+   for (entity, parent, transform_child, mut cam, path, playback, projection) in q_child.iter_mut()
+   {
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+
+      if action_state.just_pressed(FlyingCamAction::RecordKeyframe)
+         && cam.mode() != CameraMode::Path
+      {
+         let eye = match q_parent.get(parent.0) {
+            Ok(transform_parent) => transform_parent.mul_transform(*transform_child),
+            Err(_) => *transform_child,
+         };
+         let gap = cam
+            .last_keyframe_at
+            .map_or(cam_params.keyframe_spacing, |last| (now - last) as f32);
+         let mut keyframe = CameraKeyframe {
+            position: eye.translation,
+            rotation: eye.rotation,
+            fov: projection.map_or(std::f32::consts::FRAC_PI_4, |projection| projection.fov),
+            time: 0.0,
+            easing: Easing::Linear,
+         };
+         match path {
+            Some(mut path) => {
+               if let Some(last) = path.keyframes.last() {
+                  keyframe.time = last.time + gap;
+               }
+               path.keyframes.push(keyframe);
+            }
+            None => {
+               commands.entity(entity).insert(CameraPath {
+                  keyframes: vec![keyframe],
+                  looping: false,
+               });
+            }
+         }
+         cam.last_keyframe_at = Some(now);
+      } else if action_state.just_pressed(FlyingCamAction::PlayPath) {
+         if cam.mode() == CameraMode::Path {
+            if let Some(mut playback) = playback {
+               playback.paused = !playback.paused;
+            }
+         } else if path.map_or(false, |path| !path.keyframes.is_empty()) {
+            // A refused switch, e.g. while disabled, leaves the camera alone
+            let _ = cam.set_mode(CameraMode::Path);
+         }
+      } else if action_state.just_pressed(FlyingCamAction::StopPath)
+         && cam.mode() == CameraMode::Path
+      {
+         let _ = cam.set_mode(CameraMode::Free);
+      }
+   }
+}
+
+/// A point a path camera looks at from `time` seconds into the path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AimPoint {
+   pub time: f32,
+   pub point: Vec3,
+}
+
+/// What a path camera looks at.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AimTarget {
+   /// The rotations of the keyframes.
+   Keyframes,
+   /// Points keyframed on their own, sorted by time and blended between.
+   Points(Vec<AimPoint>),
+   /// An entity, wherever it goes.
+   Entity(Entity),
+}
+
+impl Default for AimTarget {
+   fn default() -> Self {
+      AimTarget::Keyframes
+   }
+}
+
+/// Aims a [`CameraMode::Path`] camera separately from the path it moves
+/// along, for a camera on rails that the player can look around from.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct PathAim {
+   pub target: AimTarget,
+   /// Dragging with `Secondary` looks around on top of the aim.
+   pub free_look: bool,
+}
+
+/// Aim point `time` seconds into the path, holding the first and last points
+/// outside of them.
+fn sample_aim_points(points: &[AimPoint], time: f32) -> Option<Vec3> {
+   let next = points.iter().position(|aim| aim.time > time);
+   match next {
+      Some(0) => points.first().map(|aim| aim.point),
+      Some(next) => {
+         let (from, to) = (points[next - 1], points[next]);
+         let span = to.time - from.time;
+         let t = if span > 0.0 {
+            (time - from.time).div(span)
+         } else {
+            1.0
+         };
+         Some(from.point.lerp(to.point, t))
+      }
+      None => points.last().map(|aim| aim.point),
+   }
+}
+
+/// Moves [`CameraMode::Path`] cameras along their [`CameraPath`], aiming them
+/// with their [`PathAim`] if they have one.
+pub fn follow_camera_path(
+   mut commands: Commands,
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   mut finished: EventWriter<PathFinished>,
+   q_targets: Query<&GlobalTransform>,
+   mut q_child: Query<(
+      Entity,
+      &Camera,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &CameraPath,
+      Option<&mut PathPlayback>,
+      Option<&mut PerspectiveProjection>,
+      Option<&PathAim>,
+   )>,
+   mut q_parent: Query<&mut Transform, Without<MovableCamera>>,
+) {
+   let motion_total = mouse.motion;
+
+   for (entity, camera, parent, mut transform_child, mut cam, path, playback, projection, aim) in
+      q_child.iter_mut()
+   {
+      if cam.mode() != CameraMode::Path {
+         continue;
+      }
+      // Paths are in world space, so fly on a free rig
+      if cam.mode_entered {
+         if let Ok(mut transform_parent) = q_parent.get_mut(parent.0) {
+            let (free_parent, free_child) = free_rig(&transform_parent, &transform_child);
+            *transform_parent = free_parent;
+            *transform_child = free_child;
+         }
+         cam.mode_entered = false;
+         cam.path_look = Vec2::ZERO;
+         commands.entity(entity).insert(PathPlayback {
+            time: path.start(),
+            ..Default::default()
+         });
+         continue;
+      }
+      let mut playback = match playback {
+         Some(playback) => playback,
+         None => continue,
+      };
+
+      let (start, end) = (path.start(), path.end());
+      if !playback.paused {
+         // Playing backwards finishes at the start
+         let forward = playback.speed >= 0.0;
+         let at_end = |time: f32| if forward { time >= end } else { time <= start };
+         let was_playing = !at_end(playback.time);
+         playback.time += time.camera_delta_seconds(entity).mul(playback.speed);
+         if !path.looping && at_end(playback.time) {
+            if was_playing {
+               finished.send(PathFinished { camera: entity });
+            }
+            playback.paused = true;
+         }
+      }
+      if !path.looping {
+         playback.time = playback.time.clamp(start, end);
+      }
+
+      let pose = match path.sample(playback.time) {
+         Some(pose) => pose,
+         None => continue,
+      };
+      transform_child.translation = pose.position;
+      if let Some(mut projection) = projection {
+         projection.fov = pose.fov;
+      }
+      let aim = match aim {
+         Some(aim) => aim,
+         None => {
+            transform_child.rotation = pose.rotation;
+            continue;
+         }
+      };
+
+      let point = match &aim.target {
+         AimTarget::Keyframes => None,
+         AimTarget::Points(points) => sample_aim_points(points, pose.time),
+         AimTarget::Entity(target) => q_targets.get(*target).ok().map(|global| global.translation),
+      };
+      let to_point = point.map(|point| point - pose.position);
+      let rotation = match (point, to_point) {
+         // A degenerate direction keeps the keyframed rotation
+         (Some(point), Some(to_point))
+            if to_point.cross(Vec3::Y).length_squared() > f32::EPSILON =>
+         {
+            Transform::from_translation(pose.position)
+               .looking_at(point, Vec3::Y)
+               .rotation
+         }
+         _ => pose.rotation,
+      };
+
+      if aim.free_look {
+         let dragging = actions.get(entity).map_or(false, |action_state| {
+            action_state.pressed(FlyingCamAction::Secondary)
+         });
+         if let (true, true, Some(window_size)) = (
+            dragging,
+            window_has_mouse(&windows, camera.window),
+            window_size(&windows, camera.window),
+         ) {
+            cam.path_look.x -= motion_total
+               .x
+               .div(window_size.x)
+               .mul(TAU)
+               .mul(cam.angular_speed);
+            cam.path_look.y = (cam.path_look.y
+               - motion_total
+                  .y
+                  .div(window_size.y)
+                  .mul(PI)
+                  .mul(cam.angular_speed))
+            .clamp(FRAC_PI_2.neg(), FRAC_PI_2);
+         }
+         transform_child.rotation = Quat::from_rotation_y(cam.path_look.x)
+            .mul(rotation)
+            .mul(Quat::from_rotation_x(cam.path_look.y));
+      } else {
+         transform_child.rotation = rotation;
+      }
+   }
+}
@@ -0,0 +1,132 @@
+//! Saving [`CameraBookmarks`] to a RON file, one set of bookmarks per scene.
+
+use crate::{CameraBookmark, CameraBookmarks};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+   collections::BTreeMap,
+   fs, io,
+   path::{Path, PathBuf},
+};
+
+/// File the bookmarks are kept in and the scene whose bookmarks are in use.
+/// Changing `scene`, e.g. on a level change, swaps in that scene's bookmarks.
+#[derive(Clone, Debug)]
+pub struct BookmarkFile {
+   pub path: PathBuf,
+   pub scene: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredBookmark {
+   translation: [f32; 3],
+   rotation: [f32; 4],
+   pivot: Option<[f32; 3]>,
+   zoom: Option<f32>,
+}
+
+impl From<&CameraBookmark> for StoredBookmark {
+   fn from(bookmark: &CameraBookmark) -> Self {
+      Self {
+         translation: bookmark.eye.translation.to_array(),
+         rotation: bookmark.eye.rotation.to_array(),
+         pivot: bookmark.pivot.map(|pivot| pivot.to_array()),
+         zoom: bookmark.zoom,
+      }
+   }
+}
+
+impl From<&StoredBookmark> for CameraBookmark {
+   fn from(stored: &StoredBookmark) -> Self {
+      Self {
+         eye: Transform {
+            translation: Vec3::from(stored.translation),
+            rotation: Quat::from_array(stored.rotation).normalize(),
+            ..Default::default()
+         },
+         pivot: stored.pivot.map(Vec3::from),
+         zoom: stored.zoom,
+      }
+   }
+}
+
+/// Bookmarks of every scene, by scene and slot.
+type StoredScenes = BTreeMap<String, BTreeMap<u32, StoredBookmark>>;
+
+fn read_scenes(path: &Path) -> io::Result<StoredScenes> {
+   match fs::read_to_string(path) {
+      Ok(text) => {
+         ron::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+      }
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(StoredScenes::new()),
+      Err(err) => Err(err),
+   }
+}
+
+impl CameraBookmarks {
+   /// Reads the bookmarks of `scene` from `path`. A missing file has no
+   /// bookmarks.
+   pub fn load(path: &Path, scene: &str) -> io::Result<Self> {
+      let scenes = read_scenes(path)?;
+      let slots: BTreeMap<u32, CameraBookmark> = scenes
+         .get(scene)
+         .map(|slots| {
+            slots
+               .iter()
+               .map(|(slot, stored)| (*slot, CameraBookmark::from(stored)))
+               .collect()
+         })
+         .unwrap_or_default();
+      Ok(Self { slots })
+   }
+
+   /// Writes these bookmarks to `path` as the bookmarks of `scene`, keeping
+   /// other scenes' bookmarks in the file.
+   pub fn store(&self, path: &Path, scene: &str) -> io::Result<()> {
+      let mut scenes = read_scenes(path)?;
+      scenes.insert(
+         scene.to_string(),
+         self
+            .slots
+            .iter()
+            .map(|(slot, bookmark)| (*slot, StoredBookmark::from(bookmark)))
+            .collect(),
+      );
+      let text = ron::ser::to_string_pretty(&scenes, ron::ser::PrettyConfig::default())
+         .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+      fs::write(path, text)
+   }
+}
+
+/// Loads the current scene's bookmarks whenever the [`BookmarkFile`] changes,
+/// and writes them back whenever they change.
+pub fn persist_bookmarks(
+   file: Option<Res<BookmarkFile>>,
+   mut bookmarks: ResMut<CameraBookmarks>,
+   mut just_loaded: Local<bool>,
+) {
+   let file = match file {
+      Some(file) => file,
+      None => return,
+   };
+   if file.is_changed() {
+      match CameraBookmarks::load(&file.path, &file.scene) {
+         Ok(loaded) => *bookmarks = loaded,
+         Err(err) => warn!(
+            "couldn't load camera bookmarks from {:?}: {}",
+            file.path, err
+         ),
+      }
+      // Replacing the bookmarks marks them changed; don't write them straight back
+      *just_loaded = true;
+      return;
+   }
+   if std::mem::take(&mut *just_loaded) {
+      return;
+   }
+   if bookmarks.is_changed() {
+      if let Err(err) = bookmarks.store(&file.path, &file.scene) {
+         warn!("couldn't save camera bookmarks to {:?}: {}", file.path, err);
+      }
+   }
+}
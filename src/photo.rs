@@ -0,0 +1,263 @@
+//! Photo mode: pause the game, fly the camera slowly, adjust field of view and
+//! roll, and take screenshots.
+
+use crate::{CameraActions, CameraMode, FlyingCamAction, FlyingCamSystem, MovableCamera};
+use bevy::{ecs::schedule::ShouldRun, prelude::*, transform::TransformSystem};
+use std::{
+   ops::{Mul, Neg},
+   path::PathBuf,
+};
+
+/// Adds photo mode, toggled with `PhotoMode` or [`TogglePhotoMode`].
+///
+/// This version of Bevy can neither pause [`Time`] nor read back frames, so
+/// gameplay systems should use [`photo_mode_inactive`] as their run criteria
+/// to freeze, and the app captures the frame when it receives
+/// [`ScreenshotRequested`].
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+   fn build(&self, app: &mut App) {
+      app.init_resource::<PhotoMode>()
+         .add_event::<TogglePhotoMode>()
+         .add_event::<ScreenshotRequested>()
+         .add_system_to_stage(CoreStage::PreUpdate, unroll_photo_camera)
+         .add_system(photo_mode.label(FlyingCamSystem::Move))
+         .add_system_to_stage(
+            CoreStage::PostUpdate,
+            roll_photo_camera
+               .label(FlyingCamSystem::Adjust)
+               .after(FlyingCamSystem::Recover)
+               .before(TransformSystem::TransformPropagate),
+         );
+   }
+}
+
+/// Photo mode settings, and the camera in photo mode.
+#[derive(Clone, Debug)]
+pub struct PhotoMode {
+   /// Fly speed multiplier while in photo mode.
+   pub speed_scale: f32,
+   /// Narrowest and widest field of view, in radians.
+   pub fov_limits: (f32, f32),
+   /// Radians per second `ZoomIn` and `ZoomOut` change the field of view by.
+   pub zoom_speed: f32,
+   /// Radians per second `RotateLeft` and `RotateRight` roll the camera by.
+   pub roll_speed: f32,
+   /// Entities hidden while in photo mode, such as the HUD.
+   pub hide: Vec<Entity>,
+   /// Directory screenshots are saved to.
+   pub screenshot_dir: PathBuf,
+   /// Render scale requested for screenshots.
+   pub supersampling: u32,
+   state: Option<PhotoState>,
+   screenshots: u32,
+}
+
+#[derive(Clone, Debug)]
+struct PhotoState {
+   camera: Entity,
+   mode: CameraMode,
+   speed: f32,
+   fov: Option<f32>,
+   roll: f32,
+   hidden: Vec<(Entity, bool)>,
+}
+
+impl Default for PhotoMode {
+   fn default() -> Self {
+      Self {
+         speed_scale: 0.2,
+         fov_limits: (0.1, 2.0),
+         zoom_speed: 0.5,
+         roll_speed: 0.5,
+         hide: Vec::new(),
+         screenshot_dir: PathBuf::from("screenshots"),
+         supersampling: 1,
+         state: None,
+         screenshots: 0,
+      }
+   }
+}
+
+impl PhotoMode {
+   pub fn is_active(&self) -> bool {
+      self.state.is_some()
+   }
+
+   /// The camera in photo mode.
+   pub fn camera(&self) -> Option<Entity> {
+      self.state.as_ref().map(|state| state.camera)
+   }
+}
+
+/// Run criteria that stops a system while in photo mode.
+pub fn photo_mode_inactive(photo: Res<PhotoMode>) -> ShouldRun {
+   if photo.is_active() {
+      ShouldRun::No
+   } else {
+      ShouldRun::Yes
+   }
+}
+
+/// Event that enters photo mode with `camera`, or leaves it.
+#[derive(Clone, Copy, Debug)]
+pub struct TogglePhotoMode {
+   pub camera: Entity,
+}
+
+/// Sent when `Screenshot` is pressed in photo mode, for the app to save the
+/// next frame of `camera` to `path`, rendered at `supersampling` times the
+/// window size.
+#[derive(Clone, Debug)]
+pub struct ScreenshotRequested {
+   pub camera: Entity,
+   pub path: PathBuf,
+   pub supersampling: u32,
+}
+
+/// Enters and leaves photo mode and handles its controls.
+pub fn photo_mode(
+   time: Res<Time>,
+   mut photo: ResMut<PhotoMode>,
+   actions: CameraActions,
+   mut toggles: EventReader<TogglePhotoMode>,
+   mut screenshots: EventWriter<ScreenshotRequested>,
+   mut q_cams: Query<(
+      Entity,
+      &mut MovableCamera,
+      Option<&mut PerspectiveProjection>,
+   )>,
+   mut q_visibility: Query<&mut Visibility>,
+) {
+   let photo = &mut *photo;
+   let mut toggle = toggles.iter().map(|event| event.camera).last();
+   for (entity, ..) in q_cams.iter_mut() {
+      if actions.get(entity).map_or(false, |action_state| {
+         action_state.just_pressed(FlyingCamAction::PhotoMode)
+      }) {
+         toggle = Some(entity);
+      }
+   }
+
+   if let Some(camera) = toggle {
+      match photo.state.take() {
+         Some(state) => {
+            if let Ok((_, mut cam, projection)) = q_cams.get_mut(state.camera) {
+               cam.enter_mode(state.mode);
+               cam.speed = state.speed;
+               if let (Some(mut projection), Some(fov)) = (projection, state.fov) {
+                  projection.fov = fov;
+               }
+            }
+            for (entity, was_visible) in state.hidden {
+               if let Ok(mut visibility) = q_visibility.get_mut(entity) {
+                  visibility.is_visible = was_visible;
+               }
+            }
+         }
+         None => {
+            if let Ok((_, mut cam, projection)) = q_cams.get_mut(camera) {
+               let hidden = photo
+                  .hide
+                  .iter()
+                  .filter_map(|entity| {
+                     let mut visibility = q_visibility.get_mut(*entity).ok()?;
+                     let was_visible = visibility.is_visible;
+                     visibility.is_visible = false;
+                     Some((*entity, was_visible))
+                  })
+                  .collect();
+               photo.state = Some(PhotoState {
+                  camera,
+                  mode: cam.mode(),
+                  speed: cam.speed,
+                  fov: projection.map(|projection| projection.fov),
+                  roll: 0.0,
+                  hidden,
+               });
+               // Let free flight adopt whatever rig the camera had
+               cam.enter_mode(CameraMode::Free);
+               cam.orbit_velocity = Vec2::ZERO;
+               cam.speed = cam.speed.mul(photo.speed_scale);
+            }
+         }
+      }
+   }
+
+   let state = match photo.state.as_mut() {
+      Some(state) => state,
+      None => return,
+   };
+   let (_, _, projection) = match q_cams.get_mut(state.camera) {
+      Ok(cam) => cam,
+      // Gone without leaving photo mode, so show what it hid
+      Err(_) => {
+         for (entity, was_visible) in state.hidden.drain(..) {
+            if let Ok(mut visibility) = q_visibility.get_mut(entity) {
+               visibility.is_visible = was_visible;
+            }
+         }
+         photo.state = None;
+         return;
+      }
+   };
+   let action_state = match actions.get(state.camera) {
+      Some(action_state) => action_state,
+      None => return,
+   };
+   let dt = time.delta_seconds();
+   let axis = |negative, positive| {
+      let mut value = 0.0;
+      if action_state.pressed(negative) {
+         value -= 1.0;
+      }
+      if action_state.pressed(positive) {
+         value += 1.0;
+      }
+      value
+   };
+
+   if let Some(mut projection) = projection {
+      let zoom = axis(FlyingCamAction::ZoomIn, FlyingCamAction::ZoomOut);
+      if zoom != 0.0 {
+         projection.fov = (projection.fov + zoom.mul(photo.zoom_speed).mul(dt))
+            .clamp(photo.fov_limits.0, photo.fov_limits.1);
+      }
+   }
+   state.roll += axis(FlyingCamAction::RotateRight, FlyingCamAction::RotateLeft)
+      .mul(photo.roll_speed)
+      .mul(dt);
+
+   if action_state.just_pressed(FlyingCamAction::Screenshot) {
+      photo.screenshots += 1;
+      screenshots.send(ScreenshotRequested {
+         camera: state.camera,
+         path: photo
+            .screenshot_dir
+            .join(format!("photo_{:04}.png", photo.screenshots)),
+         supersampling: photo.supersampling.max(1),
+      });
+   }
+}
+
+/// Takes the photo camera's roll off before the camera systems run, so they
+/// only ever see a level camera.
+pub fn unroll_photo_camera(photo: Res<PhotoMode>, mut q_transform: Query<&mut Transform>) {
+   if let Some(state) = &photo.state {
+      if let Ok(mut transform) = q_transform.get_mut(state.camera) {
+         transform.rotation = transform
+            .rotation
+            .mul(Quat::from_rotation_z(state.roll.neg()));
+      }
+   }
+}
+
+/// Puts the photo camera's roll back on for rendering.
+pub fn roll_photo_camera(photo: Res<PhotoMode>, mut q_transform: Query<&mut Transform>) {
+   if let Some(state) = &photo.state {
+      if let Ok(mut transform) = q_transform.get_mut(state.camera) {
+         transform.rotation = transform.rotation.mul(Quat::from_rotation_z(state.roll));
+      }
+   }
+}
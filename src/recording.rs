@@ -0,0 +1,197 @@
+//! Recording camera trajectories and replaying them on a camera or a ghost,
+//! for comparing navigation runs and debugging motion.
+
+use crate::MovableCamera;
+use bevy::{pbr::AlphaMode, prelude::*, reflect::TypeUuid};
+use std::ops::{Div, Mul};
+
+/// A camera trajectory sampled every frame.
+#[derive(TypeUuid, Clone, Debug, Default)]
+#[uuid = "5d1f6a8e-2b7c-4e91-9a43-c0f2e8b17d36"]
+pub struct CameraRecording {
+   /// World-space camera poses by seconds since the recording started.
+   pub samples: Vec<(f32, Transform)>,
+}
+
+impl CameraRecording {
+   pub fn duration(&self) -> f32 {
+      self.samples.last().map_or(0.0, |(time, _)| *time)
+   }
+
+   /// Pose `time` seconds in, blended between the nearest samples.
+   pub fn sample(&self, time: f32) -> Option<Transform> {
+      let next = self
+         .samples
+         .iter()
+         .position(|(sample_time, _)| *sample_time > time);
+      match next {
+         Some(0) => self.samples.first().map(|(_, transform)| *transform),
+         Some(next) => {
+            let ((from_time, from), (to_time, to)) = (self.samples[next - 1], self.samples[next]);
+            let span = to_time - from_time;
+            let t = if span > 0.0 {
+               (time - from_time).div(span)
+            } else {
+               1.0
+            };
+            Some(Transform {
+               translation: from.translation.lerp(to.translation, t),
+               rotation: from.rotation.slerp(to.rotation, t),
+               scale: from.scale.lerp(to.scale, t),
+            })
+         }
+         None => self.samples.last().map(|(_, transform)| *transform),
+      }
+   }
+}
+
+/// Appends the pose of its camera to `recording` every frame while
+/// `recording` is set.
+#[derive(Component, Clone, Debug, Default)]
+pub struct CameraRecorder {
+   pub recording: Handle<CameraRecording>,
+   pub active: bool,
+   elapsed: f32,
+}
+
+impl CameraRecorder {
+   pub fn new(recording: Handle<CameraRecording>) -> Self {
+      Self {
+         recording,
+         active: true,
+         elapsed: 0.0,
+      }
+   }
+}
+
+/// Replays a [`CameraRecording`] on its entity. On a [`MovableCamera`] it
+/// drives the camera's rig, so switch the camera to
+/// [`CameraMode::Disabled`](crate::CameraMode::Disabled) first; on any other
+/// entity, such as a ghost from [`spawn_recording_ghost`], it sets the
+/// entity's transform.
+#[derive(Component, Clone, Debug)]
+pub struct RecordingPlayback {
+   pub recording: Handle<CameraRecording>,
+   /// Seconds into the recording.
+   pub time: f32,
+   pub speed: f32,
+   pub looping: bool,
+   finished: bool,
+}
+
+impl RecordingPlayback {
+   pub fn new(recording: Handle<CameraRecording>) -> Self {
+      Self {
+         recording,
+         time: 0.0,
+         speed: 1.0,
+         looping: false,
+         finished: false,
+      }
+   }
+}
+
+/// Sent when a [`RecordingPlayback`] that doesn't loop reaches the end.
+#[derive(Clone, Copy, Debug)]
+pub struct RecordingFinished {
+   pub entity: Entity,
+}
+
+/// Spawns a small translucent box that replays `recording`, to compare it
+/// with the live camera.
+pub fn spawn_recording_ghost(
+   commands: &mut Commands,
+   meshes: &mut Assets<Mesh>,
+   materials: &mut Assets<StandardMaterial>,
+   recording: Handle<CameraRecording>,
+) -> Entity {
+   commands
+      .spawn_bundle(PbrBundle {
+         mesh: meshes.add(Mesh::from(shape::Box::new(0.3, 0.2, 0.4))),
+         material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.4, 0.8, 1.0, 0.5),
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            ..Default::default()
+         }),
+         ..Default::default()
+      })
+      .insert(RecordingPlayback::new(recording))
+      .id()
+}
+
+/// Records [`CameraRecorder`]s. Runs after transform propagation so it sees
+/// the pose that was rendered.
+pub fn record_cameras(
+   time: Res<Time>,
+   mut recordings: ResMut<Assets<CameraRecording>>,
+   mut q_recorders: Query<(&mut CameraRecorder, &GlobalTransform)>,
+) {
+   for (mut recorder, global) in q_recorders.iter_mut() {
+      if !recorder.active {
+         continue;
+      }
+      if let Some(recording) = recordings.get_mut(&recorder.recording) {
+         if recording.samples.is_empty() {
+            recorder.elapsed = 0.0;
+         }
+         recording.samples.push((
+            recorder.elapsed,
+            Transform {
+               translation: global.translation,
+               rotation: global.rotation,
+               ..Default::default()
+            },
+         ));
+         recorder.elapsed += time.delta_seconds();
+      }
+   }
+}
+
+/// Advances [`RecordingPlayback`]s and moves their entities.
+pub fn play_recordings(
+   time: Res<Time>,
+   recordings: Res<Assets<CameraRecording>>,
+   mut finished: EventWriter<RecordingFinished>,
+   mut q_playback: Query<(
+      Entity,
+      &mut RecordingPlayback,
+      &mut Transform,
+      Option<&Parent>,
+      Option<&MovableCamera>,
+   )>,
+   mut q_parent: Query<&mut Transform, Without<RecordingPlayback>>,
+) {
+   for (entity, mut playback, mut transform, parent, cam) in q_playback.iter_mut() {
+      let recording = match recordings.get(&playback.recording) {
+         Some(recording) => recording,
+         None => continue,
+      };
+      let duration = recording.duration();
+      if !playback.finished {
+         playback.time += time.delta_seconds().mul(playback.speed);
+         if playback.looping && duration > 0.0 {
+            playback.time = playback.time.rem_euclid(duration);
+         } else if playback.time >= duration || (playback.time <= 0.0 && playback.speed < 0.0) {
+            playback.time = playback.time.clamp(0.0, duration);
+            playback.finished = true;
+            finished.send(RecordingFinished { entity });
+         }
+      }
+      let pose = match recording.sample(playback.time) {
+         Some(pose) => pose,
+         None => continue,
+      };
+      // Recordings are in world space, so a camera flies them on a free rig
+      match (
+         cam,
+         parent.and_then(|parent| q_parent.get_mut(parent.0).ok()),
+      ) {
+         (Some(_), Some(mut transform_parent)) => {
+            *transform_parent = Transform::default();
+            *transform = pose;
+         }
+         _ => *transform = pose,
+      }
+   }
+}
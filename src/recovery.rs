@@ -0,0 +1,172 @@
+//! Recovery from NaN and infinite camera state, so one bad frame doesn't
+//! leave the camera lost for the rest of the session.
+
+use crate::{CameraKinematics, MouseFilter, MovableCamera, MovableCameraParams};
+use bevy::prelude::*;
+
+/// The last rig layout of a movable camera with only finite values in it,
+/// kept by [`recover_cameras`] and added to movable cameras automatically.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct LastGoodPose {
+   pub parent: Transform,
+   pub child: Transform,
+}
+
+/// Puts cameras whose rig or [`MovableCamera`] state picked up a NaN or an
+/// infinity back at their [`LastGoodPose`], stops whatever motion they had
+/// and logs a warning. Runs after the camera systems, before the bad pose is
+/// propagated.
+pub fn recover_cameras(
+   mut commands: Commands,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&mut LastGoodPose>,
+      Option<&CameraKinematics>,
+      Option<&MovableCameraParams>,
+   )>,
+   mut q_parent: Query<&mut Transform, Without<MovableCamera>>,
+) {
+   for (entity, parent, mut transform_child, mut cam, last_good, kinematics, own_params) in
+      q_child.iter_mut()
+   {
+      let mut transform_parent = match q_parent.get_mut(parent.0) {
+         Ok(transform_parent) => transform_parent,
+         Err(_) => continue,
+      };
+      let pose_ok = is_finite(&transform_parent) && is_finite(&transform_child);
+      if pose_ok && state_is_finite(&cam) {
+         let pose = LastGoodPose {
+            parent: *transform_parent,
+            child: *transform_child,
+         };
+         match last_good {
+            Some(mut last_good) => {
+               if *last_good != pose {
+                  *last_good = pose;
+               }
+            }
+            None => {
+               commands.entity(entity).insert(pose);
+            }
+         }
+         continue;
+      }
+
+      warn!(
+         "camera {:?} picked up a non-finite pose or state, resetting it to its last good pose",
+         entity
+      );
+      if !pose_ok {
+         let last_good = last_good.map_or(
+            LastGoodPose {
+               parent: Transform::identity(),
+               child: Transform::identity(),
+            },
+            |last_good| *last_good,
+         );
+         *transform_parent = last_good.parent;
+         *transform_child = last_good.child;
+      }
+      reset_state(&mut cam, own_params.unwrap_or(&cam_params));
+      // Start the interpolation over from the restored pose
+      if kinematics.is_some() {
+         commands.entity(entity).remove::<CameraKinematics>();
+      }
+   }
+}
+
+fn is_finite(transform: &Transform) -> bool {
+   transform.translation.is_finite()
+      && transform.rotation.is_finite()
+      && transform.scale.is_finite()
+}
+
+fn state_is_finite(cam: &MovableCamera) -> bool {
+   [
+      cam.speed,
+      cam.angular_speed,
+      cam.idle_time,
+      cam.slow_blend,
+      cam.speed_ramp,
+      cam.pivot_transition,
+      cam.boom_length,
+      cam.shoulder_blend,
+      cam.throttle,
+      cam.drone_pitch,
+   ]
+   .iter()
+   .all(|value| value.is_finite())
+      && [
+         cam.cursor_pos,
+         cam.orbit_velocity,
+         cam.path_look,
+         cam.drone_tilt,
+         cam.mouse_filter.smoothed,
+      ]
+      .iter()
+      .all(|value| value.is_finite())
+      && [
+         Some(cam.pivot_transition_start),
+         Some(cam.chase_velocity),
+         Some(cam.map_pan_velocity),
+         Some(cam.drone_velocity),
+         cam.reference_point,
+         cam.pending_focus,
+         cam.follow_anchor,
+      ]
+      .iter()
+      .flatten()
+      .all(|value| value.is_finite())
+      && cam
+         .precise_translation
+         .map_or(true, |precise| precise.is_finite())
+}
+
+/// Stops all motion of `cam` and puts anything that isn't finite back to
+/// its default.
+fn reset_state(cam: &mut MovableCamera, cam_params: &MovableCameraParams) {
+   let default = MovableCamera::default();
+   if !cam.speed.is_finite() || !cam.angular_speed.is_finite() {
+      cam.speed = cam_params.default_speed;
+      cam.angular_speed = cam_params.default_speed;
+   }
+   if !cam.cursor_pos.is_finite() {
+      cam.cursor_pos = Vec2::ZERO;
+   }
+   if !cam.boom_length.is_finite() {
+      cam.boom_length = default.boom_length;
+   }
+   if !cam.shoulder_blend.is_finite() {
+      cam.shoulder_blend = cam.shoulder_side.sign();
+   }
+   for point in [
+      &mut cam.reference_point,
+      &mut cam.pending_focus,
+      &mut cam.follow_anchor,
+   ] {
+      if point.map_or(false, |point| !point.is_finite()) {
+         *point = None;
+      }
+   }
+   cam.orbit_memory
+      .retain(|_, (distance, rotation)| distance.is_finite() && rotation.is_finite());
+   cam.idle_time = 0.0;
+   cam.slow_blend = default.slow_blend;
+   cam.speed_ramp = default.speed_ramp;
+   cam.mouse_filter = MouseFilter::default();
+   cam.orbit_velocity = Vec2::ZERO;
+   cam.pivot_transition_start = Vec3::ZERO;
+   cam.pivot_transition = default.pivot_transition;
+   cam.chase_velocity = Vec3::ZERO;
+   cam.path_look = Vec2::ZERO;
+   cam.map_pan_velocity = Vec3::ZERO;
+   cam.throttle = 0.0;
+   cam.drone_velocity = Vec3::ZERO;
+   cam.drone_tilt = Vec2::ZERO;
+   cam.drone_pitch = 0.0;
+   cam.precise_translation = None;
+}
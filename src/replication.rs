@@ -0,0 +1,180 @@
+//! Compact camera state messages for mirroring cameras over the network.
+//! The messages are plain serde types, so any netcode layer can carry them.
+
+use crate::{CameraModeKind, MovableCamera};
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use std::ops::Mul;
+
+/// Scale between a unit quaternion component and its quantized form.
+const ROTATION_SCALE: f32 = i16::MAX as f32;
+
+/// Identifies a camera across the network. A camera with `remote` unset
+/// sends its state; one with `remote` set mirrors the state received for
+/// its `id`, and should be a plain camera without a [`MovableCamera`] rig.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ReplicatedCamera {
+   pub id: u32,
+   pub remote: bool,
+   /// Mode the remote camera was last reported in.
+   pub remote_mode: Option<CameraModeKind>,
+}
+
+/// What changed about a camera since its last message. Unchanged fields are
+/// left out.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CameraStateDelta {
+   pub id: u32,
+   pub position: Option<[f32; 3]>,
+   /// Rotation quaternion with each component scaled to `i16`.
+   pub rotation: Option<[i16; 4]>,
+   pub mode: Option<CameraModeKind>,
+}
+
+impl CameraStateDelta {
+   pub fn quantize_rotation(rotation: Quat) -> [i16; 4] {
+      let components = rotation.normalize().to_array();
+      components.map(|component| component.mul(ROTATION_SCALE).round() as i16)
+   }
+
+   pub fn dequantize_rotation(rotation: [i16; 4]) -> Quat {
+      Quat::from_array(rotation.map(|component| component as f32 / ROTATION_SCALE)).normalize()
+   }
+}
+
+/// Sent with the state of local [`ReplicatedCamera`]s whenever it changes,
+/// for the app to pass on to the network.
+#[derive(Clone, Copy, Debug)]
+pub struct OutgoingCameraState(pub CameraStateDelta);
+
+/// Send one of these for each camera state message that arrives from the
+/// network.
+#[derive(Clone, Copy, Debug)]
+pub struct IncomingCameraState(pub CameraStateDelta);
+
+/// Thresholds under which movement isn't worth a message.
+#[derive(Clone, Copy, Debug)]
+pub struct ReplicationSettings {
+   /// Smallest change in position, in world units.
+   pub position_threshold: f32,
+   /// Smallest change in rotation, in radians.
+   pub rotation_threshold: f32,
+}
+
+impl Default for ReplicationSettings {
+   fn default() -> Self {
+      Self {
+         position_threshold: 0.01,
+         rotation_threshold: 0.002,
+      }
+   }
+}
+
+/// State last sent for a camera.
+#[derive(Clone, Copy, Debug)]
+struct SentState {
+   position: Vec3,
+   rotation: Quat,
+   mode: CameraModeKind,
+}
+
+/// Sends [`OutgoingCameraState`] for local replicated cameras.
+pub fn send_camera_state(
+   settings: Res<ReplicationSettings>,
+   mut sent: Local<HashMap<u32, SentState>>,
+   mut outgoing: EventWriter<OutgoingCameraState>,
+   q_child: Query<(&ReplicatedCamera, &Parent, &Transform, &MovableCamera)>,
+   q_parent: Query<&Transform, Without<MovableCamera>>,
+) {
+   for (replicated, parent, transform_child, cam) in q_child.iter() {
+      if replicated.remote {
+         continue;
+      }
+      let transform_parent = match q_parent.get(parent.0) {
+         Ok(transform_parent) => transform_parent,
+         Err(_) => continue,
+      };
+      let eye = transform_parent.mul_transform(*transform_child);
+      let mode = cam.mode().kind();
+      let last = sent.get(&replicated.id).copied();
+
+      let mut delta = CameraStateDelta {
+         id: replicated.id,
+         ..Default::default()
+      };
+      if last.map_or(true, |last| {
+         last.position.distance(eye.translation) >= settings.position_threshold
+      }) {
+         delta.position = Some(eye.translation.to_array());
+      }
+      if last.map_or(true, |last| {
+         last.rotation.angle_between(eye.rotation) >= settings.rotation_threshold
+      }) {
+         delta.rotation = Some(CameraStateDelta::quantize_rotation(eye.rotation));
+      }
+      if last.map_or(true, |last| last.mode != mode) {
+         delta.mode = Some(mode);
+      }
+      if delta.position.is_none() && delta.rotation.is_none() && delta.mode.is_none() {
+         continue;
+      }
+
+      // Only what was sent counts as sent, so slow drifts still go out
+      let mut state = last.unwrap_or(SentState {
+         position: eye.translation,
+         rotation: eye.rotation,
+         mode,
+      });
+      if delta.position.is_some() {
+         state.position = eye.translation;
+      }
+      if delta.rotation.is_some() {
+         state.rotation = eye.rotation;
+      }
+      state.mode = mode;
+      sent.insert(replicated.id, state);
+      outgoing.send(OutgoingCameraState(delta));
+   }
+}
+
+/// Moves remote [`ReplicatedCamera`]s to the state in [`IncomingCameraState`]
+/// messages.
+pub fn apply_remote_camera_state(
+   mut incoming: EventReader<IncomingCameraState>,
+   mut latest: Local<HashMap<u32, CameraStateDelta>>,
+   mut q_remote: Query<(&mut ReplicatedCamera, &mut Transform)>,
+) {
+   // Kept across frames so its capacity is reused
+   latest.clear();
+   for IncomingCameraState(delta) in incoming.iter() {
+      let merged = latest.entry(delta.id).or_insert(CameraStateDelta {
+         id: delta.id,
+         ..Default::default()
+      });
+      merged.position = delta.position.or(merged.position);
+      merged.rotation = delta.rotation.or(merged.rotation);
+      merged.mode = delta.mode.or(merged.mode);
+   }
+   if latest.is_empty() {
+      return;
+   }
+
+   for (mut replicated, mut transform) in q_remote.iter_mut() {
+      if !replicated.remote {
+         continue;
+      }
+      let delta = match latest.get(&replicated.id) {
+         Some(delta) => delta,
+         None => continue,
+      };
+      if let Some(position) = delta.position {
+         transform.translation = Vec3::from(position);
+      }
+      if let Some(rotation) = delta.rotation {
+         transform.rotation = CameraStateDelta::dequantize_rotation(rotation);
+      }
+      if let Some(mode) = delta.mode {
+         replicated.remote_mode = Some(mode);
+      }
+   }
+}
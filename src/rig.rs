@@ -0,0 +1,188 @@
+//! Camera behaviors assembled from small drivers, for cameras that don't fit
+//! any [`CameraMode`](crate::CameraMode).
+//!
+//! A [`CameraRig`] runs its drivers in order each frame, every driver taking
+//! the transform produced by the ones before it. For example
+//! `Position -> YawPitch -> Arm -> Smooth` is a smoothed orbit camera and
+//! `Position -> LookAt` a tracking shot. The drivers stay in this module so
+//! their names don't clash with the crate's events.
+
+use bevy::prelude::*;
+use std::{
+   any::Any,
+   f32::consts::FRAC_PI_2,
+   ops::{Div, Mul, Neg},
+};
+
+/// One step of a [`CameraRig`].
+pub trait RigDriver: AsAnyMut + Send + Sync + 'static {
+   /// Transforms the output of the previous driver.
+   fn update(&mut self, transform: Transform, dt: f32) -> Transform;
+}
+
+/// Lets [`CameraRig::driver_mut`] find a driver by type.
+pub trait AsAnyMut {
+   fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAnyMut for T {
+   fn as_any_mut(&mut self) -> &mut dyn Any {
+      self
+   }
+}
+
+/// Places the rig at `position`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+   pub position: Vec3,
+}
+
+impl RigDriver for Position {
+   fn update(&mut self, transform: Transform, _dt: f32) -> Transform {
+      Transform {
+         translation: self.position,
+         ..transform
+      }
+   }
+}
+
+/// Turns the rig to `yaw` around the global Y axis and then `pitch` around
+/// its local X axis, in radians.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct YawPitch {
+   pub yaw: f32,
+   pub pitch: f32,
+}
+
+impl YawPitch {
+   /// Adds to the angles, keeping the pitch short of straight up or down.
+   pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+      self.yaw += yaw;
+      self.pitch = (self.pitch + pitch).clamp(FRAC_PI_2.neg() + 0.01, FRAC_PI_2 - 0.01);
+   }
+}
+
+impl RigDriver for YawPitch {
+   fn update(&mut self, transform: Transform, _dt: f32) -> Transform {
+      Transform {
+         rotation: Quat::from_rotation_y(self.yaw).mul(Quat::from_rotation_x(self.pitch)),
+         ..transform
+      }
+   }
+}
+
+/// Moves the rig by `offset` in its own space, like a boom holding the camera
+/// away from its pivot.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Arm {
+   pub offset: Vec3,
+}
+
+impl RigDriver for Arm {
+   fn update(&mut self, transform: Transform, _dt: f32) -> Transform {
+      Transform {
+         translation: transform.translation + transform.rotation.mul_vec3(self.offset),
+         ..transform
+      }
+   }
+}
+
+/// Turns the rig to face `target`, keeping global Y up.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LookAt {
+   pub target: Vec3,
+}
+
+impl RigDriver for LookAt {
+   fn update(&mut self, transform: Transform, _dt: f32) -> Transform {
+      let to_target = self.target - transform.translation;
+      // A degenerate direction leaves the orientation alone
+      if to_target.cross(Vec3::Y).length_squared() <= f32::EPSILON {
+         return transform;
+      }
+      transform.looking_at(self.target, Vec3::Y)
+   }
+}
+
+/// Eases toward the output of the previous drivers. Each time constant is the
+/// seconds taken to cover about two thirds of the remaining distance; zero
+/// follows exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Smooth {
+   pub position_time: f32,
+   pub rotation_time: f32,
+   last: Option<Transform>,
+}
+
+impl Smooth {
+   pub fn new(position_time: f32, rotation_time: f32) -> Self {
+      Self {
+         position_time,
+         rotation_time,
+         last: None,
+      }
+   }
+}
+
+impl RigDriver for Smooth {
+   fn update(&mut self, transform: Transform, dt: f32) -> Transform {
+      let blend = |time: f32| {
+         if time > 0.0 {
+            1.0 - dt.div(time).neg().exp()
+         } else {
+            1.0
+         }
+      };
+      let smoothed = match self.last {
+         Some(last) => Transform {
+            translation: last
+               .translation
+               .lerp(transform.translation, blend(self.position_time)),
+            rotation: last
+               .rotation
+               .slerp(transform.rotation, blend(self.rotation_time)),
+            ..transform
+         },
+         None => transform,
+      };
+      self.last = Some(smoothed);
+      smoothed
+   }
+}
+
+/// A camera driven by a chain of [`RigDriver`]s instead of a camera mode.
+/// Don't combine it with [`MovableCamera`](crate::MovableCamera).
+#[derive(Component, Default)]
+pub struct CameraRig {
+   pub drivers: Vec<Box<dyn RigDriver>>,
+}
+
+impl CameraRig {
+   pub fn with(mut self, driver: impl RigDriver) -> Self {
+      self.drivers.push(Box::new(driver));
+      self
+   }
+
+   /// The first driver of type `T`, to steer the rig from game code.
+   pub fn driver_mut<T: RigDriver>(&mut self) -> Option<&mut T> {
+      self
+         .drivers
+         .iter_mut()
+         .find_map(|driver| driver.as_any_mut().downcast_mut::<T>())
+   }
+
+   /// Runs the drivers in order, starting from `transform`.
+   pub fn update(&mut self, transform: Transform, dt: f32) -> Transform {
+      self
+         .drivers
+         .iter_mut()
+         .fold(transform, |transform, driver| driver.update(transform, dt))
+   }
+}
+
+/// Moves every [`CameraRig`] camera to the output of its drivers.
+pub fn update_camera_rigs(time: Res<Time>, mut q_rigs: Query<(&mut CameraRig, &mut Transform)>) {
+   for (mut rig, mut transform) in q_rigs.iter_mut() {
+      *transform = rig.update(*transform, time.delta_seconds());
+   }
+}
@@ -0,0 +1,175 @@
+//! Simple cinematics as a queue of camera steps run one after another.
+
+use crate::{
+   look_at_rig, move_rig, rig_root, CameraMode, CameraPath, MovableCamera, MovableCameraParams,
+   PathFinished, TimeSource, VirtualTimeScale,
+};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// One step of a [`CameraScript`].
+#[derive(Clone, Debug)]
+pub enum ScriptStep {
+   /// Fly to a world-space pose over `duration` seconds.
+   MoveTo { transform: Transform, duration: f32 },
+   /// Hold still for this many seconds.
+   Wait(f32),
+   /// Turn to face `point` over `duration` seconds without moving.
+   LookAt { point: Vec3, duration: f32 },
+   /// Play a path from its start until it finishes. A looping path never
+   /// finishes, so the script stays on it.
+   FollowPath(CameraPath),
+}
+
+/// Queue of [`ScriptStep`]s a camera plays in order, sending
+/// [`ScriptStepFinished`] after each and [`ScriptFinished`] once the queue
+/// runs out. Steps can be pushed while it plays.
+///
+/// ```ignore
+/// let mut script = CameraScript::default();
+/// script
+///    .push(ScriptStep::MoveTo { transform, duration: 2.0 })
+///    .push(ScriptStep::Wait(1.0))
+///    .push(ScriptStep::LookAt { point, duration: 0.5 });
+/// commands.entity(camera).insert(script);
+/// ```
+#[derive(Component, Clone, Debug, Default)]
+pub struct CameraScript {
+   steps: VecDeque<ScriptStep>,
+   current: Option<(ScriptStep, f32)>,
+   finished_steps: usize,
+}
+
+impl CameraScript {
+   pub fn push(&mut self, step: ScriptStep) -> &mut Self {
+      self.steps.push_back(step);
+      self
+   }
+
+   /// Drops the steps that haven't started yet.
+   pub fn clear(&mut self) {
+      self.steps.clear();
+   }
+
+   pub fn is_finished(&self) -> bool {
+      self.current.is_none() && self.steps.is_empty()
+   }
+}
+
+/// Sent when a camera finishes step `index`, counted from zero, of its
+/// [`CameraScript`].
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptStepFinished {
+   pub camera: Entity,
+   pub index: usize,
+}
+
+/// Sent when a camera runs out of [`CameraScript`] steps. The script
+/// component is removed.
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptFinished {
+   pub camera: Entity,
+}
+
+/// Plays [`CameraScript`]s.
+pub fn run_camera_scripts(
+   mut commands: Commands,
+   time: Res<Time>,
+   time_scale: Res<VirtualTimeScale>,
+   cam_params: Res<MovableCameraParams>,
+   mut paths_finished: EventReader<PathFinished>,
+   mut paths_done: Local<Vec<Entity>>,
+   mut step_finished: EventWriter<ScriptStepFinished>,
+   mut script_finished: EventWriter<ScriptFinished>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &mut CameraScript,
+      Option<&TimeSource>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   paths_done.clear();
+   paths_done.extend(paths_finished.iter().map(|event| event.camera));
+
+   for (entity, parent, mut transform_child, mut cam, mut script, source) in q_child.iter_mut() {
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+
+      // Finish the current step
+      if let Some((step, elapsed)) = script.current.as_mut() {
+         *elapsed += time.delta_seconds() * time_scale.for_source(source);
+         let done = match step {
+            ScriptStep::MoveTo { duration, .. }
+            | ScriptStep::LookAt { duration, .. }
+            | ScriptStep::Wait(duration) => *elapsed >= *duration,
+            // An empty path has nothing to play and never sends PathFinished
+            ScriptStep::FollowPath(path) => {
+               paths_done.contains(&entity) || (!path.looping && path.end() <= path.start())
+            }
+         };
+         if !done {
+            continue;
+         }
+         script.current = None;
+         step_finished.send(ScriptStepFinished {
+            camera: entity,
+            index: script.finished_steps,
+         });
+         script.finished_steps += 1;
+      }
+
+      // Start the next one
+      let step = match script.steps.pop_front() {
+         Some(step) => step,
+         None => {
+            commands.entity(entity).remove::<CameraScript>();
+            script_finished.send(ScriptFinished { camera: entity });
+            continue;
+         }
+      };
+      match &step {
+         ScriptStep::MoveTo {
+            transform,
+            duration,
+         } => {
+            move_rig(
+               &mut commands,
+               entity,
+               *duration,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&Transform::default(), transform),
+            );
+            cam.enter_laid_out_mode(CameraMode::Free);
+            cam.orbit_velocity = Vec2::ZERO;
+         }
+         ScriptStep::LookAt { point, duration } => {
+            let (root, child) = space.to_world(&transform_parent, &transform_child);
+            let rig = look_at_rig(&root, &child, *point, cam_params.pitch_limits);
+            move_rig(
+               &mut commands,
+               entity,
+               *duration,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&rig.0, &rig.1),
+            );
+            cam.enter_laid_out_mode(CameraMode::Free);
+            cam.orbit_velocity = Vec2::ZERO;
+         }
+         ScriptStep::Wait(_) => {}
+         ScriptStep::FollowPath(path) => {
+            commands.entity(entity).insert(path.clone());
+            // Entering the mode, even again, restarts playback from the start
+            cam.enter_mode(CameraMode::Path);
+         }
+      }
+      script.current = Some((step, 0.0));
+   }
+}
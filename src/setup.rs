@@ -0,0 +1,145 @@
+//! Builds and takes down camera rigs as [`MovableCamera`] is added to and
+//! removed from cameras, so any camera can be promoted to a flying camera and
+//! back while the app runs, and cleans up after cameras that go away.
+
+use crate::{CameraStack, FloatingOrigin, MovableCamera, MovableCameraInput, PivotGizmo};
+use bevy::{prelude::*, utils::HashMap, window::WindowId};
+
+/// Root of a rig built by [`setup_movable_cameras`] for `camera`, between the
+/// camera and the parent it had, if any. It goes away again when the camera
+/// stops being movable.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MovableCameraRoot {
+   pub camera: Entity,
+}
+
+/// Gives cameras that were just made movable what the camera systems expect.
+/// Each camera is put under a new [`MovableCameraRoot`], keeping its pose. A
+/// camera that already has a parent keeps it too, with the root in between,
+/// so the rig moves with whatever the camera was attached to.
+pub fn setup_movable_cameras(
+   mut commands: Commands,
+   q_added: Query<(Entity, Option<&Parent>, Option<&MovableCameraInput>), Added<MovableCamera>>,
+) {
+   for (entity, parent, input) in q_added.iter() {
+      if input.is_none() {
+         commands
+            .entity(entity)
+            .insert(MovableCameraInput::default());
+      }
+      if let Some(parent) = parent {
+         commands.entity(parent.0).remove_children(&[entity]);
+      }
+      let root = commands
+         .spawn_bundle((Transform::identity(), GlobalTransform::identity()))
+         .insert(MovableCameraRoot { camera: entity })
+         .push_children(&[entity])
+         .id();
+      if let Some(parent) = parent {
+         commands.entity(parent.0).push_children(&[root]);
+      }
+   }
+}
+
+/// Takes down what [`setup_movable_cameras`] built for cameras that are no
+/// longer movable. A camera that is still around keeps its pose and goes back
+/// to the parent it had before its [`MovableCameraRoot`], if any; the root of
+/// a despawned camera is despawned with it, along with anything else under
+/// the root.
+pub fn teardown_movable_cameras(
+   mut commands: Commands,
+   removed: RemovedComponents<MovableCamera>,
+   q_roots: Query<(Entity, &MovableCameraRoot, &Transform, Option<&Parent>)>,
+   mut q_cams: Query<(&mut Transform, Option<&MovableCamera>), Without<MovableCameraRoot>>,
+) {
+   for entity in removed.iter() {
+      let camera = match q_cams.get_mut(entity) {
+         // Removed and added back within the frame
+         Ok((_, Some(_))) => continue,
+         Ok((transform, None)) => {
+            commands.entity(entity).remove::<MovableCameraInput>();
+            Some(transform)
+         }
+         Err(_) => None,
+      };
+      let (root, transform_root, original) =
+         match q_roots.iter().find(|(_, root, _, _)| root.camera == entity) {
+            Some((root, _, transform_root, original)) => (root, transform_root, original),
+            None => continue,
+         };
+      if let Some(mut transform) = camera {
+         *transform = transform_root.mul_transform(*transform);
+         commands.entity(root).remove_children(&[entity]);
+         if let Some(original) = original {
+            commands.entity(original.0).push_children(&[entity]);
+         }
+      }
+      commands.entity(root).despawn_recursive();
+   }
+}
+
+/// Cameras that have the cursor locked or captured, and in which window, kept
+/// by [`lock_cursor`](crate::lock_cursor).
+#[derive(Default)]
+pub(crate) struct CursorHolders(pub(crate) HashMap<Entity, WindowId>);
+
+/// Cleans up after cameras that were despawned or stopped being movable: the
+/// cursor they had locked or captured is freed and shown again, their
+/// [`PivotGizmo`] is despawned, and they are dropped as the [`CameraStack`]
+/// base and the camera the [`FloatingOrigin`] follows.
+pub fn release_removed_cameras(
+   mut commands: Commands,
+   removed: RemovedComponents<MovableCamera>,
+   mut holders: ResMut<CursorHolders>,
+   mut windows: ResMut<Windows>,
+   mut stack: ResMut<CameraStack>,
+   mut origin: Option<ResMut<FloatingOrigin>>,
+   #[cfg(target_arch = "wasm32")] web_lock: Res<crate::WebPointerLock>,
+   q_cams: Query<(), With<MovableCamera>>,
+   q_gizmos: Query<(Entity, &PivotGizmo, Option<&Parent>)>,
+   q_roots: Query<&MovableCameraRoot>,
+) {
+   let owns_cursor = !cfg!(target_arch = "wasm32");
+   for entity in removed.iter() {
+      // Removed and added back within the frame
+      if q_cams.get(entity).is_ok() {
+         continue;
+      }
+      if stack.base == Some(entity) {
+         stack.base = None;
+      }
+      if let Some(origin) = origin.as_mut() {
+         if origin.camera == Some(entity) {
+            origin.camera = None;
+         }
+      }
+      for (gizmo, _, parent) in q_gizmos
+         .iter()
+         .filter(|(_, gizmo, _)| gizmo.camera == entity)
+      {
+         // A rig built for the camera is despawned with everything under it
+         let built = parent
+            .and_then(|parent| q_roots.get(parent.0).ok())
+            .map_or(false, |root| root.camera == entity);
+         if !built {
+            commands.entity(gizmo).despawn_recursive();
+         }
+      }
+      let window_id = match holders.0.remove(&entity) {
+         Some(window_id) => window_id,
+         None => continue,
+      };
+      // Another camera in the same window may still want it
+      if holders.0.values().any(|held| *held == window_id) {
+         continue;
+      }
+      #[cfg(target_arch = "wasm32")]
+      web_lock.release_capture();
+      if owns_cursor {
+         if let Some(window) = windows.get_mut(window_id) {
+            window.set_cursor_lock_mode(false);
+            window.set_cursor_visibility(true);
+         }
+      }
+   }
+}
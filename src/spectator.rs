@@ -0,0 +1,237 @@
+//! A spectator camera that takes over from the gameplay camera and hands the
+//! view back, for shipped games.
+
+use crate::{
+   CameraActions, CameraDirector, CameraMode, CutTo, FlyingCamAction, MovableCamera,
+   MovableCameraParams,
+};
+use bevy::{prelude::*, render::camera::ActiveCameras};
+
+/// Marks the flying camera used for spectating. Spawn it with
+/// [`CameraMode::Disabled`] so it ignores input until `Spectate` is pressed.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Spectator {
+   /// Gameplay camera and its mode while spectating.
+   spectating: Option<(Entity, Option<CameraMode>)>,
+   /// Flight back to the gameplay camera, once spectating ends.
+   returning: Option<SpectatorReturn>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SpectatorReturn {
+   /// World-space spectator pose when the return started.
+   from: Transform,
+   duration: f32,
+   elapsed: f32,
+}
+
+impl Spectator {
+   /// The gameplay camera the view returns to, while spectating or flying
+   /// back.
+   pub fn gameplay_camera(&self) -> Option<Entity> {
+      self.spectating.map(|(gameplay, _)| gameplay)
+   }
+
+   /// Whether the spectator is flying back to the gameplay camera.
+   pub fn is_returning(&self) -> bool {
+      self.returning.is_some()
+   }
+}
+
+/// Event that toggles spectating, like pressing `Spectate`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ToggleSpectator;
+
+/// Sent when the spectator takes over from `gameplay`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpectatorEntered {
+   pub spectator: Entity,
+   pub gameplay: Entity,
+}
+
+/// Sent when the view starts returning to `gameplay`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpectatorExited {
+   pub spectator: Entity,
+   pub gameplay: Entity,
+}
+
+/// Sent when the spectator has flown back and the view is on `gameplay`
+/// again, with its mode restored.
+#[derive(Clone, Copy, Debug)]
+pub struct SpectatorHandoff {
+   pub spectator: Entity,
+   pub gameplay: Entity,
+}
+
+/// Toggles [`Spectator`] cameras. Entering cuts to the spectator at the active
+/// camera's pose and disables that camera if it's a flying camera; leaving
+/// restores its mode and flies the spectator back to its current pose per
+/// `spectator_return`, then cuts to it. Pressing
+/// `Spectate` on the way back carries on spectating from there.
+pub fn spectator_camera(
+   time: Res<Time>,
+   cam_params: Res<MovableCameraParams>,
+   director: Res<CameraDirector>,
+   active_cameras: Option<Res<ActiveCameras>>,
+   actions: CameraActions,
+   mut toggles: EventReader<ToggleSpectator>,
+   mut cuts: EventWriter<CutTo>,
+   mut entered: EventWriter<SpectatorEntered>,
+   mut exited: EventWriter<SpectatorExited>,
+   mut handoffs: EventWriter<SpectatorHandoff>,
+   q_global: Query<&GlobalTransform>,
+   mut q_spectators: Query<(Entity, &Parent, &mut Transform, &mut Spectator)>,
+   mut q_cams: Query<&mut MovableCamera>,
+   mut q_parent: Query<&mut Transform, Without<Spectator>>,
+) {
+   let toggled = toggles.iter().count() % 2 == 1;
+   let active = active_cameras
+      .as_ref()
+      .and_then(|active_cameras| active_cameras.get(&director.slot))
+      .and_then(|active| active.entity);
+
+   for (entity, parent, mut transform_child, mut spectator) in q_spectators.iter_mut() {
+      let pressed = actions.get(entity).map_or(false, |action_state| {
+         action_state.just_pressed(FlyingCamAction::Spectate)
+      });
+      if pressed == toggled {
+         if let Some(mut flight) = spectator.returning {
+            let gameplay = match spectator.spectating {
+               Some((gameplay, _)) => gameplay,
+               None => continue,
+            };
+            let to = match q_global.get(gameplay) {
+               Ok(global) => Transform {
+                  translation: global.translation,
+                  rotation: global.rotation,
+                  ..Default::default()
+               },
+               // The gameplay camera was despawned meanwhile
+               Err(_) => {
+                  spectator.spectating = None;
+                  spectator.returning = None;
+                  continue;
+               }
+            };
+            flight.elapsed += time.delta_seconds();
+            if flight.elapsed < flight.duration {
+               let t = cam_params
+                  .spectator_return_easing
+                  .apply(flight.elapsed / flight.duration);
+               *transform_child = Transform {
+                  translation: flight.from.translation.lerp(to.translation, t),
+                  rotation: flight.from.rotation.slerp(to.rotation, t),
+                  ..Default::default()
+               };
+               spectator.returning = Some(flight);
+               continue;
+            }
+            *transform_child = to;
+            spectator.spectating = None;
+            spectator.returning = None;
+            cuts.send(CutTo(gameplay));
+            handoffs.send(SpectatorHandoff {
+               spectator: entity,
+               gameplay,
+            });
+         }
+         continue;
+      }
+
+      // Turning back mid-flight resumes spectating where the camera is
+      if spectator.returning.take().is_some() {
+         if let Some((gameplay, _)) = spectator.spectating {
+            if let Ok(mut cam) = q_cams.get_mut(entity) {
+               cam.enter_laid_out_mode(CameraMode::Free);
+               cam.orbit_velocity = Vec2::ZERO;
+            }
+            let gameplay_mode = q_cams.get_mut(gameplay).ok().map(|mut cam| {
+               let mode = cam.mode();
+               cam.enter_mode(CameraMode::Disabled);
+               mode
+            });
+            spectator.spectating = Some((gameplay, gameplay_mode));
+            entered.send(SpectatorEntered {
+               spectator: entity,
+               gameplay,
+            });
+         }
+         continue;
+      }
+
+      match spectator.spectating {
+         None => {
+            let gameplay = match active.filter(|active| *active != entity) {
+               Some(gameplay) => gameplay,
+               None => continue,
+            };
+            let (global, mut transform_parent) =
+               match (q_global.get(gameplay), q_parent.get_mut(parent.0)) {
+                  (Ok(global), Ok(transform_parent)) => (global, transform_parent),
+                  _ => continue,
+               };
+            *transform_parent = Transform::default();
+            *transform_child = Transform {
+               translation: global.translation,
+               rotation: global.rotation,
+               ..Default::default()
+            };
+            if let Ok(mut cam) = q_cams.get_mut(entity) {
+               cam.enter_laid_out_mode(CameraMode::Free);
+               cam.orbit_velocity = Vec2::ZERO;
+            }
+            let gameplay_mode = q_cams.get_mut(gameplay).ok().map(|mut cam| {
+               let mode = cam.mode();
+               cam.enter_mode(CameraMode::Disabled);
+               mode
+            });
+            spectator.spectating = Some((gameplay, gameplay_mode));
+            cuts.send(CutTo(entity));
+            entered.send(SpectatorEntered {
+               spectator: entity,
+               gameplay,
+            });
+         }
+         Some((gameplay, gameplay_mode)) => {
+            if let Ok(mut cam) = q_cams.get_mut(entity) {
+               cam.enter_mode(CameraMode::Disabled);
+            }
+            // The gameplay camera may have been despawned meanwhile
+            let target = match q_global.get(gameplay) {
+               Ok(global) => global.translation,
+               Err(_) => {
+                  spectator.spectating = None;
+                  continue;
+               }
+            };
+            // Fly back on a free rig so the spectator's pose is its world pose
+            let from = match q_parent.get_mut(parent.0) {
+               Ok(mut transform_parent) => {
+                  let from = transform_parent.mul_transform(*transform_child);
+                  *transform_parent = Transform::default();
+                  *transform_child = from;
+                  from
+               }
+               Err(_) => continue,
+            };
+            // Let the gameplay camera catch up with its target while the
+            // spectator flies towards it
+            if let (Some(mode), Ok(mut cam)) = (gameplay_mode, q_cams.get_mut(gameplay)) {
+               cam.enter_laid_out_mode(mode);
+            }
+            spectator.returning = Some(SpectatorReturn {
+               from,
+               duration: cam_params
+                  .spectator_return
+                  .seconds(from.translation.distance(target)),
+               elapsed: 0.0,
+            });
+            exited.send(SpectatorExited {
+               spectator: entity,
+               gameplay,
+            });
+         }
+      }
+   }
+}
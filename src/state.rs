@@ -0,0 +1,97 @@
+//! Free-flying camera logic with no ECS attached, for headless tests,
+//! server-side replays and benchmarks.
+
+use crate::{movable::fly_free, step_speed, CameraBounds, MovableCameraParams};
+use bevy::{math::DVec3, prelude::*};
+
+/// One frame of input to a [`FlyingCamState`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FlyingCamFrameInput {
+   /// Movement as (left, up, forward), each from -1 to 1.
+   pub movement: Vec3,
+   /// Mouse motion to turn by, in pixels.
+   pub look: Vec2,
+   /// Lines scrolled, zooming along the view.
+   pub scroll: f32,
+   /// Whether `AdjustSpeed` was pressed, toggling slow mode.
+   pub toggle_slow: bool,
+}
+
+/// A free-flying camera and its speed state, stepped by hand with
+/// [`FlyingCamState::tick`]. Given the same inputs and time steps it always
+/// ends up in the same place, and it moves just like a free
+/// [`MovableCamera`](crate::MovableCamera) without mouse smoothing.
+#[derive(Clone)]
+pub struct FlyingCamState {
+   pub params: MovableCameraParams,
+   /// Camera pose in world space.
+   pub transform: Transform,
+   /// Size in pixels of the view that `look` is measured against.
+   pub window_size: Vec2,
+   pub bounds: Option<CameraBounds>,
+   /// Position in `f64`, with `precise_translation` on.
+   precise: Option<DVec3>,
+   slow: bool,
+   slow_blend: f32,
+   speed_ramp: f32,
+   speed: f32,
+}
+
+impl FlyingCamState {
+   pub fn new(params: MovableCameraParams, transform: Transform) -> Self {
+      Self {
+         speed: params.default_speed,
+         params,
+         transform,
+         window_size: Vec2::new(1280.0, 720.0),
+         bounds: None,
+         precise: None,
+         slow: false,
+         slow_blend: 0.0,
+         speed_ramp: 0.0,
+      }
+   }
+
+   /// Current movement speed, in units per second.
+   pub fn speed(&self) -> f32 {
+      self.speed
+   }
+
+   pub fn is_slow(&self) -> bool {
+      self.slow
+   }
+
+   /// Advances the camera by `dt` seconds of `input` and returns its new pose.
+   pub fn tick(&mut self, input: FlyingCamFrameInput, dt: f32) -> Transform {
+      if input.toggle_slow {
+         self.slow = !self.slow;
+      }
+      let (speed, angular_speed) = step_speed(
+         &self.params,
+         dt,
+         input.movement != Vec3::ZERO,
+         self.slow,
+         (&mut self.slow_blend, &mut self.speed_ramp),
+      );
+      self.speed = speed;
+      let transform = self.transform;
+      self.precise = self.params.precise_translation.then(|| {
+         self
+            .precise
+            .filter(|precise| precise.as_vec3() == transform.translation)
+            .unwrap_or_else(|| transform.translation.as_dvec3())
+      });
+      self.transform = fly_free(
+         self.transform,
+         self.precise.as_mut(),
+         &self.params,
+         (input.look, input.scroll, input.movement),
+         Some(self.window_size),
+         (speed, angular_speed),
+         None,
+         self.bounds.as_ref(),
+         dt,
+      );
+      self.transform
+   }
+}
@@ -0,0 +1,183 @@
+//! A headless app with simulated input, for integration tests of camera
+//! behavior. Enable the `test_utils` feature to use it.
+//!
+//! ```ignore
+//! let mut test = CameraTestApp::new();
+//! let camera = test.spawn_camera(Transform::default());
+//! test.press(camera, FlyingCamAction::Forward);
+//! test.advance(60);
+//! test.assert_camera_at(camera, Vec3::new(0.0, 0.0, -1.0), 0.5);
+//! ```
+
+use crate::{CameraTimestep, FlyingCamAction, FlyingCamPlugin, MovableCamera};
+use bevy::{
+   asset::AssetPlugin,
+   input::{
+      mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+      InputPlugin,
+   },
+   prelude::*,
+   transform::TransformPlugin,
+   window::{WindowId, WindowPlugin},
+};
+use leafwing_input_manager::prelude::ActionState;
+
+/// Seconds each frame of a [`CameraTestApp`] advances the camera systems by.
+pub const TEST_TIMESTEP: f32 = 1.0 / 60.0;
+
+/// An [`App`] with [`FlyingCamPlugin`], a 1280 by 720 primary window and no
+/// rendering. The camera systems advance by [`TEST_TIMESTEP`] each frame
+/// however long the frame really took.
+pub struct CameraTestApp {
+   pub app: App,
+}
+
+impl Default for CameraTestApp {
+   fn default() -> Self {
+      Self::new()
+   }
+}
+
+impl CameraTestApp {
+   pub fn new() -> Self {
+      let mut app = App::new();
+      app.add_plugins(MinimalPlugins)
+         .add_plugin(TransformPlugin)
+         .add_plugin(InputPlugin)
+         .add_plugin(WindowPlugin {
+            add_primary_window: false,
+            ..Default::default()
+         })
+         .add_plugin(AssetPlugin)
+         .add_asset::<Mesh>()
+         .add_asset::<StandardMaterial>()
+         .insert_resource(CameraTimestep::manual(TEST_TIMESTEP))
+         .add_plugin(FlyingCamPlugin);
+      let mut window = Window::new(
+         WindowId::primary(),
+         &WindowDescriptor::default(),
+         1280,
+         720,
+         1.0,
+         None,
+      );
+      // The camera systems only read the mouse of a focused window
+      window.update_focused_status_from_backend(true);
+      app.world
+         .get_resource_mut::<Windows>()
+         .expect("WindowPlugin adds Windows")
+         .add(window);
+      Self { app }
+   }
+
+   /// Spawns a camera at the world-space pose `transform`, with input on the
+   /// camera, and returns the camera. Its rig is built on the next frame.
+   pub fn spawn_camera(&mut self, transform: Transform) -> Entity {
+      self
+         .app
+         .world
+         .spawn()
+         .insert_bundle(PerspectiveCameraBundle {
+            transform,
+            ..PerspectiveCameraBundle::new_3d()
+         })
+         .insert(MovableCamera::default())
+         .insert(ActionState::<FlyingCamAction>::default())
+         .id()
+   }
+
+   fn action_state(&mut self, camera: Entity) -> Mut<ActionState<FlyingCamAction>> {
+      self
+         .app
+         .world
+         .get_mut::<ActionState<FlyingCamAction>>(camera)
+         .expect("camera has no ActionState<FlyingCamAction>")
+   }
+
+   /// Holds `action` down on `camera` until [`release`](Self::release)d.
+   pub fn press(&mut self, camera: Entity, action: FlyingCamAction) {
+      self.action_state(camera).press(action);
+   }
+
+   pub fn release(&mut self, camera: Entity, action: FlyingCamAction) {
+      self.action_state(camera).release(action);
+   }
+
+   /// Moves the mouse by `delta` pixels during the next frame.
+   pub fn move_mouse(&mut self, delta: Vec2) {
+      self
+         .app
+         .world
+         .get_resource_mut::<Events<MouseMotion>>()
+         .expect("InputPlugin adds mouse events")
+         .send(MouseMotion { delta });
+   }
+
+   /// Scrolls by `lines` during the next frame.
+   pub fn scroll(&mut self, lines: f32) {
+      self
+         .app
+         .world
+         .get_resource_mut::<Events<MouseWheel>>()
+         .expect("InputPlugin adds mouse events")
+         .send(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.0,
+            y: lines,
+         });
+   }
+
+   /// Runs `frames` frames. Presses count as just pressed for the first of
+   /// them only.
+   pub fn advance(&mut self, frames: usize) {
+      for _ in 0..frames {
+         self.app.update();
+         let mut q_actions = self.app.world.query::<&mut ActionState<FlyingCamAction>>();
+         for mut action_state in q_actions.iter_mut(&mut self.app.world) {
+            action_state.tick();
+         }
+      }
+   }
+
+   /// World-space pose of `camera`, read from its rig.
+   pub fn camera_pose(&self, camera: Entity) -> Transform {
+      let world = &self.app.world;
+      let mut pose = *world
+         .get::<Transform>(camera)
+         .expect("camera has no Transform");
+      let mut entity = camera;
+      while let Some(parent) = world.get::<Parent>(entity) {
+         entity = parent.0;
+         if let Some(transform) = world.get::<Transform>(entity) {
+            pose = transform.mul_transform(pose);
+         }
+      }
+      pose
+   }
+
+   /// Panics unless `camera` is within `tolerance` of `translation`.
+   pub fn assert_camera_at(&self, camera: Entity, translation: Vec3, tolerance: f32) {
+      let actual = self.camera_pose(camera).translation;
+      assert!(
+         actual.distance(translation) <= tolerance,
+         "camera at {:?}, expected {:?} within {}",
+         actual,
+         translation,
+         tolerance,
+      );
+   }
+
+   /// Panics unless `camera` looks along `direction`, to within `tolerance`
+   /// radians.
+   pub fn assert_camera_facing(&self, camera: Entity, direction: Vec3, tolerance: f32) {
+      let forward = self.camera_pose(camera).forward();
+      let angle = forward.angle_between(direction);
+      assert!(
+         angle <= tolerance,
+         "camera facing {:?}, expected {:?} within {} radians",
+         forward,
+         direction,
+         tolerance,
+      );
+   }
+}
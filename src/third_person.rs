@@ -0,0 +1,180 @@
+//! [`CameraMode::ThirdPerson`]: orbit a character on an adjustable boom.
+
+use crate::{
+   limit_orbit, orbit_around, rig_root, rotate_cam_quat, target_pivot, window_has_mouse,
+   window_size, yaw_angle, CameraActions, CameraMode, CameraMouse, CameraTime, FlyingCamAction,
+   GamepadLook, MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::Mul;
+
+/// Settings of [`CameraMode::ThirdPerson`] cameras. The shoulder also applies
+/// to chase cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThirdPersonParams {
+   /// Shortest and longest boom.
+   pub boom_length_limits: (f32, f32),
+   /// Over-the-shoulder framing: how far the camera sits to the right of and
+   /// above the boom.
+   pub shoulder_offset: Vec2,
+   /// Seconds taken to move the camera over to the other shoulder.
+   pub shoulder_swap_time: f32,
+}
+
+impl Default for ThirdPersonParams {
+   fn default() -> Self {
+      Self {
+         boom_length_limits: (1.0, 20.0),
+         shoulder_offset: Vec2::ZERO,
+         shoulder_swap_time: 0.2,
+      }
+   }
+}
+
+/// How far the boom of a third-person camera can extend before hitting
+/// geometry, measured from the pivot. Fill it in from a raycast along the
+/// boom to keep the camera out of walls.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BoomObstruction(pub Option<f32>);
+
+/// Heading of a third-person camera, kept up to date on the camera entity so
+/// character controllers can map input relative to the view.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CameraHeading {
+   /// Rotation around the global Y axis, in radians. Zero faces -Z.
+   pub yaw: f32,
+}
+
+impl CameraHeading {
+   pub fn rotation(&self) -> Quat {
+      Quat::from_rotation_y(self.yaw)
+   }
+
+   /// Level forward direction of the camera.
+   pub fn forward(&self) -> Vec3 {
+      self.rotation().mul_vec3(-Vec3::Z)
+   }
+
+   /// Level right direction of the camera.
+   pub fn right(&self) -> Vec3 {
+      self.rotation().mul_vec3(Vec3::X)
+   }
+}
+
+/// Glues third-person cameras to their target, orbits them with the mouse,
+/// and sets their boom length.
+pub fn third_person_camera(
+   mut commands: Commands,
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   gamepad_axes: Res<Axis<GamepadAxis>>,
+   gamepad_buttons: Res<Axis<GamepadButton>>,
+   cam_params: Res<MovableCameraParams>,
+   q_targets: Query<&GlobalTransform>,
+   mut q_child: Query<(
+      Entity,
+      &Camera,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&BoomObstruction>,
+      Option<&mut CameraHeading>,
+      Option<&GamepadLook>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+) {
+   let motion_total = mouse.motion;
+   let scroll_total = mouse.scroll;
+
+   for (entity, camera, parent, mut transform_child, mut cam, obstruction, heading, gamepad_look) in
+      q_child.iter_mut()
+   {
+      let target = match cam.mode() {
+         CameraMode::ThirdPerson { target } => target,
+         _ => continue,
+      };
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let dt = time.camera_delta_seconds(entity);
+      let (mut rotation_move, mut scroll) = (Vec2::ZERO, 0.0);
+      if let Some(gamepad_look) = gamepad_look {
+         let (look, zoom) = gamepad_look.read(&gamepad_axes, &gamepad_buttons, dt);
+         rotation_move = look;
+         scroll = zoom;
+      } else if window_has_mouse(&windows, camera.window) {
+         if action_state.pressed(FlyingCamAction::Secondary) {
+            rotation_move = motion_total;
+         }
+         scroll = scroll_total;
+      }
+      let pivot = match q_targets.get(target) {
+         Ok(global) => target_pivot(global, cam_params.pivot_offset),
+         Err(_) => continue,
+      };
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_targets) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      let before = space.to_world(&transform_parent, &transform_child);
+      let (mut root, mut child) = before;
+
+      // Swing in behind the character from wherever the camera was
+      if cam.mode_entered {
+         let eye = root.mul_transform(child);
+         let (orbit_parent, orbit_child) = orbit_around(pivot, eye);
+         root = orbit_parent;
+         cam.boom_length = orbit_child.translation.z;
+         cam.mode_entered = false;
+      }
+      root.translation = pivot;
+
+      if let (true, Some(window_size)) = (
+         rotation_move.length_squared() > 0.0,
+         window_size(&windows, camera.window),
+      ) {
+         root.rotation = limit_orbit(
+            rotate_cam_quat(window_size, rotation_move, cam.angular_speed, root.rotation),
+            cam_params.orbit_elevation_limits,
+            cam_params.orbit_yaw_limits,
+         );
+      }
+
+      let (min_boom, max_boom) = cam_params.third_person.boom_length_limits;
+      cam.boom_length =
+         (cam.boom_length - scroll.mul(cam_params.scroll_snap)).clamp(min_boom, max_boom);
+      // Pull the camera in when something is in the way
+      let boom = match obstruction.and_then(|obstruction| obstruction.0) {
+         Some(free_length) => cam.boom_length.min(free_length),
+         None => cam.boom_length,
+      };
+
+      if action_state.just_pressed(FlyingCamAction::SwapShoulder) {
+         cam.swap_shoulder();
+      }
+      let side = cam.step_shoulder(cam_params.third_person.shoulder_swap_time, dt);
+      let shoulder = cam_params.third_person.shoulder_offset;
+      child = Transform::from_xyz(shoulder.x.mul(side), shoulder.y, boom);
+      // A resting target and a still mouse leave the rig untouched
+      space.write_back(
+         before,
+         (root, child),
+         (&mut transform_parent, &mut transform_child),
+      );
+
+      let yaw = yaw_angle(root.rotation);
+      match heading {
+         Some(mut heading) => {
+            if heading.yaw != yaw {
+               heading.yaw = yaw;
+            }
+         }
+         None => {
+            commands.entity(entity).insert(CameraHeading { yaw });
+         }
+      }
+   }
+}
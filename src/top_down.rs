@@ -0,0 +1,198 @@
+//! [`CameraMode::TopDown`]: the strategy game camera, looking down at the
+//! ground from a height that also sets its pitch.
+
+use crate::{
+   net_movement, rig_root, viewport_ray, window_has_mouse, window_size, yaw_angle, CameraActions,
+   CameraMode, CameraMouse, CameraTime, FlyingCamAction, MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::{Div, Mul, Neg};
+
+/// Fraction of the height that one line of scrolling zooms by.
+const TOP_DOWN_ZOOM_STEP: f32 = 0.1;
+
+/// Settings of [`CameraMode::TopDown`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TopDownParams {
+   /// Lowest and highest the camera can be above the ground.
+   pub height_limits: (f32, f32),
+   /// Pitch, in radians below the horizon, of the camera at its lowest and at
+   /// its highest.
+   pub pitch_by_height: (f32, f32),
+   /// Height of the ground plane the camera pans over.
+   pub ground_height: f32,
+   /// Radians per second the camera turns at. Drone and 2D cameras turn at
+   /// it too.
+   pub rotate_speed: f32,
+}
+
+impl Default for TopDownParams {
+   fn default() -> Self {
+      Self {
+         height_limits: (5.0, 100.0),
+         pitch_by_height: (0.6, 1.3),
+         ground_height: 0.0,
+         rotate_speed: 1.5,
+      }
+   }
+}
+
+/// Where a ray crosses the horizontal plane at `height`, if it does so in
+/// front of its origin.
+pub fn ground_intersection(origin: Vec3, direction: Vec3, height: f32) -> Option<Vec3> {
+   if direction.y.abs() <= f32::EPSILON {
+      return None;
+   }
+   let t = (height - origin.y).div(direction.y);
+   (t >= 0.0).then(|| origin + direction.mul(t))
+}
+
+/// Rig layout for a top-down camera over `center`, turned by `yaw`, at
+/// `height` above the ground. The pitch follows the height along
+/// [`TopDownParams::pitch_by_height`].
+pub fn top_down_rig(
+   cam_params: &MovableCameraParams,
+   center: Vec3,
+   yaw: f32,
+   height: f32,
+) -> (Transform, Transform) {
+   let (min_height, max_height) = cam_params.top_down.height_limits;
+   let (near_pitch, far_pitch) = cam_params.top_down.pitch_by_height;
+   let t = if max_height > min_height {
+      ((height - min_height) / (max_height - min_height)).clamp(0.0, 1.0)
+   } else {
+      0.0
+   };
+   let pitch =
+      (near_pitch + (far_pitch - near_pitch).mul(t)).clamp(0.01, std::f32::consts::FRAC_PI_2);
+   (
+      Transform {
+         translation: center,
+         rotation: Quat::from_rotation_y(yaw),
+         ..Default::default()
+      },
+      Transform {
+         translation: Vec3::new(0.0, height, height.div(pitch.tan())),
+         rotation: Quat::from_rotation_x(pitch.neg()),
+         ..Default::default()
+      },
+   )
+}
+
+/// Pans top-down cameras parallel to the ground, turns them around the point
+/// at the center of the view, and zooms them towards the cursor.
+pub fn top_down_camera(
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(
+      Entity,
+      &Camera,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      &GlobalTransform,
+      &PerspectiveProjection,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let scroll_total = mouse.scroll;
+   let ground = cam_params.top_down.ground_height;
+   let (min_height, max_height) = cam_params.top_down.height_limits;
+
+   for (entity, camera, parent, mut transform_child, mut cam, global, projection) in
+      q_child.iter_mut()
+   {
+      let dt = time.camera_delta_seconds(entity);
+      if cam.mode() != CameraMode::TopDown {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+
+      // Settle over the ground point at the center of the current view
+      if cam.mode_entered {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         let eye = root.mul_transform(child);
+         let center = ground_intersection(eye.translation, eye.forward(), ground)
+            .unwrap_or_else(|| Vec3::new(eye.translation.x, ground, eye.translation.z));
+         let height = (eye.translation.y - ground).clamp(min_height, max_height);
+         let (rig_parent, rig_child) =
+            top_down_rig(&cam_params, center, yaw_angle(eye.rotation), height);
+         let (rig_parent, rig_child) = space.to_local(&rig_parent, &rig_child);
+         *transform_parent = rig_parent;
+         *transform_child = rig_child;
+         cam.mode_entered = false;
+      }
+
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let before = space.to_world(&transform_parent, &transform_child);
+      let (root, child) = before;
+      let mut center = root.translation;
+      let mut yaw = yaw_angle(root.rotation);
+      let mut height = child.translation.y;
+
+      // Pan parallel to the ground, faster the higher up the camera is
+      let pan = Vec2::new(
+         net_movement(action_state, FlyingCamAction::Left, FlyingCamAction::Right),
+         net_movement(
+            action_state,
+            FlyingCamAction::Back,
+            FlyingCamAction::Forward,
+         ),
+      )
+      .normalize_or_zero();
+      if pan.length_squared() > 0.0 {
+         let rotation = Quat::from_rotation_y(yaw);
+         let step = cam.speed.mul(height).mul(dt);
+         center += rotation.mul_vec3(Vec3::X).mul(pan.x.mul(step))
+            + rotation.mul_vec3(Vec3::Z.neg()).mul(pan.y.mul(step));
+      }
+
+      let turn = net_movement(
+         action_state,
+         FlyingCamAction::RotateRight,
+         FlyingCamAction::RotateLeft,
+      );
+      yaw += turn.mul(cam_params.top_down.rotate_speed).mul(dt);
+
+      // Zoom towards the ground point under the cursor so it stays put
+      if scroll_total.abs() > 0.0 && window_has_mouse(&windows, camera.window) {
+         let new_height = height
+            .mul((1.0 - TOP_DOWN_ZOOM_STEP).powf(scroll_total))
+            .clamp(min_height, max_height);
+         let cursor = windows
+            .get(camera.window)
+            .and_then(|window| window.cursor_position());
+         if let (Some(cursor), Some(window_size)) = (cursor, window_size(&windows, camera.window)) {
+            let (origin, direction) = viewport_ray(
+               window_size,
+               cursor,
+               projection.fov,
+               projection.aspect_ratio,
+               global,
+            );
+            if let Some(point) = ground_intersection(origin, direction, ground) {
+               center += (point - center).mul(1.0 - new_height.div(height));
+            }
+         }
+         height = new_height;
+      }
+
+      let (rig_parent, rig_child) = top_down_rig(&cam_params, center, yaw, height);
+      // Only write when it changes so the rig isn't needlessly marked changed
+      space.write_back(
+         before,
+         (rig_parent, rig_child),
+         (&mut transform_parent, &mut transform_child),
+      );
+   }
+}
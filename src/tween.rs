@@ -0,0 +1,310 @@
+//! Eased camera motion, shared by focusing and [`FlyTo`].
+
+use crate::{
+   free_rig, limit_pitch_to, rig_root, CameraMode, Easing, MovableCamera, MovableCameraParams,
+   TimeSource, VirtualTimeScale,
+};
+use bevy::prelude::*;
+
+/// How long an eased move takes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TravelTime {
+   /// Always take this many seconds, however far the camera goes.
+   Duration(f32),
+   /// Cover this many units per second, so long trips take longer.
+   Speed(f32),
+}
+
+impl TravelTime {
+   /// Seconds taken to travel `distance` units.
+   pub fn seconds(&self, distance: f32) -> f32 {
+      match *self {
+         TravelTime::Duration(duration) => duration.max(0.0),
+         TravelTime::Speed(speed) if speed > 0.0 => distance / speed,
+         TravelTime::Speed(_) => 0.0,
+      }
+   }
+}
+
+/// How a camera travels on an eased move.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TravelStyle {
+   pub time: TravelTime,
+   pub easing: Easing,
+   /// How far the camera lifts up halfway through, as a fraction of the
+   /// distance travelled. Long trips arc up and over instead of cutting
+   /// through whatever lies between.
+   pub arc: f32,
+}
+
+impl TravelStyle {
+   /// Eased, straight travel taking `duration` seconds.
+   pub fn duration(duration: f32) -> Self {
+      Self {
+         time: TravelTime::Duration(duration),
+         ..Default::default()
+      }
+   }
+
+   /// Eased, straight travel at `speed` units per second.
+   pub fn speed(speed: f32) -> Self {
+      Self {
+         time: TravelTime::Speed(speed),
+         ..Default::default()
+      }
+   }
+
+   pub fn with_easing(mut self, easing: Easing) -> Self {
+      self.easing = easing;
+      self
+   }
+
+   pub fn with_arc(mut self, arc: f32) -> Self {
+      self.arc = arc;
+      self
+   }
+}
+
+impl Default for TravelStyle {
+   fn default() -> Self {
+      Self {
+         time: TravelTime::Duration(1.0),
+         easing: Easing::EaseInOut,
+         arc: 0.0,
+      }
+   }
+}
+
+/// Event that flies every movable camera to a world-space pose, leaving focus
+/// mode on arrival.
+#[derive(Clone, Copy, Debug)]
+pub struct FlyTo {
+   pub transform: Transform,
+   pub style: TravelStyle,
+}
+
+impl FlyTo {
+   /// Flies to `transform` over `duration` seconds.
+   pub fn new(transform: Transform, duration: f32) -> Self {
+      Self {
+         transform,
+         style: TravelStyle::duration(duration),
+      }
+   }
+}
+
+/// Event that turns every movable camera to face `point` over `duration`
+/// seconds without moving it. Focused cameras leave focus mode first.
+#[derive(Clone, Copy, Debug)]
+pub struct LookAt {
+   pub point: Vec3,
+   pub duration: f32,
+}
+
+/// An eased move of a camera rig in progress. While present, user input
+/// doesn't move the camera.
+///
+/// The rig parent is set to its final transform when the tween starts and the
+/// camera is animated in world space from `from` to wherever `child` places it
+/// under the parent, so a pivot that keeps moving is tracked.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraTween {
+   /// World-space camera pose at the start.
+   pub from: Transform,
+   /// Camera transform relative to the rig parent at the end.
+   pub child: Transform,
+   pub duration: f32,
+   pub elapsed: f32,
+   pub easing: Easing,
+   /// Height in world units the camera lifts up by halfway through.
+   pub lift: f32,
+}
+
+impl CameraTween {
+   pub fn progress(&self) -> f32 {
+      if self.duration > 0.0 {
+         (self.elapsed / self.duration).clamp(0.0, 1.0)
+      } else {
+         1.0
+      }
+   }
+
+   /// Camera transform relative to `parent` at eased progress `t`.
+   pub fn sample(&self, parent: &Transform, t: f32) -> Transform {
+      let to = parent.mul_transform(self.child);
+      let lift = Vec3::Y * (self.lift * 4.0 * t * (1.0 - t));
+      let eye = Transform {
+         translation: self.from.translation.lerp(to.translation, t) + lift,
+         rotation: self.from.rotation.slerp(to.rotation, t),
+         scale: self.from.scale.lerp(to.scale, t),
+      };
+      Transform::from_matrix(parent.compute_matrix().inverse() * eye.compute_matrix())
+   }
+}
+
+/// Moves a camera rig to a new parent/child layout. With a positive
+/// `duration` the camera flies there with a [`CameraTween`], otherwise it
+/// jumps.
+pub fn move_rig(
+   commands: &mut Commands,
+   camera: Entity,
+   duration: f32,
+   transform_parent: &mut Transform,
+   transform_child: &mut Transform,
+   rig: (Transform, Transform),
+) {
+   travel_rig(
+      commands,
+      camera,
+      &TravelStyle::duration(duration),
+      transform_parent,
+      transform_child,
+      rig,
+   );
+}
+
+/// Moves a camera rig to a new parent/child layout, travelling as `style`
+/// says. Trips that take no time jump straight there.
+pub fn travel_rig(
+   commands: &mut Commands,
+   camera: Entity,
+   style: &TravelStyle,
+   transform_parent: &mut Transform,
+   transform_child: &mut Transform,
+   (parent, child): (Transform, Transform),
+) {
+   let from = transform_parent.mul_transform(*transform_child);
+   let distance = from
+      .translation
+      .distance(parent.mul_transform(child).translation);
+   let duration = style.time.seconds(distance);
+   if duration > 0.0 {
+      let tween = CameraTween {
+         from,
+         child,
+         duration,
+         elapsed: 0.0,
+         easing: style.easing,
+         lift: style.arc.max(0.0) * distance,
+      };
+      *transform_parent = parent;
+      *transform_child = tween.sample(&parent, 0.0);
+      commands.entity(camera).insert(tween);
+   } else {
+      *transform_parent = parent;
+      *transform_child = child;
+      commands.entity(camera).remove::<CameraTween>();
+   }
+}
+
+/// Handles [`FlyTo`] events.
+pub fn fly_to(
+   mut commands: Commands,
+   mut events: EventReader<FlyTo>,
+   mut q_child: Query<(Entity, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for FlyTo { transform, style } in events.iter() {
+      for (entity, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+         if !cam.mode().is_manual() {
+            continue;
+         }
+         if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+            travel_rig(
+               &mut commands,
+               entity,
+               style,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&Transform::default(), transform),
+            );
+            cam.enter_laid_out_mode(CameraMode::Free);
+            cam.orbit_velocity = Vec2::ZERO;
+         }
+      }
+   }
+}
+
+/// Free world-space rig with the camera where it is, turned to face `point`
+/// as far as `pitch_limits` allow.
+pub(crate) fn look_at_rig(
+   transform_parent: &Transform,
+   transform_child: &Transform,
+   point: Vec3,
+   pitch_limits: (f32, f32),
+) -> (Transform, Transform) {
+   let (free_parent, mut free_child) = free_rig(transform_parent, transform_child);
+   let to_point = point - free_child.translation;
+   // A degenerate direction leaves the orientation alone
+   if to_point.cross(Vec3::Y).length_squared() > f32::EPSILON {
+      free_child.rotation = limit_pitch_to(
+         Transform::from_translation(free_child.translation)
+            .looking_at(point, Vec3::Y)
+            .rotation,
+         pitch_limits,
+      );
+   }
+   (free_parent, free_child)
+}
+
+/// Handles [`LookAt`] events.
+pub fn look_at(
+   mut commands: Commands,
+   cam_params: Res<MovableCameraParams>,
+   mut events: EventReader<LookAt>,
+   mut q_child: Query<(Entity, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for LookAt { point, duration } in events.iter() {
+      for (entity, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+         if !cam.mode().is_manual() {
+            continue;
+         }
+         if let Some((mut transform_parent, space)) = rig_root(parent, &mut q_parent, &q_globals) {
+            let (root, child) = space.to_world(&transform_parent, &transform_child);
+            let rig = look_at_rig(&root, &child, *point, cam_params.pitch_limits);
+            move_rig(
+               &mut commands,
+               entity,
+               *duration,
+               &mut transform_parent,
+               &mut transform_child,
+               space.to_local(&rig.0, &rig.1),
+            );
+            cam.enter_laid_out_mode(CameraMode::Free);
+            cam.orbit_velocity = Vec2::ZERO;
+         }
+      }
+   }
+}
+
+/// Advances camera tweens and removes them once finished.
+pub fn tween_camera(
+   time: Res<Time>,
+   time_scale: Res<VirtualTimeScale>,
+   mut commands: Commands,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut CameraTween,
+      Option<&TimeSource>,
+   )>,
+   q_parent: Query<&Transform, Without<CameraTween>>,
+) {
+   for (entity, parent, mut transform_child, mut tween, source) in q_child.iter_mut() {
+      let transform_parent = match q_parent.get(parent.0) {
+         Ok(transform_parent) => transform_parent,
+         Err(_) => continue,
+      };
+      tween.elapsed += time.delta_seconds() * time_scale.for_source(source);
+      if tween.progress() >= 1.0 {
+         *transform_child = tween.child;
+         commands.entity(entity).remove::<CameraTween>();
+      } else {
+         *transform_child = tween.sample(transform_parent, tween.easing.apply(tween.progress()));
+      }
+   }
+}
@@ -0,0 +1,137 @@
+//! [`CameraMode::Vehicle`]: a camera with the handling of a tank or drone.
+
+use crate::{
+   free_rig, move_free, net_movement, rig_root, yaw_angle, CameraActions, CameraMode, CameraTime,
+   FlyingCamAction, MovableCamera,
+};
+use bevy::prelude::*;
+use std::ops::{Mul, Neg};
+
+/// Handling of a camera in [`CameraMode::Vehicle`]. Cameras without one get
+/// the default on entering the mode.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct VehicleHandling {
+   /// Top speed forward, in units per second. Reverse tops out at half.
+   pub max_speed: f32,
+   /// Units per second squared the speed picks up at.
+   pub acceleration: f32,
+   /// Units per second squared the speed drops at when braking or coasting.
+   pub braking: f32,
+   /// Radians per second the vehicle turns at.
+   pub max_yaw_rate: f32,
+   /// Radians per second the vehicle pitches at.
+   pub max_pitch_rate: f32,
+   /// Steepest pitch up or down, in radians.
+   pub pitch_limit: f32,
+   speed: f32,
+}
+
+impl Default for VehicleHandling {
+   fn default() -> Self {
+      Self {
+         max_speed: 15.0,
+         acceleration: 6.0,
+         braking: 12.0,
+         max_yaw_rate: 1.2,
+         max_pitch_rate: 0.6,
+         pitch_limit: 0.6,
+         speed: 0.0,
+      }
+   }
+}
+
+impl VehicleHandling {
+   /// Current forward speed, negative in reverse.
+   pub fn speed(&self) -> f32 {
+      self.speed
+   }
+}
+
+/// Drives vehicle cameras. `Forward` and `Back` speed up and slow down or
+/// reverse, `Left` and `Right` turn, and `Up` and `Down` pitch, all limited
+/// by the camera's [`VehicleHandling`].
+pub fn vehicle_camera(
+   mut commands: Commands,
+   time: CameraTime,
+   actions: CameraActions,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&mut VehicleHandling>,
+   )>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for (entity, parent, mut transform_child, mut cam, handling) in q_child.iter_mut() {
+      let dt = time.camera_delta_seconds(entity);
+      if cam.mode() != CameraMode::Vehicle {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      if cam.mode_entered {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         let (free_parent, free_child) = free_rig(&root, &child);
+         let (free_parent, free_child) = space.to_local(&free_parent, &free_child);
+         *transform_parent = free_parent;
+         *transform_child = free_child;
+         cam.mode_entered = false;
+      }
+      let mut handling = match handling {
+         Some(handling) => handling,
+         None => {
+            commands.entity(entity).insert(VehicleHandling::default());
+            continue;
+         }
+      };
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+
+      // Ease the speed toward where the throttle points it
+      let throttle = net_movement(
+         action_state,
+         FlyingCamAction::Back,
+         FlyingCamAction::Forward,
+      );
+      let target = if throttle < 0.0 {
+         throttle.mul(handling.max_speed).mul(0.5)
+      } else {
+         throttle.mul(handling.max_speed)
+      };
+      let rate = if target.abs() > handling.speed.abs() && target * handling.speed >= 0.0 {
+         handling.acceleration
+      } else {
+         handling.braking
+      };
+      let step = rate.mul(dt);
+      handling.speed += (target - handling.speed).clamp(step.neg(), step);
+
+      let (steer, climb) = (
+         net_movement(action_state, FlyingCamAction::Right, FlyingCamAction::Left),
+         net_movement(action_state, FlyingCamAction::Down, FlyingCamAction::Up),
+      );
+      move_free(
+         space,
+         (&mut transform_parent, &mut transform_child),
+         |mut eye| {
+            // Turn at no more than the handling allows, keeping the horizon level
+            let forward = eye.forward();
+            let yaw = yaw_angle(eye.rotation) + steer.mul(handling.max_yaw_rate).mul(dt);
+            let pitch = (forward.y.clamp(-1.0, 1.0).asin()
+               + climb.mul(handling.max_pitch_rate).mul(dt))
+            .clamp(handling.pitch_limit.neg(), handling.pitch_limit);
+            eye.rotation = Quat::from_rotation_y(yaw).mul(Quat::from_rotation_x(pitch));
+
+            let forward = eye.forward();
+            eye.translation += forward.mul(handling.speed).mul(dt);
+            eye
+         },
+      );
+   }
+}
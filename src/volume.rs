@@ -0,0 +1,164 @@
+//! Regions of a level with their own camera rules.
+
+use crate::{limit_pitch_to, CameraBounds, CameraMode, MovableCamera};
+use bevy::{prelude::*, utils::HashMap};
+use std::ops::Mul;
+
+/// Shape of a [`CameraVolume`], centered on and turned with its entity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VolumeShape {
+   Box { half_extents: Vec3 },
+   Sphere { radius: f32 },
+}
+
+/// Settings a [`CameraVolume`] imposes on cameras inside it. Unset ones are
+/// left alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VolumeOverrides {
+   /// Mode to switch to on entering.
+   pub mode: Option<CameraMode>,
+   /// Slowest and fastest fly speed.
+   pub speed_limits: Option<(f32, f32)>,
+   /// Lowest and highest pitch, in radians, of free flying cameras.
+   pub pitch_limits: Option<(f32, f32)>,
+   /// Bounds to keep the camera in.
+   pub bounds: Option<CameraBounds>,
+}
+
+/// A region that changes the camera's settings while a camera is inside it
+/// and puts them back when it leaves. Where volumes overlap, the one with the
+/// highest `priority` applies.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraVolume {
+   pub shape: VolumeShape,
+   pub overrides: VolumeOverrides,
+   pub priority: i32,
+}
+
+impl CameraVolume {
+   /// Whether `point` is inside the volume placed at `transform`.
+   pub fn contains(&self, transform: &GlobalTransform, point: Vec3) -> bool {
+      let local = transform
+         .rotation
+         .inverse()
+         .mul_vec3(point - transform.translation)
+         / transform.scale;
+      match self.shape {
+         VolumeShape::Box { half_extents } => local.abs().cmple(half_extents).all(),
+         VolumeShape::Sphere { radius } => local.length_squared() <= radius.mul(radius),
+      }
+   }
+}
+
+/// Sent when a camera enters a [`CameraVolume`].
+#[derive(Clone, Copy, Debug)]
+pub struct CameraVolumeEntered {
+   pub camera: Entity,
+   pub volume: Entity,
+}
+
+/// Sent when a camera leaves a [`CameraVolume`], after its settings were
+/// restored.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraVolumeExited {
+   pub camera: Entity,
+   pub volume: Entity,
+}
+
+/// Camera settings from before entering a volume.
+#[derive(Clone, Copy, Debug)]
+struct SavedSettings {
+   mode: CameraMode,
+   speed: f32,
+   bounds: Option<CameraBounds>,
+}
+
+/// Applies and restores [`CameraVolume`] overrides as cameras move between
+/// volumes.
+pub fn camera_volumes(
+   mut commands: Commands,
+   mut occupied: Local<HashMap<Entity, (Entity, SavedSettings)>>,
+   mut entered: EventWriter<CameraVolumeEntered>,
+   mut exited: EventWriter<CameraVolumeExited>,
+   q_volumes: Query<(Entity, &CameraVolume, &GlobalTransform)>,
+   mut q_child: Query<(
+      Entity,
+      &Parent,
+      &mut Transform,
+      &mut MovableCamera,
+      Option<&CameraBounds>,
+   )>,
+   q_parent: Query<&Transform, Without<MovableCamera>>,
+) {
+   for (entity, parent, mut transform_child, mut cam, bounds) in q_child.iter_mut() {
+      let transform_parent = match q_parent.get(parent.0) {
+         Ok(transform_parent) => transform_parent,
+         Err(_) => continue,
+      };
+      let eye = transform_parent.mul_transform(*transform_child).translation;
+      let inside = q_volumes
+         .iter()
+         .filter(|(_, volume, global)| volume.contains(global, eye))
+         .max_by_key(|(_, volume, _)| volume.priority)
+         .map(|(volume_entity, volume, _)| (volume_entity, *volume));
+      let current = occupied.get(&entity).copied();
+
+      if current.map(|(volume, _)| volume) != inside.map(|(volume, _)| volume) {
+         if let Some((volume, saved)) = current {
+            cam.enter_mode(saved.mode);
+            cam.speed = saved.speed;
+            match saved.bounds {
+               Some(bounds) => commands.entity(entity).insert(bounds),
+               None => commands.entity(entity).remove::<CameraBounds>(),
+            };
+            occupied.remove(&entity);
+            exited.send(CameraVolumeExited {
+               camera: entity,
+               volume,
+            });
+         }
+         if let Some((volume_entity, volume)) = inside {
+            occupied.insert(
+               entity,
+               (
+                  volume_entity,
+                  SavedSettings {
+                     mode: cam.mode(),
+                     speed: cam.speed,
+                     bounds: bounds.copied(),
+                  },
+               ),
+            );
+            if let Some(mode) = volume.overrides.mode {
+               // A refused switch, e.g. while disabled, keeps the mode
+               let _ = cam.set_mode(mode);
+            }
+            if let Some(bounds) = volume.overrides.bounds {
+               commands.entity(entity).insert(bounds);
+            }
+            entered.send(CameraVolumeEntered {
+               camera: entity,
+               volume: volume_entity,
+            });
+         }
+      }
+
+      let overrides = match inside {
+         Some((_, volume)) => volume.overrides,
+         None => continue,
+      };
+      if let Some((min_speed, max_speed)) = overrides.speed_limits {
+         let speed = cam.speed.clamp(min_speed, max_speed);
+         if speed != cam.speed {
+            cam.speed = speed;
+         }
+      }
+      if let (Some((min_pitch, max_pitch)), CameraMode::Free) = (overrides.pitch_limits, cam.mode())
+      {
+         let rotation = limit_pitch_to(transform_child.rotation, (min_pitch, max_pitch));
+         if rotation != transform_child.rotation {
+            transform_child.rotation = rotation;
+         }
+      }
+   }
+}
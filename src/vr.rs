@@ -0,0 +1,96 @@
+//! [`CameraMode::Vr`]: locomotion for head-tracked cameras.
+
+use crate::{
+   net_movement, rig_root, yaw_angle, CameraActions, CameraMode, CameraTime, FlyingCamAction,
+   MovableCamera, MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::ops::Mul;
+
+/// Settings of [`CameraMode::Vr`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VrParams {
+   /// Radians a snap turn turns by.
+   pub snap_turn: f32,
+}
+
+impl Default for VrParams {
+   fn default() -> Self {
+      Self {
+         snap_turn: std::f32::consts::FRAC_PI_4,
+      }
+   }
+}
+
+/// Moves the rig root of VR cameras and never touches the camera itself,
+/// whose transform belongs to the headset. The movement keys walk along the
+/// level heading of the head, `Up` and `Down` rise and sink, and
+/// `RotateLeft` and `RotateRight` snap-turn around the head.
+pub fn vr_camera(
+   time: CameraTime,
+   cam_params: Res<MovableCameraParams>,
+   actions: CameraActions,
+   mut q_child: Query<(Entity, &Parent, &Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   for (entity, parent, transform_child, mut cam) in q_child.iter_mut() {
+      if cam.mode() != CameraMode::Vr {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      let (mut root, child) = space.to_world(&transform_parent, transform_child);
+      // Snap turns are around Y, so keep the play space level
+      if cam.mode_entered {
+         root.rotation = Quat::from_rotation_y(yaw_angle(root.rotation));
+         *transform_parent = space.to_local(&root, &child).0;
+         cam.mode_entered = false;
+      }
+      let before = root;
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+      let head = root.mul_transform(child);
+
+      let heading = Quat::from_rotation_y(yaw_angle(head.rotation));
+      let walk = Vec3::new(
+         net_movement(action_state, FlyingCamAction::Left, FlyingCamAction::Right),
+         net_movement(action_state, FlyingCamAction::Down, FlyingCamAction::Up),
+         net_movement(
+            action_state,
+            FlyingCamAction::Forward,
+            FlyingCamAction::Back,
+         ),
+      )
+      .normalize_or_zero();
+      if walk.length_squared() > 0.0 {
+         root.translation += heading
+            .mul_vec3(walk)
+            .mul(cam.speed)
+            .mul(time.camera_delta_seconds(entity));
+      }
+
+      let mut turn = 0.0;
+      if action_state.just_pressed(FlyingCamAction::RotateLeft) {
+         turn += cam_params.vr.snap_turn;
+      }
+      if action_state.just_pressed(FlyingCamAction::RotateRight) {
+         turn -= cam_params.vr.snap_turn;
+      }
+      if turn != 0.0 {
+         // Turn the play space around the head so the head stays put
+         let turn = Quat::from_rotation_y(turn);
+         let pivot = head.translation;
+         root.translation = pivot + turn.mul_vec3(root.translation - pivot);
+         root.rotation = turn.mul(root.rotation);
+      }
+      // Only the root is written back; the camera belongs to the headset
+      if root != before {
+         *transform_parent = space.to_local(&root, &child).0;
+      }
+   }
+}
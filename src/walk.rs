@@ -0,0 +1,178 @@
+//! [`CameraMode::Walk`]: walk through a building at eye height, for
+//! architectural visualization.
+
+use crate::{
+   free_rig, move_free, net_movement, rig_root, window_has_mouse, window_size, yaw_angle,
+   CameraActions, CameraMode, CameraMouse, CameraTime, FlyingCamAction, MovableCamera,
+   MovableCameraParams,
+};
+use bevy::prelude::*;
+use std::{
+   f32::consts::{PI, TAU},
+   ops::{Div, Mul},
+};
+
+/// Settings of [`CameraMode::Walk`] cameras.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WalkParams {
+   /// Height of the eye above the floor.
+   pub eye_height: f32,
+   /// Tallest step the camera climbs; anything taller blocks it.
+   pub step_height: f32,
+   /// Units per second the camera walks at.
+   pub speed: f32,
+   /// Lowest and highest pitch, in radians, the camera can look at.
+   pub pitch_limits: (f32, f32),
+   /// Height of the highest surface at or below a point, used as the floor.
+   /// Without it the floor is flat at `floor`.
+   pub ground: Option<fn(Vec3) -> Option<f32>>,
+   /// Floor height when there is no `ground`.
+   pub floor: f32,
+}
+
+impl Default for WalkParams {
+   fn default() -> Self {
+      Self {
+         eye_height: 1.7,
+         step_height: 0.3,
+         speed: 2.0,
+         pitch_limits: (-1.4, 1.4),
+         ground: None,
+         floor: 0.0,
+      }
+   }
+}
+
+impl MovableCameraParams {
+   /// Settings for walking through buildings: a standing eye height, stairs
+   /// climbed in steps of up to 35 cm, walking pace and a limited look up
+   /// and down. Put cameras in [`CameraMode::Walk`] and set `walk.ground` to
+   /// a raycast against the building.
+   pub fn walkthrough() -> Self {
+      Self {
+         walk: WalkParams {
+            eye_height: 1.6,
+            step_height: 0.35,
+            speed: 1.4,
+            pitch_limits: (-1.0, 1.0),
+            ..Default::default()
+         },
+         ..Default::default()
+      }
+   }
+}
+
+/// Floor height under `position` that a walker with its feet at `feet` can
+/// stand on: the highest surface no more than `step` above its feet.
+fn floor_under(
+   cam_params: &MovableCameraParams,
+   position: Vec3,
+   feet: f32,
+   step: f32,
+) -> Option<f32> {
+   match cam_params.walk.ground {
+      Some(ground) => ground(Vec3::new(position.x, feet + step, position.z)),
+      None => Some(cam_params.walk.floor),
+   }
+   .filter(|floor| *floor <= feet + step)
+}
+
+/// Walks walk cameras over the floor at [`WalkParams::eye_height`]. The
+/// movement keys walk along the level heading, climbing steps up to
+/// [`WalkParams::step_height`] and stopping at anything taller, and dragging
+/// with `Secondary` looks around within [`WalkParams::pitch_limits`].
+pub fn walk_camera(
+   windows: Res<Windows>,
+   time: CameraTime,
+   actions: CameraActions,
+   mouse: Res<CameraMouse>,
+   cam_params: Res<MovableCameraParams>,
+   mut q_child: Query<(Entity, &Camera, &Parent, &mut Transform, &mut MovableCamera)>,
+   mut q_parent: Query<(&mut Transform, Option<&Parent>), Without<MovableCamera>>,
+   q_globals: Query<&GlobalTransform>,
+) {
+   let motion_total = mouse.motion;
+   let (min_pitch, max_pitch) = cam_params.walk.pitch_limits;
+
+   for (entity, camera, parent, mut transform_child, mut cam) in q_child.iter_mut() {
+      if cam.mode() != CameraMode::Walk {
+         continue;
+      }
+      let (mut transform_parent, space) = match rig_root(parent, &mut q_parent, &q_globals) {
+         Some(rig) => rig,
+         None => continue,
+      };
+      if cam.mode_entered {
+         let (root, child) = space.to_world(&transform_parent, &transform_child);
+         let (free_parent, mut free_child) = free_rig(&root, &child);
+         // Land on whatever is below, however far down
+         let eye = free_child.translation;
+         let feet = eye.y - cam_params.walk.eye_height;
+         if let Some(floor) = floor_under(&cam_params, eye, feet, cam_params.walk.step_height) {
+            free_child.translation.y = floor + cam_params.walk.eye_height;
+         }
+         let (free_parent, free_child) = space.to_local(&free_parent, &free_child);
+         *transform_parent = free_parent;
+         *transform_child = free_child;
+         cam.mode_entered = false;
+      }
+      let action_state = match actions.get(entity) {
+         Some(action_state) => action_state,
+         None => continue,
+      };
+
+      let look = (action_state.pressed(FlyingCamAction::Secondary) || cam.cursor_captured)
+         && window_has_mouse(&windows, camera.window);
+      let look_size = window_size(&windows, camera.window).filter(|_| look);
+      let angular_speed = cam.angular_speed;
+      let walk = Vec3::new(
+         net_movement(action_state, FlyingCamAction::Left, FlyingCamAction::Right),
+         0.0,
+         net_movement(
+            action_state,
+            FlyingCamAction::Forward,
+            FlyingCamAction::Back,
+         ),
+      )
+      .normalize_or_zero();
+      let dt = time.camera_delta_seconds(entity);
+      move_free(
+         space,
+         (&mut transform_parent, &mut transform_child),
+         |mut eye| {
+            let mut yaw = yaw_angle(eye.rotation);
+            let mut pitch = eye
+               .forward()
+               .y
+               .clamp(-1.0, 1.0)
+               .asin()
+               .clamp(min_pitch, max_pitch);
+            if let Some(window_size) = look_size {
+               yaw -= motion_total
+                  .x
+                  .div(window_size.x)
+                  .mul(TAU)
+                  .mul(angular_speed);
+               pitch = (pitch - motion_total.y.div(window_size.y).mul(PI).mul(angular_speed))
+                  .clamp(min_pitch, max_pitch);
+            }
+            eye.rotation = Quat::from_rotation_y(yaw).mul(Quat::from_rotation_x(pitch));
+
+            if walk.length_squared() == 0.0 {
+               return eye;
+            }
+            let feet = eye.translation.y - cam_params.walk.eye_height;
+            let next = eye.translation
+               + Quat::from_rotation_y(yaw)
+                  .mul_vec3(walk)
+                  .mul(cam_params.walk.speed)
+                  .mul(dt);
+            // Anything higher than a step is a wall, and nothing below is a ledge
+            if let Some(floor) = floor_under(&cam_params, next, feet, cam_params.walk.step_height) {
+               eye.translation = Vec3::new(next.x, floor + cam_params.walk.eye_height, next.z);
+            }
+            eye
+         },
+      );
+   }
+}
@@ -0,0 +1,182 @@
+//! Pointer lock in the browser, where the cursor can only be locked from
+//! inside a click and the user can take it back with Esc at any time.
+
+use crate::{CameraMode, MovableCamera};
+use bevy::prelude::*;
+use std::sync::{
+   atomic::{AtomicBool, Ordering},
+   Arc,
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+/// Pointer lock of the page, shared with the browser's event handlers.
+///
+/// In a browser the camera systems never lock or move the cursor themselves:
+/// - First-person cameras capture the cursor on the next click on the canvas,
+///   since browsers only grant the lock from inside a click. Taking it back
+///   with Esc counts as `ReleaseCursor`, and another click captures it again.
+/// - Holding `Secondary` looks around by dragging. With `lock_on_drag` the
+///   pointer is locked for the drag; without it the cursor moves across the
+///   page while looking, as browsers can't move it back.
+pub struct WebPointerLock {
+   /// CSS selector of the canvas to lock to. `None` takes the first canvas
+   /// on the page.
+   pub canvas: Option<String>,
+   /// Lock the pointer while the right button drags to look.
+   pub lock_on_drag: bool,
+   shared: Arc<SharedLock>,
+   installed: bool,
+}
+
+#[derive(Default)]
+struct SharedLock {
+   /// A first-person camera wants the cursor on the next click.
+   wanted: AtomicBool,
+   locked: AtomicBool,
+   /// The lock is only held for a right-button drag.
+   dragging: AtomicBool,
+   gained: AtomicBool,
+   lost: AtomicBool,
+}
+
+impl Default for WebPointerLock {
+   fn default() -> Self {
+      Self {
+         canvas: None,
+         lock_on_drag: true,
+         shared: Arc::default(),
+         installed: false,
+      }
+   }
+}
+
+impl WebPointerLock {
+   /// Whether the browser has the pointer locked to the canvas.
+   pub fn is_locked(&self) -> bool {
+      self.shared.locked.load(Ordering::Relaxed)
+   }
+
+   /// Hands the pointer back to the page.
+   pub fn release(&self) {
+      if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+         document.exit_pointer_lock();
+      }
+   }
+
+   /// Stops capturing on clicks and hands the pointer back, for a camera that
+   /// went away with it.
+   pub(crate) fn release_capture(&self) {
+      self.shared.wanted.store(false, Ordering::Relaxed);
+      if self.is_locked() {
+         self.release();
+      }
+   }
+
+   /// Brings the capture of `cam` in line with the browser's pointer lock,
+   /// after [`capture_cursor`](crate::capture_cursor) has decided on it.
+   pub(crate) fn sync_capture(&self, cam: &mut MovableCamera) {
+      let first_person = cam.mode() == CameraMode::FirstPerson;
+      self.shared.wanted.store(first_person, Ordering::Relaxed);
+      let captured = self.is_locked() && !self.shared.dragging.load(Ordering::Relaxed);
+      let gained = self.shared.gained.swap(false, Ordering::Relaxed);
+      let lost = self.shared.lost.swap(false, Ordering::Relaxed);
+      if !first_person {
+         if captured {
+            self.release();
+         }
+         return;
+      }
+      if gained {
+         cam.cursor_released = false;
+         cam.cursor_captured = true;
+      } else if lost {
+         if cam.cursor_captured {
+            cam.cursor_released = true;
+         }
+         cam.cursor_captured = false;
+      } else if !captured {
+         // Nothing to capture until the user clicks
+         cam.cursor_captured = false;
+      } else if !cam.cursor_captured {
+         // Let go with `ReleaseCursor`
+         self.release();
+      }
+   }
+}
+
+/// Hooks [`WebPointerLock`] up to the canvas once it exists: clicks request
+/// the lock, releasing the right button ends a drag lock, and the page tells
+/// it when the lock is gained or lost. Also keeps the context menu from
+/// opening on right-drags.
+pub fn install_pointer_lock(mut lock: ResMut<WebPointerLock>) {
+   if lock.installed {
+      return;
+   }
+   let document = match web_sys::window().and_then(|window| window.document()) {
+      Some(document) => document,
+      None => return,
+   };
+   let selector = lock.canvas.as_deref().unwrap_or("canvas");
+   let canvas = match document.query_selector(selector).ok().flatten() {
+      Some(canvas) => canvas,
+      // Not created yet
+      None => return,
+   };
+
+   let shared = lock.shared.clone();
+   let lock_on_drag = lock.lock_on_drag;
+   let target = canvas.clone();
+   let on_mouse_down = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+      let wanted = shared.wanted.load(Ordering::Relaxed);
+      let drag = !wanted && lock_on_drag && event.button() == 2;
+      if (wanted || drag) && !shared.locked.load(Ordering::Relaxed) {
+         shared.dragging.store(drag, Ordering::Relaxed);
+         target.request_pointer_lock();
+      }
+   }) as Box<dyn FnMut(_)>);
+
+   let shared = lock.shared.clone();
+   let exit_document = document.clone();
+   let on_mouse_up = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+      if event.button() == 2 && shared.dragging.load(Ordering::Relaxed) {
+         exit_document.exit_pointer_lock();
+      }
+   }) as Box<dyn FnMut(_)>);
+
+   let on_context_menu = Closure::wrap(Box::new(|event: web_sys::Event| {
+      event.prevent_default();
+   }) as Box<dyn FnMut(_)>);
+
+   let shared = lock.shared.clone();
+   let lock_document = document.clone();
+   let on_lock_change = Closure::wrap(Box::new(move |_: web_sys::Event| {
+      let locked = lock_document.pointer_lock_element().is_some();
+      let was_locked = shared.locked.swap(locked, Ordering::Relaxed);
+      let dragging = shared.dragging.load(Ordering::Relaxed);
+      if locked && !was_locked && !dragging {
+         shared.gained.store(true, Ordering::Relaxed);
+      } else if !locked && was_locked {
+         if !dragging {
+            shared.lost.store(true, Ordering::Relaxed);
+         }
+         shared.dragging.store(false, Ordering::Relaxed);
+      }
+   }) as Box<dyn FnMut(_)>);
+
+   let _ =
+      canvas.add_event_listener_with_callback("mousedown", on_mouse_down.as_ref().unchecked_ref());
+   let _ =
+      document.add_event_listener_with_callback("mouseup", on_mouse_up.as_ref().unchecked_ref());
+   let _ = canvas
+      .add_event_listener_with_callback("contextmenu", on_context_menu.as_ref().unchecked_ref());
+   let _ = document.add_event_listener_with_callback(
+      "pointerlockchange",
+      on_lock_change.as_ref().unchecked_ref(),
+   );
+   // The listeners live as long as the page
+   on_mouse_down.forget();
+   on_mouse_up.forget();
+   on_context_menu.forget();
+   on_lock_change.forget();
+   lock.installed = true;
+}
@@ -0,0 +1,86 @@
+//! Property tests of pitch limiting over arbitrary camera rotations.
+
+use bevy::prelude::*;
+use bevy_flying_cam::{limit_pitch, limit_pitch_to};
+use proptest::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+const EPSILON: f32 = 1e-3;
+
+fn any_rotation() -> impl Strategy<Value = Quat> {
+   (-1.0f32..1.0, -1.0f32..1.0, -1.0f32..1.0, -1.0f32..1.0)
+      .prop_filter("too short to normalize", |(x, y, z, w)| {
+         x * x + y * y + z * z + w * w > 0.01
+      })
+      .prop_map(|(x, y, z, w)| Quat::from_xyzw(x, y, z, w).normalize())
+}
+
+/// Yaw, pitch and roll of a rotation, with the pitch anywhere, even past
+/// vertical, and the roll less than a quarter turn either way.
+fn any_angles() -> impl Strategy<Value = (f32, f32, f32)> {
+   (-PI..PI, -PI..PI, -1.5f32..1.5).prop_filter("too close to vertical", |(_, pitch, _)| {
+      (pitch.abs() - FRAC_PI_2).abs() > 0.01
+   })
+}
+
+/// Pitch limits with `min <= max`.
+fn any_limits() -> impl Strategy<Value = (f32, f32)> {
+   (-FRAC_PI_2..FRAC_PI_2, -FRAC_PI_2..FRAC_PI_2).prop_map(|(a, b)| (a.min(b), a.max(b)))
+}
+
+fn from_angles(yaw: f32, pitch: f32, roll: f32) -> Quat {
+   Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch) * Quat::from_rotation_z(roll)
+}
+
+fn pitch(rotation: Quat) -> f32 {
+   rotation.mul_vec3(-Vec3::Z).y.clamp(-1.0, 1.0).asin()
+}
+
+fn same_rotation(a: Quat, b: Quat) -> bool {
+   a.dot(b).abs() > 1.0 - EPSILON
+}
+
+proptest! {
+   #[test]
+   fn result_is_within_limits(rotation in any_rotation(), limits in any_limits()) {
+      let limited = limit_pitch_to(rotation, limits);
+      let pitch = pitch(limited);
+      prop_assert!(pitch >= limits.0 - EPSILON && pitch <= limits.1 + EPSILON);
+      prop_assert!((limited.length() - 1.0).abs() < EPSILON);
+   }
+
+   #[test]
+   fn keeps_yaw_and_roll(
+      (yaw, pitch_angle, roll) in any_angles(),
+      limits in any_limits(),
+   ) {
+      let rotation = from_angles(yaw, pitch_angle, roll);
+      let expected = from_angles(yaw, pitch_angle.clamp(limits.0, limits.1), roll);
+      let limited = limit_pitch_to(rotation, limits);
+      prop_assert!(same_rotation(limited, expected), "{:?} != {:?}", limited, expected);
+   }
+
+   #[test]
+   fn rotation_within_limits_is_untouched(
+      (yaw, pitch_angle, roll) in any_angles(),
+      limits in any_limits(),
+   ) {
+      prop_assume!(pitch_angle > limits.0 + EPSILON && pitch_angle < limits.1 - EPSILON);
+      let rotation = from_angles(yaw, pitch_angle, roll);
+      prop_assert_eq!(limit_pitch_to(rotation, limits), rotation);
+   }
+
+   #[test]
+   fn is_idempotent(rotation in any_rotation(), limits in any_limits()) {
+      let once = limit_pitch_to(rotation, limits);
+      prop_assert!(same_rotation(limit_pitch_to(once, limits), once));
+   }
+
+   #[test]
+   fn pitching_over_the_top_stops_at_vertical((yaw, pitch_angle, roll) in any_angles()) {
+      prop_assume!(pitch_angle.abs() > FRAC_PI_2);
+      let limited = limit_pitch(from_angles(yaw, pitch_angle, roll));
+      let expected = from_angles(yaw, FRAC_PI_2.copysign(pitch_angle), roll);
+      prop_assert!(same_rotation(limited, expected), "{:?} != {:?}", limited, expected);
+   }
+}
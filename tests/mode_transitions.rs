@@ -0,0 +1,36 @@
+//! Which [`CameraMode`] changes [`MovableCamera::set_mode`] allows.
+
+use bevy::prelude::*;
+use bevy_flying_cam::{CameraMode, MovableCamera};
+
+#[test]
+fn free_flight_leads_to_every_mode_and_back() {
+   let target = Entity::from_raw(7);
+   for mode in [
+      CameraMode::Orbit,
+      CameraMode::Chase {
+         target,
+         offset: Vec3::Z,
+      },
+      CameraMode::Flight,
+      CameraMode::Map,
+      CameraMode::Walk,
+      CameraMode::Path,
+      CameraMode::Disabled,
+   ] {
+      let mut cam = MovableCamera::default();
+      assert_eq!(cam.set_mode(mode), Ok(()));
+      assert_eq!(cam.set_mode(CameraMode::Free), Ok(()));
+   }
+}
+
+#[test]
+fn other_modes_only_lead_back_to_free_flight() {
+   let mut cam = MovableCamera::default().with_mode(CameraMode::TopDown);
+   assert!(cam.set_mode(CameraMode::Map).is_err());
+   assert!(cam.set_mode(CameraMode::Orbit).is_err());
+   assert_eq!(cam.mode(), CameraMode::TopDown);
+   assert_eq!(cam.set_mode(CameraMode::Disabled), Ok(()));
+   assert!(cam.set_mode(CameraMode::Walk).is_err());
+   assert_eq!(cam.set_mode(CameraMode::Free), Ok(()));
+}
@@ -0,0 +1,166 @@
+//! Round trips of camera rigs through [`RigSpace`] under a transformed
+//! parent.
+
+use bevy::prelude::*;
+use bevy_flying_cam::RigSpace;
+
+const EPSILON: f32 = 1e-4;
+
+#[test]
+fn to_local_undoes_to_world() {
+   let frame = GlobalTransform {
+      translation: Vec3::new(3.0, -2.0, 7.5),
+      rotation: Quat::from_euler(EulerRot::YXZ, 0.7, -0.3, 0.2),
+      scale: Vec3::new(2.0, 0.5, 1.5),
+   };
+   let root = Transform {
+      translation: Vec3::new(-1.0, 4.0, 0.5),
+      rotation: Quat::from_rotation_y(1.2),
+      scale: Vec3::splat(0.25),
+   };
+   let child = Transform::from_xyz(0.5, 1.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y);
+   let space = RigSpace::new(Some(&frame), &root);
+   assert!(!space.is_identity());
+
+   let (world_root, world_child) = space.to_world(&root, &child);
+   let (local_root, local_child) = space.to_local(&world_root, &world_child);
+   assert!(local_root
+      .translation
+      .abs_diff_eq(root.translation, EPSILON));
+   assert!(local_root.rotation.abs_diff_eq(root.rotation, EPSILON));
+   assert!(local_root.scale.abs_diff_eq(root.scale, EPSILON));
+   assert!(local_child
+      .translation
+      .abs_diff_eq(child.translation, EPSILON));
+   assert!(local_child.rotation.abs_diff_eq(child.rotation, EPSILON));
+}
+
+#[test]
+fn to_world_places_the_pivot_in_world_space() {
+   let frame = GlobalTransform {
+      translation: Vec3::new(10.0, 0.0, 0.0),
+      rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+      scale: Vec3::splat(2.0),
+   };
+   let root = Transform::from_xyz(1.0, 0.0, 0.0);
+   let space = RigSpace::new(Some(&frame), &root);
+   let (world_root, _) = space.to_world(&root, &Transform::identity());
+   assert!(world_root
+      .translation
+      .abs_diff_eq(frame.mul_vec3(root.translation), EPSILON));
+}
+
+/// Spawns a camera under a turned, scaled and moved entity, so its rig is
+/// built there.
+#[cfg(feature = "test_utils")]
+fn spawn_under_frame(
+   test: &mut bevy_flying_cam::test_utils::CameraTestApp,
+   transform: Transform,
+) -> Entity {
+   let camera = test.spawn_camera(transform);
+   let frame = Transform {
+      translation: Vec3::new(5.0, 1.0, -3.0),
+      rotation: Quat::from_euler(EulerRot::YXZ, 0.8, 0.2, -0.1),
+      scale: Vec3::splat(2.0),
+   };
+   test
+      .app
+      .world
+      .spawn()
+      .insert_bundle((frame, GlobalTransform::from(frame)))
+      .push_children(&[camera]);
+   camera
+}
+
+#[cfg(feature = "test_utils")]
+#[test]
+fn chase_follows_the_target_under_a_turned_parent() {
+   use bevy_flying_cam::{test_utils::CameraTestApp, CameraMode, MovableCamera};
+
+   let mut test = CameraTestApp::new();
+   let camera = spawn_under_frame(&mut test, Transform::from_xyz(0.0, 0.0, 4.0));
+   let target = Transform::from_xyz(-2.0, 0.5, 1.0).with_rotation(Quat::from_rotation_y(0.6));
+   let target_entity = test
+      .app
+      .world
+      .spawn()
+      .insert_bundle((target, GlobalTransform::from(target)))
+      .id();
+   let offset = Vec3::new(0.0, 2.0, 6.0);
+   test
+      .app
+      .world
+      .get_mut::<MovableCamera>(camera)
+      .expect("camera is movable")
+      .set_mode(CameraMode::Chase {
+         target: target_entity,
+         offset,
+      })
+      .expect("free cameras can chase");
+   test.advance(600);
+
+   test.assert_camera_at(
+      camera,
+      target.translation + target.rotation.mul_vec3(offset),
+      1e-2,
+   );
+}
+
+#[cfg(feature = "test_utils")]
+#[test]
+fn bookmarks_recall_the_world_pose_under_a_turned_parent() {
+   use bevy_flying_cam::{
+      test_utils::CameraTestApp, CameraBookmarks, FlyingCamAction, RecallCameraBookmark,
+      SaveCameraBookmark,
+   };
+
+   let mut test = CameraTestApp::new();
+   let camera = spawn_under_frame(&mut test, Transform::from_xyz(1.0, 0.0, 3.0));
+   test.advance(1);
+   let saved = test.camera_pose(camera);
+   test
+      .app
+      .world
+      .get_resource_mut::<Events<SaveCameraBookmark>>()
+      .expect("the plugin adds bookmark events")
+      .send(SaveCameraBookmark { camera, slot: 1 });
+   test.advance(1);
+   let bookmark = *test
+      .app
+      .world
+      .get_resource::<CameraBookmarks>()
+      .expect("the plugin adds bookmarks")
+      .get(1)
+      .expect("slot 1 was saved");
+   assert!(bookmark
+      .eye
+      .translation
+      .abs_diff_eq(saved.translation, EPSILON));
+   assert!(bookmark.eye.rotation.abs_diff_eq(saved.rotation, EPSILON));
+
+   test.press(camera, FlyingCamAction::Forward);
+   test.advance(30);
+   test.release(camera, FlyingCamAction::Forward);
+   test.advance(1);
+   assert!(
+      test
+         .camera_pose(camera)
+         .translation
+         .distance(saved.translation)
+         > 0.1
+   );
+
+   test
+      .app
+      .world
+      .get_resource_mut::<Events<RecallCameraBookmark>>()
+      .expect("the plugin adds bookmark events")
+      .send(RecallCameraBookmark {
+         camera: Some(camera),
+         slot: 1,
+      });
+   test.advance(120);
+
+   test.assert_camera_at(camera, saved.translation, 1e-3);
+   test.assert_camera_facing(camera, saved.forward(), 1e-3);
+}
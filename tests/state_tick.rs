@@ -0,0 +1,59 @@
+//! Determinism of the ECS-free camera tick.
+
+use bevy::prelude::*;
+use bevy_flying_cam::{FlyingCamFrameInput, FlyingCamState, MovableCameraParams};
+
+/// A few seconds of flying, turning, zooming and toggling slow mode, with
+/// uneven time steps.
+fn script() -> Vec<(FlyingCamFrameInput, f32)> {
+   (0..240)
+      .map(|frame| {
+         let input = FlyingCamFrameInput {
+            movement: Vec3::new(
+               (frame % 7) as f32 / 7.0 - 0.5,
+               0.0,
+               if frame < 120 { 1.0 } else { -1.0 },
+            ),
+            look: Vec2::new((frame % 5) as f32 - 2.0, (frame % 3) as f32 - 1.0),
+            scroll: if frame % 40 == 0 { 1.0 } else { 0.0 },
+            toggle_slow: frame == 90 || frame == 180,
+         };
+         let dt = 1.0 / 60.0 + (frame % 4) as f32 * 0.002;
+         (input, dt)
+      })
+      .collect()
+}
+
+fn run(script: &[(FlyingCamFrameInput, f32)]) -> Vec<Transform> {
+   let mut state = FlyingCamState::new(
+      MovableCameraParams::default(),
+      Transform::from_xyz(0.0, 1.0, 5.0),
+   );
+   script
+      .iter()
+      .map(|(input, dt)| state.tick(*input, *dt))
+      .collect()
+}
+
+#[test]
+fn same_inputs_give_the_same_poses() {
+   let script = script();
+   assert_eq!(run(&script), run(&script));
+}
+
+#[test]
+fn a_cloned_state_carries_on_the_same_way() {
+   let script = script();
+   let (first, rest) = script.split_at(100);
+   let mut state = FlyingCamState::new(
+      MovableCameraParams::default(),
+      Transform::from_xyz(0.0, 1.0, 5.0),
+   );
+   for (input, dt) in first {
+      state.tick(*input, *dt);
+   }
+   let mut replay = state.clone();
+   for (input, dt) in rest {
+      assert_eq!(state.tick(*input, *dt), replay.tick(*input, *dt));
+   }
+}
@@ -0,0 +1,202 @@
+//! Camera behavior driven through the headless test harness.
+
+use bevy::prelude::*;
+use bevy_flying_cam::{
+   test_utils::{CameraTestApp, TEST_TIMESTEP},
+   FlyingCamAction, FlyingCamFrameInput, FlyingCamState, FocusFallback, MovableCamera,
+   MovableCameraParams, MovableCameraRoot,
+};
+
+#[test]
+fn forward_flies_along_the_view() {
+   let mut test = CameraTestApp::new();
+   let camera = test.spawn_camera(Transform::default());
+   test.press(camera, FlyingCamAction::Forward);
+   test.advance(30);
+
+   let pose = test.camera_pose(camera);
+   assert!(
+      pose.translation.z < -0.1,
+      "camera at {:?}",
+      pose.translation
+   );
+   assert!(pose.translation.x.abs() < 1e-3 && pose.translation.y.abs() < 1e-3);
+   test.assert_camera_facing(camera, -Vec3::Z, 1e-3);
+}
+
+#[test]
+fn released_keys_stop_the_camera() {
+   let mut test = CameraTestApp::new();
+   let camera = test.spawn_camera(Transform::default());
+   test.press(camera, FlyingCamAction::Forward);
+   test.advance(10);
+   test.release(camera, FlyingCamAction::Forward);
+   test.advance(1);
+
+   let stopped = test.camera_pose(camera).translation;
+   test.advance(10);
+   test.assert_camera_at(camera, stopped, 1e-4);
+}
+
+#[test]
+fn dragging_with_secondary_turns_in_place() {
+   let mut test = CameraTestApp::new();
+   let camera = test.spawn_camera(Transform::from_xyz(1.0, 2.0, 3.0));
+   test.press(camera, FlyingCamAction::Secondary);
+   test.move_mouse(Vec2::new(100.0, 0.0));
+   test.advance(1);
+
+   let forward = test.camera_pose(camera).forward();
+   assert!(
+      forward.angle_between(-Vec3::Z) > 0.01,
+      "still facing {:?}",
+      forward
+   );
+   test.assert_camera_at(camera, Vec3::new(1.0, 2.0, 3.0), 1e-4);
+}
+
+#[test]
+fn double_press_of_focus_resets_the_orbit_while_flying_to_it() {
+   let mut test = CameraTestApp::new();
+   let cam_params = MovableCameraParams {
+      focus_fallback: FocusFallback::Point(Vec3::ZERO),
+      ..Default::default()
+   };
+   let (reset_distance, frames) = (
+      cam_params.focus_reset_distance,
+      (cam_params.focus_duration / TEST_TIMESTEP) as usize,
+   );
+   test.app.insert_resource(cam_params);
+   let camera =
+      test.spawn_camera(Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y));
+
+   // The first press starts the flight to the pivot, and the second comes
+   // well within the reset window, before the flight is over
+   test.press(camera, FlyingCamAction::Focus);
+   test.advance(1);
+   test.release(camera, FlyingCamAction::Focus);
+   test.advance(1);
+   test.press(camera, FlyingCamAction::Focus);
+   test.advance(1);
+   test.release(camera, FlyingCamAction::Focus);
+   test.advance(frames * 2);
+
+   test.assert_camera_at(camera, Vec3::new(0.0, 0.0, reset_distance), 1e-3);
+   test.assert_camera_facing(camera, -Vec3::Z, 1e-3);
+}
+
+#[test]
+fn rigs_are_built_under_the_camera_parent_and_taken_down_again() {
+   let mut test = CameraTestApp::new();
+   let camera = test.spawn_camera(Transform::from_xyz(0.0, 1.0, 2.0));
+   let holder = test
+      .app
+      .world
+      .spawn()
+      .insert_bundle((
+         Transform::from_xyz(4.0, 0.0, 0.0),
+         GlobalTransform::identity(),
+      ))
+      .push_children(&[camera])
+      .id();
+   test.advance(1);
+
+   let root = test.app.world.get::<Parent>(camera).unwrap().0;
+   assert_eq!(
+      test
+         .app
+         .world
+         .get::<MovableCameraRoot>(root)
+         .map(|root| root.camera),
+      Some(camera)
+   );
+   assert_eq!(test.app.world.get::<Parent>(root).unwrap().0, holder);
+   test.assert_camera_at(camera, Vec3::new(4.0, 1.0, 2.0), 1e-4);
+
+   test.app.world.entity_mut(camera).remove::<MovableCamera>();
+   test.advance(1);
+
+   assert_eq!(test.app.world.get::<Parent>(camera).unwrap().0, holder);
+   assert!(test.app.world.get_entity(root).is_none());
+   test.assert_camera_at(camera, Vec3::new(4.0, 1.0, 2.0), 1e-4);
+}
+
+#[test]
+fn free_flight_matches_the_ecs_free_tick() {
+   let mut test = CameraTestApp::new();
+   let start = Transform::from_xyz(0.0, 1.0, 5.0);
+   let camera = test.spawn_camera(start);
+   let mut state = FlyingCamState::new(MovableCameraParams::default(), start);
+
+   // Each step holds some keys for a few frames, with the mouse dragging
+   // while `Secondary` is held and the speed toggled as it starts
+   let steps: [(&[FlyingCamAction], Vec2, f32, usize); 6] = [
+      (&[FlyingCamAction::Forward], Vec2::ZERO, 0.0, 30),
+      (
+         &[FlyingCamAction::Forward, FlyingCamAction::Left],
+         Vec2::ZERO,
+         0.0,
+         20,
+      ),
+      (
+         &[FlyingCamAction::Secondary],
+         Vec2::new(12.0, -5.0),
+         0.0,
+         15,
+      ),
+      (&[FlyingCamAction::AdjustSpeed], Vec2::ZERO, 0.0, 1),
+      (
+         &[FlyingCamAction::Up, FlyingCamAction::Back],
+         Vec2::ZERO,
+         1.0,
+         25,
+      ),
+      (&[], Vec2::ZERO, 0.0, 30),
+   ];
+   for (actions, look, scroll, frames) in steps {
+      for frame in 0..frames {
+         if frame == 0 {
+            for action in actions {
+               test.press(camera, *action);
+            }
+         }
+         if look != Vec2::ZERO {
+            test.move_mouse(look);
+         }
+         let scroll = if frame == 0 { scroll } else { 0.0 };
+         if scroll != 0.0 {
+            test.scroll(scroll);
+         }
+         test.advance(1);
+
+         let held = |action| actions.contains(&action);
+         let axis = |negative, positive| match (held(negative), held(positive)) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+         };
+         let pose = state.tick(
+            FlyingCamFrameInput {
+               movement: Vec3::new(
+                  axis(FlyingCamAction::Right, FlyingCamAction::Left),
+                  axis(FlyingCamAction::Down, FlyingCamAction::Up),
+                  axis(FlyingCamAction::Back, FlyingCamAction::Forward),
+               ),
+               look: if held(FlyingCamAction::Secondary) {
+                  look
+               } else {
+                  Vec2::ZERO
+               },
+               scroll,
+               toggle_slow: frame == 0 && held(FlyingCamAction::AdjustSpeed),
+            },
+            TEST_TIMESTEP,
+         );
+         test.assert_camera_at(camera, pose.translation, 1e-4);
+         test.assert_camera_facing(camera, pose.forward(), 1e-3);
+      }
+      for action in actions {
+         test.release(camera, *action);
+      }
+   }
+}